@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE posts DROP COLUMN author_id;
+DROP TABLE sessions;
+DROP TABLE users;
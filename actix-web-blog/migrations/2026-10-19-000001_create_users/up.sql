@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS users (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  username VARCHAR NOT NULL UNIQUE,
+  password_hash VARCHAR NOT NULL,
+  is_admin BOOLEAN NOT NULL DEFAULT 'f'
+);
+
+-- ログインで発行したトークンのハッシュ。expires_at は UNIX 秒
+CREATE TABLE IF NOT EXISTS sessions (
+  token_hash VARCHAR NOT NULL PRIMARY KEY,
+  user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  expires_at BIGINT NOT NULL
+);
+
+ALTER TABLE posts ADD COLUMN author_id INTEGER REFERENCES users(id);
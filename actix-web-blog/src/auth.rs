@@ -0,0 +1,147 @@
+use crate::error::ApiError;
+use crate::repository::{Post, Repository, User};
+use actix_web::{dev::Payload, http::header::AUTHORIZATION, web, FromRequest, HttpRequest};
+use argon2::{password_hash::SaltString, Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ログイン中のユーザー。ハンドラの引数に書くと未ログインのリクエストは 401 になる
+pub struct AuthUser(pub User);
+
+impl AuthUser {
+    // 管理者か、その記事を書いた本人なら編集できる
+    pub fn can_edit(&self, post: &Post) -> bool {
+        self.0.is_admin || post.author_id() == Some(self.0.id)
+    }
+
+    pub fn ensure_can_edit(&self, post: &Post) -> Result<(), ApiError> {
+        if self.can_edit(post) {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden)
+        }
+    }
+}
+
+impl FromRequest for AuthUser {
+    type Error = ApiError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let token = bearer_token(req);
+        let repo = req.app_data::<web::Data<Repository>>().cloned();
+        Box::pin(async move {
+            let (token, repo) = match (token, repo) {
+                (Some(token), Some(repo)) => (token, repo),
+                _ => return Err(ApiError::Unauthorized),
+            };
+            repo.find_user_by_token(hash_token(&token), now())
+                .await?
+                .map(AuthUser)
+                .ok_or(ApiError::Unauthorized)
+        })
+    }
+}
+
+pub fn bearer_token(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::to_string)
+}
+
+// UNIX 秒
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+pub fn hash_password(password: &str) -> Result<String, ApiError> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| ApiError::Other(anyhow::anyhow!(e.to_string())))?;
+    Ok(hash.to_string())
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    match PasswordHash::new(password_hash) {
+        Ok(hash) => Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+// 存在しないユーザーのログインでも照合にかかる時間を揃えるためのハッシュ。
+// 本物と同じパラメータで作るので、応答時間からユーザー名の有無を推測できない
+pub fn dummy_password_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| hash_password("dummy password").unwrap_or_default())
+}
+
+// 256 bit の乱数を 16 進文字列にしてトークンにする。DB にはハッシュだけを保存する
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: i32, is_admin: bool) -> AuthUser {
+        AuthUser(User::new(id, &format!("user{}", id), is_admin))
+    }
+
+    #[test]
+    fn test_can_edit() {
+        let post = Post::new(1, Some(1));
+        assert!(user(1, false).can_edit(&post));
+        assert!(!user(2, false).can_edit(&post));
+        assert!(user(2, true).can_edit(&post));
+        assert!(matches!(
+            user(2, false).ensure_can_edit(&post),
+            Err(ApiError::Forbidden)
+        ));
+        // 作者のいない古い記事は管理者だけ
+        assert!(!user(1, false).can_edit(&Post::new(2, None)));
+    }
+
+    #[test]
+    fn test_password() {
+        let hash = hash_password("password123").unwrap();
+        assert!(verify_password("password123", &hash));
+        assert!(!verify_password("password124", &hash));
+        assert!(!verify_password("password123", "plain"));
+        assert!(PasswordHash::new(dummy_password_hash()).is_ok());
+    }
+
+    #[test]
+    fn test_token() {
+        let token = generate_token();
+        assert_eq!(64, token.len());
+        assert_ne!(token, generate_token());
+        assert_ne!(token, hash_token(&token));
+    }
+
+    #[actix_web::test]
+    async fn test_missing_token() {
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let res = AuthUser::from_request(&req, &mut Payload::None).await;
+        assert!(matches!(res, Err(ApiError::Unauthorized)));
+    }
+}
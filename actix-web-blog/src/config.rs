@@ -0,0 +1,225 @@
+use anyhow::Context;
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+// JSON ボディの上限（バイト）の既定値
+pub const DEFAULT_BODY_LIMIT: usize = 16 * 1024;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum ConfigError {
+    #[error("{0} is not set")]
+    Missing(&'static str),
+    #[error("{key} must be {expected}, got {value:?}")]
+    Invalid {
+        key: &'static str,
+        expected: &'static str,
+        value: String,
+    },
+    #[error("TLS_CERT and TLS_KEY must be set together")]
+    IncompleteTls,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+// サーバーの設定。起動時に環境変数から一度だけ読む
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    pub database_url: String,
+    pub host: String,
+    pub port: u16,
+    // None なら CPU の数だけワーカーを立てる
+    pub workers: Option<usize>,
+    pub pool_size: u32,
+    // プールから接続を取るときの待ち時間。起動時の接続確認にも使う
+    pub pool_timeout: Duration,
+    // None なら平文の HTTP で待ち受ける
+    pub tls: Option<TlsConfig>,
+    // 停止シグナルを受けてから処理中のリクエストを待つ秒数
+    pub shutdown_timeout: u64,
+    pub body_limit: usize,
+    // yew-blog の dist。指定するとフロントエンドも同じサーバーから配信する
+    pub static_dir: Option<PathBuf>,
+}
+
+impl ServerConfig {
+    // テストしやすいよう環境変数の取得関数を受け取る
+    pub fn from_env<F>(var: F) -> Result<Self, ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let database_url = var("DATABASE_URL")
+            .filter(|url| !url.is_empty())
+            .ok_or(ConfigError::Missing("DATABASE_URL"))?;
+        let tls = match (var("TLS_CERT"), var("TLS_KEY")) {
+            (Some(cert), Some(key)) => Some(TlsConfig {
+                cert_path: cert.into(),
+                key_path: key.into(),
+            }),
+            (None, None) => None,
+            _ => return Err(ConfigError::IncompleteTls),
+        };
+        let workers = match var("WORKERS") {
+            Some(_) => Some(positive(&var, "WORKERS", 0)?),
+            None => None,
+        };
+
+        Ok(Self {
+            database_url,
+            host: var("HOST").unwrap_or_else(|| "0.0.0.0".to_string()),
+            port: positive(&var, "PORT", 8080)?,
+            workers,
+            pool_size: positive(&var, "DATABASE_POOL_SIZE", 10)?,
+            pool_timeout: Duration::from_secs(positive(&var, "DATABASE_POOL_TIMEOUT", 30)?),
+            tls,
+            shutdown_timeout: parse(&var, "SHUTDOWN_TIMEOUT", 30, "a number of seconds")?,
+            body_limit: positive(&var, "BODY_LIMIT", DEFAULT_BODY_LIMIT)?,
+            static_dir: var("STATIC_DIR")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from),
+        })
+    }
+}
+
+fn parse<F, T>(
+    var: &F,
+    key: &'static str,
+    default: T,
+    expected: &'static str,
+) -> Result<T, ConfigError>
+where
+    F: Fn(&str) -> Option<String>,
+    T: FromStr,
+{
+    match var(key) {
+        Some(value) => value.parse().map_err(|_| ConfigError::Invalid {
+            key,
+            expected,
+            value,
+        }),
+        None => Ok(default),
+    }
+}
+
+fn positive<F, T>(var: &F, key: &'static str, default: T) -> Result<T, ConfigError>
+where
+    F: Fn(&str) -> Option<String>,
+    T: FromStr + Default + PartialOrd,
+{
+    let expected = "a positive integer";
+    let value = parse(var, key, default, expected)?;
+    if value <= T::default() {
+        return Err(ConfigError::Invalid {
+            key,
+            expected,
+            value: var(key).unwrap_or_default(),
+        });
+    }
+    Ok(value)
+}
+
+impl TlsConfig {
+    // PEM の証明書チェーンと秘密鍵を読み込む
+    pub fn load(&self) -> anyhow::Result<rustls::ServerConfig> {
+        let certs = CertificateDer::pem_file_iter(&self.cert_path)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .with_context(|| format!("failed to read TLS_CERT {}", self.cert_path.display()))?;
+        if certs.is_empty() {
+            anyhow::bail!("no certificate found in {}", self.cert_path.display());
+        }
+        let key = PrivateKeyDer::from_pem_file(&self.key_path)
+            .with_context(|| format!("failed to read TLS_KEY {}", self.key_path.display()))?;
+
+        rustls::ServerConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("TLS_CERT and TLS_KEY do not match")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn from(pairs: &[(&str, &str)]) -> Result<ServerConfig, ConfigError> {
+        let env: HashMap<String, String> = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        ServerConfig::from_env(|key| env.get(key).cloned())
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config = from(&[("DATABASE_URL", "posts.db")]).unwrap();
+        assert_eq!(("0.0.0.0", 8080), (config.host.as_str(), config.port));
+        assert_eq!(None, config.workers);
+        assert_eq!(10, config.pool_size);
+        assert_eq!(Duration::from_secs(30), config.pool_timeout);
+        assert_eq!(None, config.tls);
+        assert_eq!(30, config.shutdown_timeout);
+        assert_eq!(DEFAULT_BODY_LIMIT, config.body_limit);
+        assert_eq!(None, config.static_dir);
+    }
+
+    #[test]
+    fn test_overrides() {
+        let config = from(&[
+            ("DATABASE_URL", "posts.db"),
+            ("HOST", "127.0.0.1"),
+            ("PORT", "8443"),
+            ("WORKERS", "2"),
+            ("DATABASE_POOL_SIZE", "4"),
+            ("TLS_CERT", "cert.pem"),
+            ("TLS_KEY", "key.pem"),
+            ("SHUTDOWN_TIMEOUT", "0"),
+            ("STATIC_DIR", "../yew-blog/dist"),
+        ])
+        .unwrap();
+        assert_eq!(8443, config.port);
+        assert_eq!(Some(2), config.workers);
+        assert_eq!(4, config.pool_size);
+        assert_eq!(PathBuf::from("key.pem"), config.tls.unwrap().key_path);
+        assert_eq!(0, config.shutdown_timeout);
+        assert_eq!(Some(PathBuf::from("../yew-blog/dist")), config.static_dir);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Err(ConfigError::Missing("DATABASE_URL")), from(&[]));
+        assert_eq!(
+            "PORT must be a positive integer, got \"http\"",
+            from(&[("DATABASE_URL", "x"), ("PORT", "http")])
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(matches!(
+            from(&[("DATABASE_URL", "x"), ("WORKERS", "0")]),
+            Err(ConfigError::Invalid { key: "WORKERS", .. })
+        ));
+        assert_eq!(
+            Err(ConfigError::IncompleteTls),
+            from(&[("DATABASE_URL", "x"), ("TLS_CERT", "cert.pem")])
+        );
+    }
+
+    #[test]
+    fn test_missing_tls_files() {
+        let tls = TlsConfig {
+            cert_path: "/nonexistent/cert.pem".into(),
+            key_path: "/nonexistent/key.pem".into(),
+        };
+        let e = tls.load().unwrap_err();
+        assert!(e.to_string().contains("TLS_CERT /nonexistent/cert.pem"));
+    }
+}
@@ -1,33 +1,201 @@
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde_json::json;
+use std::collections::BTreeMap;
+use validator::ValidationErrors;
+
 #[derive(thiserror::Error, Debug)]
 pub enum ApiError {
-    #[error("Post not found")]
+    #[error("Not found")]
     #[allow(dead_code)] // 使ってるのに使ってないって言われるからとりあえず回避
     NotFound,
+    #[error("Authentication required")]
+    Unauthorized,
+    #[error("Forbidden")]
+    Forbidden,
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("Payload too large")]
+    PayloadTooLarge,
+    // 項目名 → その項目のエラーメッセージ
+    #[error("Validation failed")]
+    Validation(BTreeMap<String, Vec<String>>),
+    // DB に接続できない・プールが空かない。時間をおけば成功しうる
+    #[error(transparent)]
+    Unavailable(anyhow::Error),
     #[error(transparent)]
     Other(anyhow::Error),
 }
 
-macro_rules! impl_from_trait {
-    ($etype: ty) => {
-        impl From<$etype> for ApiError {
-            fn from(e: $etype) -> Self {
-                ApiError::Other(anyhow::anyhow!(e))
+impl ApiError {
+    fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound => "not_found",
+            ApiError::Unauthorized => "unauthorized",
+            ApiError::Forbidden => "forbidden",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::PayloadTooLarge => "payload_too_large",
+            ApiError::Validation(_) => "validation",
+            ApiError::Unavailable(_) => "unavailable",
+            ApiError::Other(_) => "internal",
+        }
+    }
+
+    // 内部エラーの詳細はクライアントに返さず、ログで request_id から辿る
+    fn message(&self) -> String {
+        match self {
+            ApiError::Unavailable(_) => "service temporarily unavailable".to_string(),
+            ApiError::Other(_) => "internal server error".to_string(),
+            e => e.to_string(),
+        }
+    }
+
+    pub fn to_response(&self, request_id: Option<&str>) -> HttpResponse {
+        let mut body = json!({ "error": self.code(), "message": self.message() });
+        if let ApiError::Validation(fields) = self {
+            body["fields"] = json!(fields);
+        }
+        if let Some(id) = request_id {
+            body["request_id"] = json!(id);
+        }
+        HttpResponse::build(self.status_code()).json(body)
+    }
+
+    // サーバー側の問題だけ error にして、元のエラーも残す
+    pub fn log(&self) {
+        match self {
+            ApiError::Unavailable(e) | ApiError::Other(e) => {
+                tracing::error!(error = ?e, status = %self.status_code(), "request failed")
+            }
+            e => tracing::info!(error = %e, status = %self.status_code(), "request rejected"),
+        }
+    }
+}
+
+impl From<DieselError> for ApiError {
+    fn from(e: DieselError) -> Self {
+        match e {
+            DieselError::NotFound => ApiError::NotFound,
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                ApiError::Conflict("resource already exists".to_string())
             }
+            e => ApiError::Other(anyhow::anyhow!(e)),
         }
-    };
+    }
 }
 
-impl_from_trait!(diesel::r2d2::Error);
-impl_from_trait!(diesel::r2d2::PoolError);
-impl_from_trait!(diesel::result::Error);
-impl_from_trait!(actix_web::error::BlockingError);
+// プールから接続を取れない（タイムアウト・接続失敗）ときは 503 にする
+impl From<diesel::r2d2::PoolError> for ApiError {
+    fn from(e: diesel::r2d2::PoolError) -> Self {
+        ApiError::Unavailable(anyhow::anyhow!(e))
+    }
+}
 
-use actix_web::{HttpResponse, ResponseError};
+impl From<diesel::r2d2::Error> for ApiError {
+    fn from(e: diesel::r2d2::Error) -> Self {
+        ApiError::Unavailable(anyhow::anyhow!(e))
+    }
+}
+
+impl From<actix_web::error::BlockingError> for ApiError {
+    fn from(e: actix_web::error::BlockingError) -> Self {
+        ApiError::Other(anyhow::anyhow!(e))
+    }
+}
+
+// 構造体全体の検証（schema）のエラーは "__all__" に入る
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        let fields = errors
+            .field_errors()
+            .into_iter()
+            .map(|(field, errors)| {
+                let messages = errors
+                    .iter()
+                    .map(|e| match &e.message {
+                        Some(message) => message.to_string(),
+                        None => e.code.to_string(),
+                    })
+                    .collect();
+                (field.to_string(), messages)
+            })
+            .collect();
+        ApiError::Validation(fields)
+    }
+}
+
+// request_id はミドルウェアがレスポンスを作り直すときに付ける
 impl ResponseError for ApiError {
-    fn error_response(&self) -> HttpResponse {
+    fn status_code(&self) -> StatusCode {
         match self {
-            ApiError::NotFound => HttpResponse::NotFound().finish(),
-            ApiError::Other(_) => HttpResponse::ServiceUnavailable().finish(),
+            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden => StatusCode::FORBIDDEN,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    fn error_response(&self) -> HttpResponse {
+        self.to_response(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::MessageBody;
+
+    fn db_error(kind: DatabaseErrorKind) -> ApiError {
+        DieselError::DatabaseError(kind, Box::new("constraint failed".to_string())).into()
+    }
+
+    fn body_json(res: HttpResponse) -> serde_json::Value {
+        let bytes = res.into_body().try_into_bytes().unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_classify_diesel_errors() {
+        assert!(matches!(
+            ApiError::from(DieselError::NotFound),
+            ApiError::NotFound
+        ));
+        assert!(matches!(
+            db_error(DatabaseErrorKind::UniqueViolation),
+            ApiError::Conflict(_)
+        ));
+        assert_eq!(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            db_error(DatabaseErrorKind::ForeignKeyViolation).status_code()
+        );
+        assert_eq!(
+            StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Unavailable(anyhow::anyhow!("timed out")).status_code()
+        );
+    }
+
+    #[test]
+    fn test_json_body() {
+        let body = body_json(ApiError::Conflict("taken".to_string()).to_response(Some("abc")));
+        assert_eq!(
+            json!({ "error": "conflict", "message": "taken", "request_id": "abc" }),
+            body
+        );
+
+        // 内部エラーの中身は返さない
+        let e = ApiError::Other(anyhow::anyhow!("disk I/O error"));
+        let body = body_json(e.error_response());
+        assert_eq!("internal", body["error"]);
+        assert_eq!("internal server error", body["message"]);
+        assert!(body.get("request_id").is_none());
+    }
 }
@@ -1,30 +1,53 @@
+mod auth;
+mod config;
 mod error;
+mod pagination;
 mod repository;
+mod request_id;
 mod schema;
+mod spa;
+mod validation;
 
-use actix_web::middleware::{Logger, NormalizePath};
-use actix_web::{web, App, HttpResponse, HttpServer};
+use actix_web::http::header::LINK;
+use actix_web::middleware::{from_fn, Logger, NormalizePath};
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use anyhow::Context;
+use auth::AuthUser;
+use config::ServerConfig;
 use error::ApiError;
-use repository::{NewPost, Repository};
+use repository::{ListPosts, NewPost, NewUser, PostChanges, ReplacePost, Repository};
+use serde::{Deserialize, Serialize};
+use std::process;
+use validation::{ValidatedJson, ValidatedQuery};
+use validator::Validate;
+
+// ログインで発行したトークンの有効期間（秒）
+const SESSION_TTL_SECS: i64 = 7 * 24 * 60 * 60;
 
 #[actix_web::post("/posts")]
 async fn create_post(
     repo: web::Data<Repository>,
-    new_post: web::Json<NewPost>,
+    user: AuthUser,
+    new_post: ValidatedJson<NewPost>,
 ) -> Result<HttpResponse, ApiError> {
     let new_post = new_post.into_inner();
-    // validate メソッドを実装して検証するパターン
-    if !new_post.validate() {
-        return Ok(HttpResponse::BadRequest().body("length of title is invalid."));
-    }
-    let post = repo.create_post(new_post).await?;
+    let post = repo.create_post(new_post, user.0.id).await?;
     Ok(HttpResponse::Ok().json(post))
 }
 
+// 本文は記事の配列のまま返し、ページ送りの情報はヘッダーで返す
 #[actix_web::get("/posts")]
-async fn list_posts(repo: web::Data<Repository>) -> Result<HttpResponse, ApiError> {
-    let posts = repo.list_posts().await?;
-    Ok(HttpResponse::Ok().json(posts))
+async fn list_posts(
+    req: HttpRequest,
+    repo: web::Data<Repository>,
+    query: ValidatedQuery<ListPosts>,
+) -> Result<HttpResponse, ApiError> {
+    let query = query.into_inner();
+    let page = repo.list_posts(query.clone()).await?;
+    let mut res = HttpResponse::Ok();
+    res.insert_header(("X-Total-Count", page.total))
+        .insert_header((LINK, pagination::link_header(&req, &query, &page)));
+    Ok(res.json(page.posts))
 }
 
 #[actix_web::get("/posts/{id}")]
@@ -37,21 +60,448 @@ async fn get_post(
     Ok(HttpResponse::Ok().json(post))
 }
 
+// 作者本人か管理者だけが変更できる
+async fn update_own_post(
+    repo: &Repository,
+    user: &AuthUser,
+    id: i32,
+    changes: PostChanges,
+) -> Result<HttpResponse, ApiError> {
+    let post = repo.get_post(id).await?;
+    user.ensure_can_edit(&post)?;
+    let post = repo.update_post(id, changes).await?;
+    Ok(HttpResponse::Ok().json(post))
+}
+
+#[actix_web::put("/posts/{id}")]
+async fn replace_post(
+    repo: web::Data<Repository>,
+    user: AuthUser,
+    path: web::Path<i32>,
+    replace: ValidatedJson<ReplacePost>,
+) -> Result<HttpResponse, ApiError> {
+    let changes = replace.into_inner().into();
+    update_own_post(&repo, &user, path.into_inner(), changes).await
+}
+
+#[actix_web::patch("/posts/{id}")]
+async fn update_post(
+    repo: web::Data<Repository>,
+    user: AuthUser,
+    path: web::Path<i32>,
+    changes: ValidatedJson<PostChanges>,
+) -> Result<HttpResponse, ApiError> {
+    update_own_post(&repo, &user, path.into_inner(), changes.into_inner()).await
+}
+
+#[actix_web::delete("/posts/{id}")]
+async fn delete_post(
+    repo: web::Data<Repository>,
+    user: AuthUser,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+    let post = repo.get_post(id).await?;
+    user.ensure_can_edit(&post)?;
+    repo.delete_post(id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// 誰でも author として登録できる。管理者は DB で is_admin を立てる
+#[actix_web::post("/users")]
+async fn create_user(
+    repo: web::Data<Repository>,
+    new_user: ValidatedJson<NewUser>,
+) -> Result<HttpResponse, ApiError> {
+    let new_user = new_user.into_inner();
+    // ハッシュ計算は重いのでワーカースレッドを塞がないよう別スレッドで行う
+    let password = new_user.password;
+    let password_hash = web::block(move || auth::hash_password(&password)).await??;
+    let user = repo.create_user(new_user.username, password_hash).await?;
+    Ok(HttpResponse::Created().json(user))
+}
+
+#[derive(Deserialize, Validate)]
+struct LoginRequest {
+    #[validate(length(min = 1, message = "username must not be empty"))]
+    username: String,
+    #[validate(length(min = 1, message = "password must not be empty"))]
+    password: String,
+}
+
+#[derive(Serialize)]
+struct LoginResponse {
+    // 以降のリクエストで Authorization: Bearer <token> として送る
+    token: String,
+    expires_at: i64,
+}
+
+#[actix_web::post("/login")]
+async fn login(
+    repo: web::Data<Repository>,
+    req: ValidatedJson<LoginRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let LoginRequest { username, password } = req.into_inner();
+    let user = repo.find_user_by_username(username).await?;
+    // ユーザーがいなくてもダミーのハッシュと照合して、同じだけ時間をかける
+    let hash = match &user {
+        Some(user) => user.password_hash.clone(),
+        None => auth::dummy_password_hash().to_string(),
+    };
+    let verified = web::block(move || auth::verify_password(&password, &hash)).await?;
+    let user = match user {
+        Some(user) if verified => user,
+        _ => return Err(ApiError::Unauthorized),
+    };
+    let token = auth::generate_token();
+    let expires_at = auth::now() + SESSION_TTL_SECS;
+    repo.create_session(user.id, auth::hash_token(&token), expires_at)
+        .await?;
+    Ok(HttpResponse::Ok().json(LoginResponse { token, expires_at }))
+}
+
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(web::PathConfig::default().error_handler(validation::path_error))
+        .service(create_user)
+        .service(login)
+        .service(create_post)
+        .service(list_posts)
+        .service(get_post)
+        .service(replace_post)
+        .service(update_post)
+        .service(delete_post);
+}
+
 #[actix_web::main]
-async fn main() -> std::io::Result<()> {
+async fn main() {
     tracing_subscriber::fmt().init();
-    let database_url = std::env::var("DATABASE_URL").unwrap();
-    let repo = web::Data::new(Repository::new(&database_url));
-    HttpServer::new(move || {
+    if let Err(e) = run().await {
+        eprintln!("failed to start server: {:#}", e);
+        process::exit(1);
+    }
+}
+
+async fn run() -> anyhow::Result<()> {
+    let config = ServerConfig::from_env(|key| std::env::var(key).ok())?;
+    let repo = Repository::new(&config.database_url, config.pool_size, config.pool_timeout)
+        .with_context(|| format!("failed to open database {}", config.database_url))?;
+    // 起動時にスキーマを最新にする。失敗したら待ち受けを始めない
+    let applied = repo
+        .run_migrations()
+        .with_context(|| format!("failed to run migrations on {}", config.database_url))?;
+    for version in applied {
+        tracing::info!(%version, "applied migration");
+    }
+
+    // index.html が無いと SPA のフォールバックが全部 404 になるので起動時に確かめる
+    if let Some(dir) = &config.static_dir {
+        if !dir.join("index.html").is_file() {
+            anyhow::bail!(
+                "STATIC_DIR {} does not contain index.html (run `trunk build` in yew-blog)",
+                dir.display()
+            );
+        }
+        tracing::info!(dir = %dir.display(), "serving frontend");
+    }
+
+    let repo = web::Data::new(repo);
+    let body_limit = config.body_limit;
+    let static_dir = config.static_dir.clone();
+    let mut server = HttpServer::new(move || {
         App::new()
             .app_data(repo.clone())
+            .app_data(web::JsonConfig::default().limit(body_limit))
+            .wrap(from_fn(request_id::request_id))
             .wrap(Logger::default())
             .wrap(NormalizePath::trim())
-            .service(create_post)
-            .service(list_posts)
-            .service(get_post)
+            .configure(|cfg| {
+                if let Some(dir) = &static_dir {
+                    spa::routes(cfg, dir);
+                }
+            })
+            .configure(routes)
     })
-    .bind(("0.0.0.0", 8080))?
-    .run()
-    .await
+    // SIGINT / SIGTERM を受けたら新しい接続を断り、処理中のリクエストを待ってから止まる
+    .shutdown_timeout(config.shutdown_timeout);
+    if let Some(workers) = config.workers {
+        server = server.workers(workers);
+    }
+
+    let addr = (config.host.as_str(), config.port);
+    let server = match &config.tls {
+        Some(tls) => server.bind_rustls_0_23(addr, tls.load()?),
+        None => server.bind(addr),
+    }
+    .with_context(|| format!("failed to bind {}:{}", config.host, config.port))?;
+    tracing::info!(
+        host = %config.host,
+        port = config.port,
+        tls = config.tls.is_some(),
+        "listening"
+    );
+    server.run().await.context("server stopped with an error")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{self as atest, TestRequest};
+    use serde_json::{json, Value};
+
+    // ハンドラと同じ構成のアプリを in-memory の DB で立てる
+    macro_rules! init_app {
+        ($repo:expr) => {
+            atest::init_service(
+                App::new()
+                    .app_data($repo.clone())
+                    .wrap(from_fn(request_id::request_id))
+                    .configure(routes),
+            )
+            .await
+        };
+    }
+
+    fn repo() -> web::Data<Repository> {
+        web::Data::new(Repository::in_memory())
+    }
+
+    // ユーザーとセッションを直接作り、Authorization ヘッダーの値を返す
+    async fn login_as(repo: &Repository, username: &str) -> String {
+        let user = repo
+            .create_user(username.to_string(), String::new())
+            .await
+            .unwrap();
+        let token = auth::generate_token();
+        repo.create_session(user.id, auth::hash_token(&token), auth::now() + 60)
+            .await
+            .unwrap();
+        format!("Bearer {}", token)
+    }
+
+    #[actix_web::test]
+    async fn test_create_list_and_get_post() {
+        let repo = repo();
+        let app = init_app!(repo);
+        let token = login_as(&repo, "alice").await;
+
+        let req = TestRequest::post()
+            .uri("/posts")
+            .insert_header(("authorization", token))
+            .set_json(json!({ "title": "hello", "body": "world" }))
+            .to_request();
+        let post: Value = atest::call_and_read_body_json(&app, req).await;
+        assert_eq!("hello", post["title"]);
+        assert_eq!(false, post["published"]);
+        let id = post["id"].as_i64().unwrap();
+
+        let req = TestRequest::get().uri("/posts").to_request();
+        let posts: Vec<Value> = atest::call_and_read_body_json(&app, req).await;
+        assert_eq!(vec![post.clone()], posts);
+
+        let req = TestRequest::get()
+            .uri(&format!("/posts/{}", id))
+            .to_request();
+        let found: Value = atest::call_and_read_body_json(&app, req).await;
+        assert_eq!(post, found);
+    }
+
+    #[actix_web::test]
+    async fn test_create_post_errors() {
+        let repo = repo();
+        let app = init_app!(repo);
+
+        let req = TestRequest::post()
+            .uri("/posts")
+            .set_json(json!({ "title": "hello", "body": "world" }))
+            .to_request();
+        assert_eq!(401, atest::call_service(&app, req).await.status().as_u16());
+
+        let token = login_as(&repo, "alice").await;
+        let req = TestRequest::post()
+            .uri("/posts")
+            .insert_header(("authorization", token))
+            .set_json(json!({ "title": "", "body": "world" }))
+            .to_request();
+        let res = atest::call_service(&app, req).await;
+        assert_eq!(422, res.status().as_u16());
+        let body: Value = atest::read_body_json(res).await;
+        assert!(body["fields"]["title"].is_array());
+    }
+
+    #[actix_web::test]
+    async fn test_get_missing_post() {
+        let repo = repo();
+        let app = init_app!(repo);
+        let req = TestRequest::get().uri("/posts/1").to_request();
+        let res = atest::call_service(&app, req).await;
+        assert_eq!(404, res.status().as_u16());
+        let body: Value = atest::read_body_json(res).await;
+        assert_eq!("not_found", body["error"]);
+
+        // 数値でない id も JSON のエラーにする
+        let req = TestRequest::get().uri("/posts/abc").to_request();
+        let res = atest::call_service(&app, req).await;
+        assert_eq!(400, res.status().as_u16());
+        let body: Value = atest::read_body_json(res).await;
+        assert_eq!("bad_request", body["error"]);
+        assert!(body["request_id"].is_string());
+    }
+
+    #[actix_web::test]
+    async fn test_update_and_delete_own_post() {
+        let repo = repo();
+        let app = init_app!(repo);
+        let alice = login_as(&repo, "alice").await;
+        let bob = login_as(&repo, "bob").await;
+        let req = TestRequest::post()
+            .uri("/posts")
+            .insert_header(("authorization", alice.clone()))
+            .set_json(json!({ "title": "hello", "body": "world" }))
+            .to_request();
+        let post: Value = atest::call_and_read_body_json(&app, req).await;
+        let uri = format!("/posts/{}", post["id"]);
+
+        let req = TestRequest::patch()
+            .uri(&uri)
+            .insert_header(("authorization", bob.clone()))
+            .set_json(json!({ "published": true }))
+            .to_request();
+        assert_eq!(403, atest::call_service(&app, req).await.status().as_u16());
+
+        let req = TestRequest::patch()
+            .uri(&uri)
+            .insert_header(("authorization", alice.clone()))
+            .set_json(json!({ "published": true }))
+            .to_request();
+        let updated: Value = atest::call_and_read_body_json(&app, req).await;
+        assert_eq!(true, updated["published"]);
+        assert_eq!("hello", updated["title"]);
+
+        let req = TestRequest::delete()
+            .uri(&uri)
+            .insert_header(("authorization", alice))
+            .to_request();
+        assert_eq!(204, atest::call_service(&app, req).await.status().as_u16());
+        let req = TestRequest::get().uri(&uri).to_request();
+        assert_eq!(404, atest::call_service(&app, req).await.status().as_u16());
+    }
+
+    #[actix_web::test]
+    async fn test_list_posts_pages() {
+        let repo = repo();
+        let app = init_app!(repo);
+        let token = login_as(&repo, "alice").await;
+        for (title, published) in [
+            ("c", true),
+            ("a", false),
+            ("e", true),
+            ("b", true),
+            ("d", false),
+        ] {
+            let req = TestRequest::post()
+                .uri("/posts")
+                .insert_header(("authorization", token.clone()))
+                .set_json(json!({ "title": title, "body": "body" }))
+                .to_request();
+            let post: Value = atest::call_and_read_body_json(&app, req).await;
+            let req = TestRequest::patch()
+                .uri(&format!("/posts/{}", post["id"]))
+                .insert_header(("authorization", token.clone()))
+                .set_json(json!({ "published": published }))
+                .to_request();
+            atest::call_service(&app, req).await;
+        }
+        let titles = |posts: Vec<Value>| -> Vec<String> {
+            posts
+                .iter()
+                .map(|p| p["title"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let req = TestRequest::get()
+            .uri("/posts?limit=2&offset=2&sort=title")
+            .to_request();
+        let res = atest::call_service(&app, req).await;
+        assert_eq!("5", res.headers().get("x-total-count").unwrap());
+        let link = res.headers().get(LINK).unwrap().to_str().unwrap();
+        assert!(link.contains("offset=4&sort=title&order=asc>; rel=\"next\""));
+        let posts: Vec<Value> = atest::read_body_json(res).await;
+        assert_eq!(vec!["c", "d"], titles(posts));
+
+        let req = TestRequest::get()
+            .uri("/posts?published=true&sort=title&order=desc&limit=2")
+            .to_request();
+        let res = atest::call_service(&app, req).await;
+        assert_eq!("3", res.headers().get("x-total-count").unwrap());
+        let posts: Vec<Value> = atest::read_body_json(res).await;
+        assert_eq!(vec!["e", "c"], titles(posts));
+
+        // "c" の id を cursor にして続きを読む
+        let req = TestRequest::get()
+            .uri("/posts?published=true&sort=title&order=desc&limit=2&cursor=1")
+            .to_request();
+        let posts: Vec<Value> = atest::call_and_read_body_json(&app, req).await;
+        assert_eq!(vec!["b"], titles(posts));
+
+        for (uri, status) in [
+            ("/posts?limit=0", 422),
+            ("/posts?offset=1&cursor=1", 422),
+            ("/posts?sort=body", 400),
+            ("/posts?sort=title&cursor=99", 400),
+        ] {
+            let req = TestRequest::get().uri(uri).to_request();
+            let res = atest::call_service(&app, req).await;
+            assert_eq!(status, res.status().as_u16(), "{}", uri);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_register_and_login() {
+        let repo = repo();
+        let app = init_app!(repo);
+        let user = json!({ "username": "carol", "password": "password123" });
+
+        let req = TestRequest::post()
+            .uri("/users")
+            .set_json(&user)
+            .to_request();
+        assert_eq!(201, atest::call_service(&app, req).await.status().as_u16());
+        let req = TestRequest::post()
+            .uri("/users")
+            .set_json(&user)
+            .to_request();
+        assert_eq!(409, atest::call_service(&app, req).await.status().as_u16());
+
+        let req = TestRequest::post()
+            .uri("/login")
+            .set_json(&user)
+            .to_request();
+        let res: Value = atest::call_and_read_body_json(&app, req).await;
+        assert_eq!(64, res["token"].as_str().unwrap().len());
+
+        let wrong = json!({ "username": "carol", "password": "password124" });
+        let req = TestRequest::post()
+            .uri("/login")
+            .set_json(&wrong)
+            .to_request();
+        assert_eq!(401, atest::call_service(&app, req).await.status().as_u16());
+
+        let unknown = json!({ "username": "dave", "password": "password123" });
+        let req = TestRequest::post()
+            .uri("/login")
+            .set_json(&unknown)
+            .to_request();
+        assert_eq!(401, atest::call_service(&app, req).await.status().as_u16());
+
+        let empty = json!({ "username": "carol", "password": "" });
+        let req = TestRequest::post()
+            .uri("/login")
+            .set_json(&empty)
+            .to_request();
+        let res = atest::call_service(&app, req).await;
+        assert_eq!(422, res.status().as_u16());
+        let res: Value = atest::read_body_json(res).await;
+        assert_eq!("validation", res["error"]);
+        assert!(res["fields"]["password"].is_array());
+    }
 }
@@ -0,0 +1,97 @@
+use crate::repository::{ListPosts, PostPage};
+use actix_web::HttpRequest;
+
+// GET /posts の Link ヘッダー（RFC 8288）。
+// offset で読んでいるときは first / prev / next / last を、cursor で読んでいるときは first / next を返す。
+// どのリンクも limit / sort / order / published を引き継ぐ
+pub fn link_header(req: &HttpRequest, query: &ListPosts, page: &PostPage) -> String {
+    let info = req.connection_info();
+    let base = format!("{}://{}{}", info.scheme(), info.host(), req.path());
+    let link = |offset: Option<i64>, cursor: Option<i32>, rel: &str| {
+        let query = ListPosts {
+            offset,
+            cursor,
+            ..query.clone()
+        };
+        let qs = serde_urlencoded::to_string(&query).unwrap_or_default();
+        format!("<{}?{}>; rel=\"{}\"", base, qs, rel)
+    };
+
+    let mut links = vec![link(None, None, "first")];
+    if query.cursor.is_some() {
+        if let Some(last) = page.posts.last().filter(|_| page.has_more) {
+            links.push(link(None, Some(last.id()), "next"));
+        }
+        return links.join(", ");
+    }
+
+    let offset = query.offset.unwrap_or(0);
+    if offset > 0 {
+        links.push(link(Some((offset - query.limit).max(0)), None, "prev"));
+    }
+    if page.has_more {
+        links.push(link(Some(offset + query.limit), None, "next"));
+    }
+    // 最後のページの先頭。総数がちょうど limit の倍数でも空のページを指さないようにする
+    let last = (page.total - 1).max(0) / query.limit * query.limit;
+    links.push(link(Some(last), None, "last"));
+    links.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::{Post, PostSort, SortOrder};
+    use actix_web::test::TestRequest;
+
+    fn query(offset: Option<i64>, cursor: Option<i32>) -> ListPosts {
+        ListPosts {
+            limit: 2,
+            offset,
+            cursor,
+            sort: PostSort::Title,
+            order: SortOrder::Desc,
+            published: Some(true),
+        }
+    }
+
+    fn page(ids: &[i32], total: i64, has_more: bool) -> PostPage {
+        PostPage {
+            posts: ids.iter().map(|id| Post::new(*id, None)).collect(),
+            total,
+            has_more,
+        }
+    }
+
+    #[test]
+    fn test_offset_links() {
+        let req = TestRequest::get()
+            .uri("/posts")
+            .insert_header(("host", "blog.example"))
+            .to_http_request();
+        let header = link_header(&req, &query(Some(2), None), &page(&[3, 4], 5, true));
+        let base = "http://blog.example/posts?limit=2";
+        let rest = "sort=title&order=desc&published=true";
+        assert_eq!(
+            [
+                format!("<{}&{}>; rel=\"first\"", base, rest),
+                format!("<{}&offset=0&{}>; rel=\"prev\"", base, rest),
+                format!("<{}&offset=4&{}>; rel=\"next\"", base, rest),
+                format!("<{}&offset=4&{}>; rel=\"last\"", base, rest),
+            ]
+            .join(", "),
+            header
+        );
+    }
+
+    #[test]
+    fn test_cursor_links() {
+        let req = TestRequest::get().uri("/posts").to_http_request();
+        let header = link_header(&req, &query(None, Some(9)), &page(&[7, 5], 5, true));
+        assert!(header.contains("cursor=5&sort=title&order=desc&published=true>; rel=\"next\""));
+        assert!(!header.contains("rel=\"prev\""));
+
+        let header = link_header(&req, &query(None, Some(9)), &page(&[7], 5, false));
+        assert!(!header.contains("rel=\"next\""));
+    }
+}
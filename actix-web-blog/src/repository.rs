@@ -3,31 +3,147 @@ use crate::schema::*;
 use actix_web::web;
 use diesel::prelude::*;
 use diesel::r2d2::{self, ConnectionManager};
-use diesel::sqlite::SqliteConnection;
-use serde::{Deserialize, Deserializer, Serialize};
+use diesel::sqlite::{Sqlite, SqliteConnection};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use std::time::Duration;
+use validator::{Validate, ValidationError};
 
 type DbPool = r2d2::Pool<ConnectionManager<SqliteConnection>>;
 
-#[derive(Deserialize, Insertable)]
+// migrations/ 以下の SQL をバイナリに埋め込み、diesel CLI なしで適用できるようにする
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+// 入力の検証は validator の属性で宣言し、ValidatedJson エクストラクタが自動で実行する
+#[derive(Deserialize, Insertable, Validate)]
 #[diesel(table_name = posts)]
 pub struct NewPost {
+    #[validate(custom = "validate_title")]
+    title: String,
+    #[validate(custom = "validate_body")]
+    body: String,
+}
+
+// 作成・置き換え・部分更新で同じ規則を使う
+fn validate_title(title: &str) -> Result<(), ValidationError> {
+    check_length(title, 1..=100, "title must be 1-100 characters")
+}
+
+fn validate_body(body: &str) -> Result<(), ValidationError> {
+    check_length(body, 1..=199, "body must be 1-199 characters")
+}
+
+fn check_length(
+    value: &str,
+    range: RangeInclusive<usize>,
+    message: &'static str,
+) -> Result<(), ValidationError> {
+    if range.contains(&value.chars().count()) {
+        return Ok(());
+    }
+    let mut e = ValidationError::new("length");
+    e.message = Some(message.into());
+    Err(e)
+}
+
+// PATCH 用。送られた項目だけを更新する
+#[derive(Deserialize, AsChangeset, Validate)]
+#[diesel(table_name = posts)]
+#[validate(schema(function = "validate_not_empty", skip_on_field_errors = false))]
+pub struct PostChanges {
+    #[validate(custom = "validate_title")]
+    title: Option<String>,
+    #[validate(custom = "validate_body")]
+    body: Option<String>,
+    published: Option<bool>,
+}
+
+fn validate_not_empty(changes: &PostChanges) -> Result<(), ValidationError> {
+    if changes.title.is_none() && changes.body.is_none() && changes.published.is_none() {
+        let mut e = ValidationError::new("empty");
+        e.message = Some("no fields to update".into());
+        return Err(e);
+    }
+    Ok(())
+}
+
+// PUT 用。published を省略したら非公開にする
+#[derive(Deserialize, Validate)]
+pub struct ReplacePost {
+    #[validate(custom = "validate_title")]
     title: String,
-    #[serde(deserialize_with = "max200")]
+    #[validate(custom = "validate_body")]
     body: String,
+    #[serde(default)]
+    published: bool,
 }
 
-// serde のデシリアライズ時に検証するパターン
-fn max200<'de, D>(de: D) -> Result<String, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    String::deserialize(de).and_then(|s| {
-        if !s.is_empty() && s.len() < 200 {
-            Ok(s)
-        } else {
-            Err(serde::de::Error::custom("string length is 0 or too long"))
+impl From<ReplacePost> for PostChanges {
+    fn from(replace: ReplacePost) -> Self {
+        Self {
+            title: Some(replace.title),
+            body: Some(replace.body),
+            published: Some(replace.published),
         }
-    })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PostSort {
+    #[default]
+    Id,
+    Title,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+// GET /posts のクエリ。offset でページを飛ぶか、cursor（前のページの最後の記事の id）で続きを読む
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
+#[validate(schema(function = "validate_list_posts"))]
+pub struct ListPosts {
+    #[serde(default = "default_limit")]
+    #[validate(range(min = 1, max = 100, message = "limit must be 1-100"))]
+    pub limit: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 0, message = "offset must not be negative"))]
+    pub offset: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<i32>,
+    #[serde(default)]
+    pub sort: PostSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    // 省略したときは公開・非公開の両方を返す
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<bool>,
+}
+
+fn default_limit() -> i64 {
+    20
+}
+
+fn validate_list_posts(query: &ListPosts) -> Result<(), ValidationError> {
+    if query.offset.is_some() && query.cursor.is_some() {
+        let mut e = ValidationError::new("pagination");
+        e.message = Some("offset and cursor cannot be used together".into());
+        return Err(e);
+    }
+    Ok(())
+}
+
+pub struct PostPage {
+    pub posts: Vec<Post>,
+    // 絞り込み条件に合う記事の総数
+    pub total: i64,
+    pub has_more: bool,
 }
 
 #[derive(Serialize, Queryable)]
@@ -36,33 +152,85 @@ pub struct Post {
     title: String,
     body: String,
     published: bool,
+    // ユーザー導入前の記事は None
+    author_id: Option<i32>,
 }
 
-pub struct Repository {
-    pool: DbPool,
+impl Post {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn author_id(&self) -> Option<i32> {
+        self.author_id
+    }
+}
+
+#[derive(Deserialize, Validate)]
+pub struct NewUser {
+    #[validate(custom = "validate_username")]
+    pub username: String,
+    #[validate(length(min = 8, max = 128, message = "password must be 8-128 characters"))]
+    pub password: String,
 }
 
-impl NewPost {
-    pub fn validate(&self) -> bool {
-        self.title.len() > 0 && self.title.len() <= 100
+// URL やログに出しても困らないよう英数字と - _ だけにする
+fn validate_username(username: &str) -> Result<(), ValidationError> {
+    if (3..=30).contains(&username.len())
+        && username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Ok(());
     }
+    let mut e = ValidationError::new("username");
+    e.message = Some("username must be 3-30 characters of [A-Za-z0-9_-]".into());
+    Err(e)
+}
+
+#[derive(Serialize, Queryable)]
+pub struct User {
+    pub id: i32,
+    pub username: String,
+    #[serde(skip)]
+    pub password_hash: String,
+    pub is_admin: bool,
+}
+
+pub struct Repository {
+    pool: DbPool,
 }
 
 impl Repository {
-    pub fn new(database_url: &str) -> Self {
+    // 作成時に接続を張るので、DB を開けなければ pool_timeout 待ったあとエラーになる
+    pub fn new(
+        database_url: &str,
+        pool_size: u32,
+        pool_timeout: Duration,
+    ) -> Result<Self, r2d2::PoolError> {
         let manager = ConnectionManager::<SqliteConnection>::new(database_url);
         let pool = r2d2::Pool::builder()
-            .build(manager)
-            .expect("Failed to create a pool.");
-        Self { pool }
+            .max_size(pool_size)
+            .connection_timeout(pool_timeout)
+            .build(manager)?;
+        Ok(Self { pool })
+    }
+
+    // 未適用のマイグレーションを流し、適用したバージョンを返す。起動時に呼ぶ
+    pub fn run_migrations(&self) -> anyhow::Result<Vec<String>> {
+        let mut conn = self.pool.get()?;
+        let applied = conn
+            .run_pending_migrations(MIGRATIONS)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        Ok(applied.iter().map(|version| version.to_string()).collect())
     }
 
-    pub async fn create_post(&self, new_post: NewPost) -> Result<Post, ApiError> {
+    pub async fn create_post(&self, new_post: NewPost, author_id: i32) -> Result<Post, ApiError> {
         let mut conn = self.pool.get()?;
         let post = web::block(move || {
             // 別スレッドに move するから new_post は使えなくなる
             diesel::insert_into(posts::table)
-                .values(new_post)
+                .values((new_post, posts::author_id.eq(author_id)))
                 .get_result(&mut conn)
         })
         .await??;
@@ -70,11 +238,71 @@ impl Repository {
         Ok(post)
     }
 
-    pub async fn list_posts(&self) -> Result<Vec<Post>, ApiError> {
+    pub async fn list_posts(&self, query: ListPosts) -> Result<PostPage, ApiError> {
         let mut conn = self.pool.get()?;
-        let res = web::block(move || posts::table.load(&mut conn)).await??;
+        web::block(move || {
+            let total = filtered_posts(query.published)
+                .count()
+                .get_result(&mut conn)?;
 
-        Ok(res)
+            let mut select = filtered_posts(query.published);
+            if let Some(cursor) = query.cursor {
+                select = match query.sort {
+                    PostSort::Id => match query.order {
+                        SortOrder::Asc => select.filter(posts::id.gt(cursor)),
+                        SortOrder::Desc => select.filter(posts::id.lt(cursor)),
+                    },
+                    // タイトルが同じ記事は id で順番を決める
+                    PostSort::Title => {
+                        let title: String = posts::table
+                            .find(cursor)
+                            .select(posts::title)
+                            .first(&mut conn)
+                            .optional()?
+                            .ok_or_else(|| {
+                                ApiError::BadRequest(format!("cursor {} does not exist", cursor))
+                            })?;
+                        match query.order {
+                            SortOrder::Asc => select.filter(
+                                posts::title
+                                    .gt(title.clone())
+                                    .or(posts::title.eq(title).and(posts::id.gt(cursor))),
+                            ),
+                            SortOrder::Desc => select.filter(
+                                posts::title
+                                    .lt(title.clone())
+                                    .or(posts::title.eq(title).and(posts::id.lt(cursor))),
+                            ),
+                        }
+                    }
+                };
+            }
+            select = match (query.sort, query.order) {
+                (PostSort::Id, SortOrder::Asc) => select.order(posts::id.asc()),
+                (PostSort::Id, SortOrder::Desc) => select.order(posts::id.desc()),
+                (PostSort::Title, SortOrder::Asc) => {
+                    select.order((posts::title.asc(), posts::id.asc()))
+                }
+                (PostSort::Title, SortOrder::Desc) => {
+                    select.order((posts::title.desc(), posts::id.desc()))
+                }
+            };
+
+            // 1 件多く読んで次のページがあるかを判定する
+            let mut posts: Vec<Post> = select
+                .limit(query.limit + 1)
+                .offset(query.offset.unwrap_or(0))
+                .load(&mut conn)?;
+            let has_more = posts.len() as i64 > query.limit;
+            posts.truncate(query.limit as usize);
+
+            Ok(PostPage {
+                posts,
+                total,
+                has_more,
+            })
+        })
+        .await?
     }
 
     pub async fn get_post(&self, id: i32) -> Result<Post, ApiError> {
@@ -85,6 +313,157 @@ impl Repository {
 
         Ok(res)
     }
+
+    pub async fn update_post(&self, id: i32, changes: PostChanges) -> Result<Post, ApiError> {
+        let mut conn = self.pool.get()?;
+        let post = web::block(move || {
+            diesel::update(posts::table.find(id))
+                .set(changes)
+                .get_result(&mut conn)
+                .optional()
+        })
+        .await??
+        .ok_or(ApiError::NotFound)?;
+
+        Ok(post)
+    }
+
+    pub async fn delete_post(&self, id: i32) -> Result<(), ApiError> {
+        let mut conn = self.pool.get()?;
+        let deleted =
+            web::block(move || diesel::delete(posts::table.find(id)).execute(&mut conn)).await??;
+        if deleted == 0 {
+            return Err(ApiError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    // 登録済みのユーザー名なら UNIQUE 制約違反になる
+    pub async fn create_user(
+        &self,
+        username: String,
+        password_hash: String,
+    ) -> Result<User, ApiError> {
+        let mut conn = self.pool.get()?;
+        let user = web::block(move || {
+            diesel::insert_into(users::table)
+                .values((
+                    users::username.eq(username),
+                    users::password_hash.eq(password_hash),
+                ))
+                .get_result(&mut conn)
+        })
+        .await??;
+
+        Ok(user)
+    }
+
+    pub async fn find_user_by_username(&self, username: String) -> Result<Option<User>, ApiError> {
+        let mut conn = self.pool.get()?;
+        let res = web::block(move || {
+            users::table
+                .filter(users::username.eq(username))
+                .first(&mut conn)
+                .optional()
+        })
+        .await??;
+
+        Ok(res)
+    }
+
+    pub async fn create_session(
+        &self,
+        user_id: i32,
+        token_hash: String,
+        expires_at: i64,
+    ) -> Result<(), ApiError> {
+        let mut conn = self.pool.get()?;
+        web::block(move || {
+            diesel::insert_into(sessions::table)
+                .values((
+                    sessions::token_hash.eq(token_hash),
+                    sessions::user_id.eq(user_id),
+                    sessions::expires_at.eq(expires_at),
+                ))
+                .execute(&mut conn)
+        })
+        .await??;
+
+        Ok(())
+    }
+
+    // 期限内のセッションを持つユーザーを返す
+    pub async fn find_user_by_token(
+        &self,
+        token_hash: String,
+        now: i64,
+    ) -> Result<Option<User>, ApiError> {
+        let mut conn = self.pool.get()?;
+        let res = web::block(move || {
+            sessions::table
+                .inner_join(users::table)
+                .filter(sessions::token_hash.eq(token_hash))
+                .filter(sessions::expires_at.gt(now))
+                .select(users::all_columns)
+                .first(&mut conn)
+                .optional()
+        })
+        .await??;
+
+        Ok(res)
+    }
+}
+
+fn filtered_posts(published: Option<bool>) -> posts::BoxedQuery<'static, Sqlite> {
+    let mut query = posts::table.into_boxed();
+    if let Some(published) = published {
+        query = query.filter(posts::published.eq(published));
+    }
+    query
+}
+
+#[cfg(test)]
+impl Repository {
+    // テストごとにまっさらな DB を作る。:memory: は接続ごとに別の DB になるので、
+    // 接続を 1 本だけにして作り直されないようにする
+    pub fn in_memory() -> Self {
+        let manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .build(manager)
+            .expect("Failed to create a pool.");
+        let repo = Self { pool };
+        repo.run_migrations().expect("Failed to run migrations.");
+        repo
+    }
+}
+
+#[cfg(test)]
+impl Post {
+    pub fn new(id: i32, author_id: Option<i32>) -> Self {
+        Self {
+            id,
+            title: "title".to_string(),
+            body: "body".to_string(),
+            published: false,
+            author_id,
+        }
+    }
+}
+
+#[cfg(test)]
+impl User {
+    pub fn new(id: i32, username: &str, is_admin: bool) -> Self {
+        Self {
+            id,
+            username: username.to_string(),
+            password_hash: String::new(),
+            is_admin,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -92,9 +471,53 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_conn() {
-        let database_url = std::env::var("DATABASE_URL").unwrap();
-        let repo = Repository::new(&database_url);
-        assert!(repo.pool.get().is_ok());
+    fn test_migrations() {
+        let repo = Repository::in_memory();
+        let mut conn = repo.pool.get().unwrap();
+        assert!(!conn.has_pending_migration(MIGRATIONS).unwrap());
+        // down.sql でも戻せること
+        conn.revert_all_migrations(MIGRATIONS).unwrap();
+        assert_eq!(2, conn.run_pending_migrations(MIGRATIONS).unwrap().len());
+    }
+
+    #[test]
+    fn test_validate_new_user() {
+        let user = |username: &str, password: &str| NewUser {
+            username: username.to_string(),
+            password: password.to_string(),
+        };
+        assert!(user("alice", "password123").validate().is_ok());
+        assert!(user("al", "password123").validate().is_err());
+        assert!(user("a lice", "password123").validate().is_err());
+        let errors = user("a", "short").validate().unwrap_err();
+        assert_eq!(2, errors.field_errors().len());
+    }
+
+    #[test]
+    fn test_post_changes() {
+        let changes: PostChanges = serde_json::from_str(r#"{"published":true}"#).unwrap();
+        assert!(changes.validate().is_ok());
+        assert_eq!(
+            (None, None, Some(true)),
+            (changes.title, changes.body, changes.published)
+        );
+
+        let changes: PostChanges = serde_json::from_str(r#"{"title":"","body":""}"#).unwrap();
+        let errors = changes.validate().unwrap_err();
+        assert!(errors.field_errors().contains_key("title"));
+        assert!(errors.field_errors().contains_key("body"));
+
+        let changes: PostChanges = serde_json::from_str("{}").unwrap();
+        assert!(changes
+            .validate()
+            .unwrap_err()
+            .errors()
+            .contains_key("__all__"));
+
+        // PUT は作成時と同じく title と body が必須
+        assert!(serde_json::from_str::<ReplacePost>(r#"{"title":"a"}"#).is_err());
+        let replace: ReplacePost = serde_json::from_str(r#"{"title":"a","body":"b"}"#).unwrap();
+        assert!(replace.validate().is_ok());
+        assert_eq!(Some(false), PostChanges::from(replace).published);
     }
 }
@@ -0,0 +1,109 @@
+use crate::error::ApiError;
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+};
+use rand_core::{OsRng, RngCore};
+use tracing::Instrument;
+
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+// 手前のプロキシが付けた ID があれば引き継ぎ、なければ作る。
+// ログにはリクエストごとの span で ID を出し、ApiError のレスポンスには本文にも ID を入れる
+pub async fn request_id(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let id = req
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| is_valid(v))
+        .map(str::to_string)
+        .unwrap_or_else(generate);
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.path()
+    );
+
+    let res = next.call(req).instrument(span.clone()).await?;
+    let mut res = match res
+        .response()
+        .error()
+        .and_then(|e| e.as_error::<ApiError>())
+    {
+        Some(e) => {
+            span.in_scope(|| e.log());
+            let body = e.to_response(Some(&id));
+            res.into_response(body)
+        }
+        None => res.map_into_boxed_body(),
+    };
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        res.headers_mut().insert(X_REQUEST_ID, value);
+    }
+    Ok(res)
+}
+
+// ログやヘッダーに入れても壊れない長さと文字だけ受け付ける
+fn is_valid(id: &str) -> bool {
+    (1..=64).contains(&id.len())
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn generate() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{middleware::from_fn, test as atest, web, App, HttpResponse};
+
+    async fn conflict() -> Result<HttpResponse, ApiError> {
+        Err(ApiError::Conflict("taken".to_string()))
+    }
+
+    #[actix_web::test]
+    async fn test_adds_request_id_to_errors() {
+        let app = atest::init_service(
+            App::new()
+                .wrap(from_fn(request_id))
+                .route("/", web::get().to(conflict)),
+        )
+        .await;
+
+        let res = atest::call_service(&app, atest::TestRequest::get().uri("/").to_request()).await;
+        assert_eq!(409, res.status().as_u16());
+        let id = res.headers().get(&X_REQUEST_ID).unwrap().to_str().unwrap();
+        assert_eq!(32, id.len());
+        let id = id.to_string();
+        let body: serde_json::Value = atest::read_body_json(res).await;
+        assert_eq!("conflict", body["error"]);
+        assert_eq!(id, body["request_id"]);
+
+        // 送られてきた ID は引き継ぐ
+        let req = atest::TestRequest::get()
+            .uri("/")
+            .insert_header((X_REQUEST_ID, "from-proxy"))
+            .to_request();
+        let body: serde_json::Value = atest::call_and_read_body_json(&app, req).await;
+        assert_eq!("from-proxy", body["request_id"]);
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(is_valid("abc-123_x"));
+        assert!(!is_valid(""));
+        assert!(!is_valid("a b"));
+        assert!(!is_valid(&"a".repeat(65)));
+    }
+}
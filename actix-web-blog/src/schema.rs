@@ -6,5 +6,28 @@ diesel::table! {
         title -> Text,
         body -> Text,
         published -> Bool,
+        author_id -> Nullable<Integer>,
     }
 }
+
+diesel::table! {
+    sessions (token_hash) {
+        token_hash -> Text,
+        user_id -> Integer,
+        expires_at -> BigInt,
+    }
+}
+
+diesel::table! {
+    users (id) {
+        id -> Integer,
+        username -> Text,
+        password_hash -> Text,
+        is_admin -> Bool,
+    }
+}
+
+diesel::joinable!(posts -> users (author_id));
+diesel::joinable!(sessions -> users (user_id));
+
+diesel::allow_tables_to_appear_in_same_query!(posts, sessions, users,);
@@ -0,0 +1,299 @@
+use crate::error::ApiError;
+use actix_files::NamedFile;
+use actix_web::{
+    guard::{self, GuardContext},
+    http::{
+        header::{self, ContentEncoding, HeaderValue},
+        Method,
+    },
+    web, HttpRequest, HttpResponse,
+};
+use std::path::{Component, Path, PathBuf};
+
+// trunk build の出力先（yew-blog/dist）。ハンドラからは web::Data<StaticDir> で参照する
+#[derive(Clone, Debug)]
+pub struct StaticDir(pub PathBuf);
+
+// ファイル名にハッシュが入っている成果物は中身が変われば名前も変わるので、ずっとキャッシュさせる
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+// index.html は毎回サーバーに確認させ、新しいハッシュの成果物を読みにいけるようにする
+const REVALIDATE: &str = "no-cache";
+
+// API のルートより先に登録する。ページ遷移は SPA に、それ以外で API に無いパスは dist のファイルに回す
+pub fn routes(cfg: &mut web::ServiceConfig, dir: &Path) {
+    cfg.app_data(web::Data::new(StaticDir(dir.to_path_buf())))
+        .service(
+            web::resource("/{path:.*}")
+                .guard(guard::Get())
+                .guard(guard::fn_guard(is_navigation))
+                .to(serve),
+        )
+        .default_service(web::to(fallback));
+}
+
+// default_service にはガードが効かないので、メソッドはここで見る。
+// GET / HEAD 以外で API に無いパスは index.html ではなく API と同じ形の 404 にする
+async fn fallback(req: HttpRequest, dir: web::Data<StaticDir>) -> Result<HttpResponse, ApiError> {
+    if req.method() != Method::GET && req.method() != Method::HEAD {
+        return Err(ApiError::NotFound);
+    }
+    Ok(serve(req, dir).await)
+}
+
+// ブラウザのページ遷移（Accept に text/html を含む GET）。API と同じパスでも SPA を返す
+pub fn is_navigation(ctx: &GuardContext) -> bool {
+    ctx.head()
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"))
+}
+
+// dist にあるファイルを返し、なければ index.html を返して yew のルーターに任せる。
+// 拡張子付きのパスは成果物の取得とみなし、見つからなければ 404 にする
+pub async fn serve(req: HttpRequest, dir: web::Data<StaticDir>) -> HttpResponse {
+    let Some(relative) = safe_relative_path(req.path()) else {
+        return HttpResponse::NotFound().finish();
+    };
+    let file = dir.0.join(&relative);
+    if relative.as_os_str().is_empty() || !file.is_file() {
+        if relative.extension().is_some() && !req.path().ends_with(".html") {
+            return HttpResponse::NotFound().finish();
+        }
+        return send(&req, &dir.0.join("index.html"));
+    }
+    send(&req, &file)
+}
+
+fn send(req: &HttpRequest, file: &Path) -> HttpResponse {
+    let (path, encoding) = precompressed(req, file);
+    let named = match NamedFile::open(&path) {
+        Ok(named) => named,
+        Err(_) => return HttpResponse::NotFound().finish(),
+    };
+    let mut named = named
+        .set_content_type(mime_guess::from_path(file).first_or_octet_stream())
+        .disable_content_disposition();
+    if let Some(encoding) = encoding {
+        named = named.set_content_encoding(encoding);
+    }
+
+    let mut res = named.into_response(req);
+    let cache_control = if is_hashed(file) {
+        IMMUTABLE
+    } else {
+        REVALIDATE
+    };
+    let headers = res.headers_mut();
+    headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(cache_control),
+    );
+    headers.insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+    res
+}
+
+// 隣に圧縮済みの .br / .gz があって、クライアントが受け付けるならそちらを返す
+fn precompressed(req: &HttpRequest, file: &Path) -> (PathBuf, Option<ContentEncoding>) {
+    let accept = req
+        .headers()
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    for (encoding, ext) in [
+        (ContentEncoding::Brotli, "br"),
+        (ContentEncoding::Gzip, "gz"),
+    ] {
+        let mut name = file.as_os_str().to_owned();
+        name.push(".");
+        name.push(ext);
+        let compressed = PathBuf::from(name);
+        if accepts(accept, encoding.as_str()) && compressed.is_file() {
+            return (compressed, Some(encoding));
+        }
+    }
+    (file.to_path_buf(), None)
+}
+
+fn accepts(accept_encoding: &str, encoding: &str) -> bool {
+    accept_encoding.split(',').any(|item| {
+        let mut parts = item.split(';').map(str::trim);
+        let name = parts.next().unwrap_or("");
+        // q=0 は「受け付けない」の意味
+        let refused = parts
+            .filter_map(|p| p.strip_prefix("q="))
+            .any(|q| q.parse::<f32>() == Ok(0.0));
+        (name == encoding || name == "*") && !refused
+    })
+}
+
+// dist の外を指すパスは受け付けない
+fn safe_relative_path(path: &str) -> Option<PathBuf> {
+    let relative = PathBuf::from(path.trim_start_matches('/'));
+    relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then_some(relative)
+}
+
+// trunk は yew-blog-c0e41f57b323c62f.js や yew-blog-c0e41f57b323c62f_bg.wasm のような名前を付ける
+fn is_hashed(file: &Path) -> bool {
+    let Some(stem) = file.file_stem().and_then(|s| s.to_str()) else {
+        return false;
+    };
+    stem.split('-').skip(1).any(|part| {
+        let hash = part.split('_').next().unwrap_or("");
+        hash.len() >= 16 && hash.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request_id;
+    use actix_web::middleware::from_fn;
+    use actix_web::test::{self as atest, TestRequest};
+    use actix_web::App;
+    use serde_json::Value;
+    use std::fs;
+
+    const ASSET: &str = "yew-blog-c0e41f57b323c62f.js";
+
+    // テストごとに別のディレクトリに dist を作り、drop で消す
+    struct Dist(PathBuf);
+
+    impl Drop for Dist {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn dist(name: &str) -> Dist {
+        let dir = std::env::temp_dir().join(format!(
+            "actix-web-blog-spa-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("index.html"), "<html></html>").unwrap();
+        fs::write(dir.join(ASSET), "console.log(1)").unwrap();
+        fs::write(dir.join(format!("{}.br", ASSET)), "brotli").unwrap();
+        fs::write(dir.join(format!("{}.gz", ASSET)), "gzip").unwrap();
+        Dist(dir)
+    }
+
+    macro_rules! init_app {
+        ($dir:expr) => {{
+            let dir: &Dist = $dir;
+            atest::init_service(
+                App::new()
+                    .wrap(from_fn(request_id::request_id))
+                    .configure(|cfg| routes(cfg, &dir.0))
+                    .route(
+                        "/posts",
+                        web::get().to(|| async { HttpResponse::Ok().json(Vec::<i32>::new()) }),
+                    ),
+            )
+            .await
+        }};
+    }
+
+    #[actix_web::test]
+    async fn test_hashed_asset_is_cached_and_precompressed() {
+        let dir = dist("asset");
+        let app = init_app!(&dir);
+
+        let req = TestRequest::get()
+            .uri(&format!("/{}", ASSET))
+            .insert_header((header::ACCEPT_ENCODING, "gzip, br"))
+            .to_request();
+        let res = atest::call_service(&app, req).await;
+        assert_eq!(200, res.status().as_u16());
+        assert_eq!(IMMUTABLE, res.headers().get(header::CACHE_CONTROL).unwrap());
+        assert_eq!("br", res.headers().get(header::CONTENT_ENCODING).unwrap());
+        assert_eq!(
+            "text/javascript",
+            res.headers().get(header::CONTENT_TYPE).unwrap()
+        );
+        assert_eq!("brotli", atest::read_body(res).await);
+
+        let req = TestRequest::get()
+            .uri(&format!("/{}", ASSET))
+            .insert_header((header::ACCEPT_ENCODING, "gzip, br;q=0"))
+            .to_request();
+        assert_eq!("gzip", atest::call_and_read_body(&app, req).await);
+
+        let req = TestRequest::get().uri(&format!("/{}", ASSET)).to_request();
+        let res = atest::call_service(&app, req).await;
+        assert!(res.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!("console.log(1)", atest::read_body(res).await);
+    }
+
+    #[actix_web::test]
+    async fn test_spa_fallback() {
+        let dir = dist("fallback");
+        let app = init_app!(&dir);
+
+        // ページ遷移なら API と同じパスでも index.html
+        let req = TestRequest::get()
+            .uri("/posts/1")
+            .insert_header((header::ACCEPT, "text/html,application/xhtml+xml"))
+            .to_request();
+        let res = atest::call_service(&app, req).await;
+        assert_eq!(
+            REVALIDATE,
+            res.headers().get(header::CACHE_CONTROL).unwrap()
+        );
+        assert_eq!("<html></html>", atest::read_body(res).await);
+
+        // fetch からは API が返る
+        let req = TestRequest::get().uri("/posts").to_request();
+        assert_eq!("[]", atest::call_and_read_body(&app, req).await);
+
+        let req = TestRequest::get().uri("/no-such-page").to_request();
+        assert_eq!("<html></html>", atest::call_and_read_body(&app, req).await);
+
+        let req = TestRequest::get()
+            .uri("/missing-0123456789abcdef.js")
+            .to_request();
+        assert_eq!(404, atest::call_service(&app, req).await.status().as_u16());
+    }
+
+    #[actix_web::test]
+    async fn test_fallback_is_get_only() {
+        let dir = dist("method");
+        let app = init_app!(&dir);
+
+        let req = TestRequest::post().uri("/no-such-route").to_request();
+        let res = atest::call_service(&app, req).await;
+        assert_eq!(404, res.status().as_u16());
+        let body: Value = atest::read_body_json(res).await;
+        assert_eq!("not_found", body["error"]);
+        assert!(body["request_id"].is_string());
+
+        let req = TestRequest::delete()
+            .uri("/no-such-route")
+            .insert_header((header::ACCEPT, "text/html"))
+            .to_request();
+        assert_eq!(404, atest::call_service(&app, req).await.status().as_u16());
+
+        let req = TestRequest::default()
+            .method(Method::HEAD)
+            .uri("/no-such-route")
+            .to_request();
+        assert_eq!(200, atest::call_service(&app, req).await.status().as_u16());
+    }
+
+    #[test]
+    fn test_paths() {
+        assert!(safe_relative_path("/../etc/passwd").is_none());
+        assert!(safe_relative_path("/a/./b").is_some());
+        assert!(is_hashed(Path::new("yew-blog-c0e41f57b323c62f_bg.wasm")));
+        assert!(!is_hashed(Path::new("index.html")));
+        assert!(!is_hashed(Path::new("my-blog-post.js")));
+        assert!(accepts("gzip, deflate, br", "br"));
+        assert!(!accepts("gzip, br;q=0", "br"));
+        assert!(accepts("*", "gzip"));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::error::ApiError;
+use actix_web::{
+    dev::Payload,
+    error::{JsonPayloadError, PathError},
+    web, FromRequest, HttpRequest,
+};
+use serde::de::DeserializeOwned;
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use validator::Validate;
+
+// JSON を読み込んだあと validator で検証するエクストラクタ。上限は app_data の JsonConfig に従う
+pub struct ValidatedJson<T>(pub T);
+
+impl<T> ValidatedJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned + Validate + 'static> FromRequest for ValidatedJson<T> {
+    type Error = ApiError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = web::Json::<T>::from_request(req, payload);
+        Box::pin(async move {
+            let value = json.await.map_err(json_error)?.into_inner();
+            value.validate()?;
+            Ok(ValidatedJson(value))
+        })
+    }
+}
+
+// クエリ文字列を読み込んだあと validator で検証するエクストラクタ
+pub struct ValidatedQuery<T>(pub T);
+
+impl<T> ValidatedQuery<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned + Validate + 'static> FromRequest for ValidatedQuery<T> {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let result = web::Query::<T>::from_query(req.query_string())
+            .map_err(|e| ApiError::BadRequest(format!("Query parse error: [{}]", e)))
+            .and_then(|query| {
+                let value = query.into_inner();
+                value.validate()?;
+                Ok(ValidatedQuery(value))
+            });
+        ready(result)
+    }
+}
+
+// web::Path が読めないとき（/posts/abc など）も ApiError の JSON で返す
+pub fn path_error(e: PathError, _: &HttpRequest) -> actix_web::Error {
+    ApiError::BadRequest(format!("Path parse error: [{}]", e)).into()
+}
+
+fn json_error(e: actix_web::Error) -> ApiError {
+    match e.as_error::<JsonPayloadError>() {
+        Some(JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. }) => {
+            ApiError::PayloadTooLarge
+        }
+        Some(e) => ApiError::BadRequest(format!("Json parse error: [{}]", e)),
+        None => ApiError::BadRequest(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::NewPost;
+    use actix_web::test::TestRequest;
+
+    async fn extract(body: &str, limit: usize) -> Result<NewPost, ApiError> {
+        let (req, mut payload) = TestRequest::post()
+            .insert_header(("content-type", "application/json"))
+            .app_data(web::JsonConfig::default().limit(limit))
+            .set_payload(body.to_string())
+            .to_http_parts();
+        ValidatedJson::<NewPost>::from_request(&req, &mut payload)
+            .await
+            .map(ValidatedJson::into_inner)
+    }
+
+    #[actix_web::test]
+    async fn test_lists_every_failing_field() {
+        let title = "a".repeat(101);
+        let body = format!(r#"{{"title":"{}","body":""}}"#, title);
+        match extract(&body, 1024).await {
+            Err(ApiError::Validation(fields)) => {
+                assert_eq!(vec!["title must be 1-100 characters"], fields["title"]);
+                assert_eq!(vec!["body must be 1-199 characters"], fields["body"]);
+            }
+            _ => panic!("expected validation error"),
+        }
+        assert!(extract(r#"{"title":"a","body":"b"}"#, 1024).await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_rejects_malformed_and_large_body() {
+        assert!(matches!(
+            extract(r#"{"title":"a"}"#, 1024).await,
+            Err(ApiError::BadRequest(_))
+        ));
+        assert!(matches!(
+            extract(r#"{"title":"a","body":"b"}"#, 8).await,
+            Err(ApiError::PayloadTooLarge)
+        ));
+    }
+}
@@ -0,0 +1,16 @@
+use sea_orm::entity::prelude::*;
+
+// フィードの中身が最後に変わった日時。記事の公開・非公開・削除・編集のたびに進め、戻さない。
+// 行は id = 1 の 1 件だけ
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "feed_state")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: i32,
+    pub changed_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
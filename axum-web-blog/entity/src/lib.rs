@@ -1,2 +1,11 @@
+pub mod comment;
+pub mod feed_state;
 pub mod post;
+pub mod post_draft;
+pub mod post_revision;
+pub mod post_tag;
+pub mod session;
+pub mod slug;
+pub mod tag;
+pub mod user;
 pub use sea_orm;
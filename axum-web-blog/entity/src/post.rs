@@ -5,17 +5,89 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub title: String,
+    // URL 用の一意なスラッグ。タイトルから作り、重複したら -2, -3 ... を付ける
+    #[sea_orm(unique)]
+    pub slug: String,
     #[sea_orm(column_type = "Text", nullable)]
     pub body: String,
     pub published: bool,
+    // 予約公開の日時（この日時を過ぎると公開扱いになる）
+    pub publish_at: Option<DateTimeUtc>,
+    pub published_at: Option<DateTimeUtc>,
+    pub created_at: DateTimeUtc,
+    // 書いたユーザー。ユーザー導入前の記事は None で、管理者だけが編集できる
+    pub author_id: Option<i32>,
 }
 
-#[derive(Copy, Clone, Debug, EnumIter)]
-pub enum Relation {}
+impl Model {
+    // 公開済み、または予約日時を過ぎていれば公開扱い
+    pub fn is_public(&self, now: DateTimeUtc) -> bool {
+        self.published || self.publish_at.is_some_and(|at| at <= now)
+    }
+
+    pub fn published_time(&self, now: DateTimeUtc) -> Option<DateTimeUtc> {
+        self.published_at
+            .or(self.publish_at.filter(|at| *at <= now))
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::comment::Entity")]
+    Comment,
+    #[sea_orm(has_many = "super::post_tag::Entity")]
+    PostTag,
+    #[sea_orm(has_many = "super::post_revision::Entity")]
+    PostRevision,
+    #[sea_orm(has_one = "super::post_draft::Entity")]
+    PostDraft,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::AuthorId",
+        to = "super::user::Column::Id",
+        on_delete = "SetNull"
+    )]
+    Author,
+}
+
+impl Related<super::comment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Comment.def()
+    }
+}
+
+impl Related<super::post_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PostTag.def()
+    }
+}
+
+impl Related<super::post_revision::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PostRevision.def()
+    }
+}
+
+impl Related<super::post_draft::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PostDraft.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Author.def()
+    }
+}
+
+// post_tag を経由した多対多
+impl Related<super::tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::post_tag::Relation::Tag.def()
+    }
 
-impl RelationTrait for Relation {
-    fn def(&self) -> RelationDef {
-        panic!("No RelationDef")
+    fn via() -> Option<RelationDef> {
+        Some(super::post_tag::Relation::Post.def().rev())
     }
 }
 
@@ -0,0 +1,36 @@
+use sea_orm::entity::prelude::*;
+
+// 記事を保存するたびに残すスナップショット。rev は記事ごとに 1 から振る
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "post_revisions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub post_id: i32,
+    pub rev: i32,
+    pub title: String,
+    #[sea_orm(column_type = "Text")]
+    pub body: String,
+    // 保存したユーザー（ADMIN_TOKEN での保存は None）
+    pub editor_id: Option<i32>,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::post::Entity",
+        from = "Column::PostId",
+        to = "super::post::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Post,
+}
+
+impl Related<super::post::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Post.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,30 @@
+// URL に使うスラッグを作る。英数字（日本語などの文字も含む）以外は '-' にまとめる
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    let mut pending_dash = false;
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            if pending_dash && !slug.is_empty() {
+                slug.push('-');
+            }
+            pending_dash = false;
+            slug.extend(c.to_lowercase());
+        } else {
+            pending_dash = true;
+        }
+    }
+    slug
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn slugify_text() {
+        assert_eq!("hello-world", slugify("Hello, World!"));
+        assert_eq!("rust-2021", slugify("  Rust -- 2021 "));
+        assert_eq!("日本語-タグ", slugify("日本語 タグ"));
+        assert_eq!("", slugify("!!!"));
+    }
+}
@@ -0,0 +1,52 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "tags")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub name: String,
+    #[sea_orm(unique)]
+    pub slug: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::post_tag::Entity")]
+    PostTag,
+}
+
+impl Related<super::post_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PostTag.def()
+    }
+}
+
+// post_tag を経由した多対多
+impl Related<super::post::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::post_tag::Relation::Post.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::post_tag::Relation::Tag.def().rev())
+    }
+}
+
+// タグから記事をたどるリンク（tag -> post_tag -> post）
+#[derive(Debug)]
+pub struct TaggedPosts;
+
+impl Linked for TaggedPosts {
+    type FromEntity = Entity;
+    type ToEntity = super::post::Entity;
+
+    fn link(&self) -> Vec<RelationDef> {
+        vec![
+            super::post_tag::Relation::Tag.def().rev(),
+            super::post_tag::Relation::Post.def(),
+        ]
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,12 +1,30 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20230209_111150_create_posts;
+mod m20261019_000001_add_publishing_to_posts;
+mod m20261019_000002_create_comments;
+mod m20261019_000003_create_tags;
+mod m20261019_000004_add_slug_to_posts;
+mod m20261019_000005_add_created_at_to_posts;
+mod m20261019_000006_create_users;
+mod m20261019_000007_create_post_revisions;
+mod m20261019_000008_create_feed_state;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20230209_111150_create_posts::Migration)]
+        vec![
+            Box::new(m20230209_111150_create_posts::Migration),
+            Box::new(m20261019_000001_add_publishing_to_posts::Migration),
+            Box::new(m20261019_000002_create_comments::Migration),
+            Box::new(m20261019_000003_create_tags::Migration),
+            Box::new(m20261019_000004_add_slug_to_posts::Migration),
+            Box::new(m20261019_000005_add_created_at_to_posts::Migration),
+            Box::new(m20261019_000006_create_users::Migration),
+            Box::new(m20261019_000007_create_post_revisions::Migration),
+            Box::new(m20261019_000008_create_feed_state::Migration),
+        ]
     }
 }
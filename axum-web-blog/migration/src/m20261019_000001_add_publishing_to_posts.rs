@@ -0,0 +1,48 @@
+use entity::post;
+use sea_orm_migration::prelude::*;
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite は 1 つの ALTER TABLE で 1 カラムしか追加できないので分けて実行する
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(post::Entity)
+                    .add_column(ColumnDef::new(post::Column::PublishAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(post::Entity)
+                    .add_column(
+                        ColumnDef::new(post::Column::PublishedAt).timestamp_with_time_zone(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(post::Entity)
+                    .drop_column(post::Column::PublishAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(post::Entity)
+                    .drop_column(post::Column::PublishedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
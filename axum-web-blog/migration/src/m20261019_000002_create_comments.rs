@@ -0,0 +1,63 @@
+use entity::{comment, post};
+use sea_orm_migration::prelude::*;
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(comment::Entity)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(comment::Column::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(comment::Column::PostId).integer().not_null())
+                    .col(ColumnDef::new(comment::Column::Author).string().not_null())
+                    .col(ColumnDef::new(comment::Column::Body).text().not_null())
+                    .col(
+                        ColumnDef::new(comment::Column::Status)
+                            .string_len(16)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(
+                        ColumnDef::new(comment::Column::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    // 記事を消したらコメントも消す
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_comments_post_id")
+                            .from(comment::Entity, comment::Column::PostId)
+                            .to(post::Entity, post::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_comments_post_id_status")
+                    .table(comment::Entity)
+                    .col(comment::Column::PostId)
+                    .col(comment::Column::Status)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(comment::Entity).to_owned())
+            .await
+    }
+}
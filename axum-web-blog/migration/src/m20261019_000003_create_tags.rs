@@ -0,0 +1,84 @@
+use entity::{post, post_tag, tag};
+use sea_orm_migration::prelude::*;
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(tag::Entity)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(tag::Column::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(tag::Column::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(tag::Column::Slug)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(post_tag::Entity)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(post_tag::Column::PostId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(post_tag::Column::TagId).integer().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(post_tag::Column::PostId)
+                            .col(post_tag::Column::TagId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_post_tags_post_id")
+                            .from(post_tag::Entity, post_tag::Column::PostId)
+                            .to(post::Entity, post::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_post_tags_tag_id")
+                            .from(post_tag::Entity, post_tag::Column::TagId)
+                            .to(tag::Entity, tag::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // タグから記事を引くための索引（主キーは post_id が先頭なので別に張る）
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_post_tags_tag_id")
+                    .table(post_tag::Entity)
+                    .col(post_tag::Column::TagId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(post_tag::Entity).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(tag::Entity).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,86 @@
+use entity::{post, slug::slugify};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::ConnectionTrait;
+use std::collections::HashSet;
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 既存の行があるので一旦空文字で追加し、埋めてから一意制約を張る
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(post::Entity)
+                    .add_column(
+                        ColumnDef::new(post::Column::Slug)
+                            .string()
+                            .not_null()
+                            .default(""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+        let select = Query::select()
+            .columns([post::Column::Id, post::Column::Title])
+            .from(post::Entity)
+            .order_by(post::Column::Id, Order::Asc)
+            .to_owned();
+        let rows = db.query_all(backend.build(&select)).await?;
+        let mut taken = HashSet::new();
+        for row in rows {
+            let id: i32 = row.try_get("", "id")?;
+            let title: String = row.try_get("", "title")?;
+            let mut base = slugify(&title);
+            if base.is_empty() {
+                base = "post".to_string();
+            }
+            let mut slug = base.clone();
+            let mut n = 2;
+            while !taken.insert(slug.clone()) {
+                slug = format!("{}-{}", base, n);
+                n += 1;
+            }
+            let update = Query::update()
+                .table(post::Entity)
+                .value(post::Column::Slug, slug)
+                .and_where(Expr::col(post::Column::Id).eq(id))
+                .to_owned();
+            db.execute(backend.build(&update)).await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_posts_slug")
+                    .table(post::Entity)
+                    .col(post::Column::Slug)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_posts_slug")
+                    .table(post::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(post::Entity)
+                    .drop_column(post::Column::Slug)
+                    .to_owned(),
+            )
+            .await
+    }
+}
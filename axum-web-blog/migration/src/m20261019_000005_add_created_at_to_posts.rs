@@ -0,0 +1,71 @@
+use entity::post;
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::ConnectionTrait;
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite の ALTER TABLE では CURRENT_TIMESTAMP を既定値にできないので、
+        // 定数で追加してから公開日時（なければ移行した時刻）で埋める
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(post::Entity)
+                    .add_column(
+                        ColumnDef::new(post::Column::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("1970-01-01T00:00:00+00:00"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let now = chrono::Utc::now();
+        let update = Query::update()
+            .table(post::Entity)
+            .value(
+                post::Column::CreatedAt,
+                Func::coalesce([
+                    Expr::col(post::Column::PublishedAt).into(),
+                    Expr::col(post::Column::PublishAt).into(),
+                    Expr::val(now).into(),
+                ]),
+            )
+            .to_owned();
+        let db = manager.get_connection();
+        db.execute(manager.get_database_backend().build(&update))
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_posts_created_at")
+                    .table(post::Entity)
+                    .col(post::Column::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_posts_created_at")
+                    .table(post::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(post::Entity)
+                    .drop_column(post::Column::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
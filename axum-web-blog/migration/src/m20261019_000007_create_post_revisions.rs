@@ -0,0 +1,144 @@
+use entity::{post, post_draft, post_revision};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::ConnectionTrait;
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(post_revision::Entity)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(post_revision::Column::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(post_revision::Column::PostId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(post_revision::Column::Rev)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(post_revision::Column::Title)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(post_revision::Column::Body)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(post_revision::Column::EditorId).integer())
+                    .col(
+                        ColumnDef::new(post_revision::Column::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_post_revisions_post_id")
+                            .from(post_revision::Entity, post_revision::Column::PostId)
+                            .to(post::Entity, post::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // 同じ rev が二重に振られないようにする
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_post_revisions_post_id_rev")
+                    .table(post_revision::Entity)
+                    .col(post_revision::Column::PostId)
+                    .col(post_revision::Column::Rev)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(post_draft::Entity)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(post_draft::Column::PostId)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(post_draft::Column::Title)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(post_draft::Column::Body).text().not_null())
+                    .col(ColumnDef::new(post_draft::Column::EditorId).integer())
+                    .col(
+                        ColumnDef::new(post_draft::Column::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_post_drafts_post_id")
+                            .from(post_draft::Entity, post_draft::Column::PostId)
+                            .to(post::Entity, post::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 既存の記事は今の内容を rev 1 として残しておく
+        let backfill = Query::insert()
+            .into_table(post_revision::Entity)
+            .columns([
+                post_revision::Column::PostId,
+                post_revision::Column::Rev,
+                post_revision::Column::Title,
+                post_revision::Column::Body,
+                post_revision::Column::EditorId,
+                post_revision::Column::CreatedAt,
+            ])
+            .select_from(
+                Query::select()
+                    .column(post::Column::Id)
+                    .expr(Expr::val(1))
+                    .columns([
+                        post::Column::Title,
+                        post::Column::Body,
+                        post::Column::AuthorId,
+                        post::Column::CreatedAt,
+                    ])
+                    .from(post::Entity)
+                    .to_owned(),
+            )
+            .map_err(|e| DbErr::Migration(e.to_string()))?
+            .to_owned();
+        let db = manager.get_connection();
+        db.execute(manager.get_database_backend().build(&backfill))
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(post_draft::Entity).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(post_revision::Entity).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,298 @@
+use crate::config::AppConfig;
+use crate::error::BlogError;
+use crate::repository::user::UserQuery;
+use ::entity::{
+    post,
+    user::{self, UserRole},
+};
+use argon2::{password_hash::SaltString, Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use axum::{
+    async_trait,
+    extract::{FromRequest, RequestParts},
+    http::header::AUTHORIZATION,
+};
+use chrono::{DateTime, Utc};
+use rand_core::{OsRng, RngCore};
+use sea_orm::{ConnectionTrait, DbErr};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, OnceLock};
+use subtle::ConstantTimeEq;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Caller {
+    Anonymous,
+    // ADMIN_TOKEN で認証された管理者（ユーザーには紐づかない）
+    Admin,
+    User(user::Model),
+}
+
+impl Caller {
+    pub fn is_admin(&self) -> bool {
+        match self {
+            Caller::Admin => true,
+            Caller::User(user) => user.role == UserRole::Admin,
+            Caller::Anonymous => false,
+        }
+    }
+
+    pub fn user_id(&self) -> Option<i32> {
+        match self {
+            Caller::User(user) => Some(user.id),
+            _ => None,
+        }
+    }
+
+    // 管理者か、その記事を書いた本人なら編集できる
+    pub fn can_edit(&self, post: &post::Model) -> bool {
+        self.is_admin() || (self.user_id().is_some() && self.user_id() == post.author_id)
+    }
+
+    pub fn require_login(&self) -> Result<(), BlogError> {
+        match self {
+            Caller::Anonymous => Err(BlogError::Unauthorized),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn require_admin(&self) -> Result<(), BlogError> {
+        self.require_login()?;
+        if !self.is_admin() {
+            return Err(BlogError::Forbidden("admin only".to_string()));
+        }
+        Ok(())
+    }
+
+    // 未ログインなら 401、ログインしていても本人でも管理者でもなければ 403
+    pub fn require_editor(&self, post: &post::Model) -> Result<(), BlogError> {
+        self.require_login()?;
+        if !self.can_edit(post) {
+            return Err(BlogError::Forbidden(format!(
+                "only the author or an admin can modify post {}",
+                post.id
+            )));
+        }
+        Ok(())
+    }
+}
+
+// トークンからユーザーを引く。エクストラクタはコネクションの型を知らないのでトレイトオブジェクトで渡す
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    async fn find_user_by_token(
+        &self,
+        token: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<user::Model>, DbErr>;
+}
+
+#[async_trait]
+impl<C: ConnectionTrait + Send> SessionStore for C {
+    async fn find_user_by_token(
+        &self,
+        token: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<user::Model>, DbErr> {
+        UserQuery::find_user_by_token_hash(self, &hash_token(token), now).await
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for Caller {
+    type Rejection = BlogError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let given = match req
+            .headers()
+            .and_then(|headers| headers.get(AUTHORIZATION))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        {
+            Some(given) => given.to_string(),
+            None => return Ok(Caller::Anonymous),
+        };
+        let extensions = req.extensions();
+        let admin_token = extensions
+            .and_then(|ext| ext.get::<Arc<AppConfig>>())
+            .and_then(|config| config.admin_token.as_deref());
+        if admin_token.is_some_and(|admin_token| is_admin_token(admin_token, &given)) {
+            return Ok(Caller::Admin);
+        }
+
+        // 期限切れや不明なトークンは匿名として扱い、保護された操作で 401 にする
+        let store = match extensions.and_then(|ext| ext.get::<Arc<dyn SessionStore>>()) {
+            Some(store) => store.clone(),
+            None => return Ok(Caller::Anonymous),
+        };
+        Ok(match store.find_user_by_token(&given, Utc::now()).await? {
+            Some(user) => Caller::User(user),
+            None => Caller::Anonymous,
+        })
+    }
+}
+
+// 比較にかかる時間から管理者トークンを 1 文字ずつ推測されないよう、
+// 長さを揃えた SHA-256 のダイジェスト同士を定数時間で比べる
+fn is_admin_token(admin_token: &str, given: &str) -> bool {
+    Sha256::digest(admin_token.as_bytes())
+        .ct_eq(&Sha256::digest(given.as_bytes()))
+        .into()
+}
+
+pub fn hash_password(password: &str) -> Result<String, BlogError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| BlogError::Internal(format!("failed to hash password: {}", e)))
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+// Argon2 は重いので、ランタイムのワーカーを塞がないよう blocking 用のスレッドで計算する
+pub async fn spawn_hash_password(password: String) -> Result<String, BlogError> {
+    tokio::task::spawn_blocking(move || hash_password(&password))
+        .await
+        .map_err(|e| BlogError::Internal(format!("failed to hash password: {}", e)))?
+}
+
+// ユーザーが見つからない（password_hash が None）ときもダミーのハッシュと照合して false を返す。
+// 応答時間からユーザー名が登録済みかどうかを推測させない
+pub async fn spawn_verify_password(
+    password: String,
+    password_hash: Option<String>,
+) -> Result<bool, BlogError> {
+    tokio::task::spawn_blocking(move || match password_hash {
+        Some(password_hash) => verify_password(&password, &password_hash),
+        None => {
+            verify_password(&password, dummy_password_hash());
+            false
+        }
+    })
+    .await
+    .map_err(|e| BlogError::Internal(format!("failed to verify password: {}", e)))
+}
+
+// 本物と同じパラメータで作っておき、照合にかかる時間を揃える
+fn dummy_password_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| hash_password("dummy password").unwrap_or_default())
+}
+
+// 推測されないよう 256 bit の乱数を 16 進文字列にしてトークンにする
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn user(id: i32, role: UserRole) -> user::Model {
+        user::Model {
+            id,
+            username: format!("user{}", id),
+            password_hash: String::new(),
+            role,
+            created_at: Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+        }
+    }
+
+    fn post_by(author_id: Option<i32>) -> post::Model {
+        post::Model {
+            id: 1,
+            title: "title".to_string(),
+            slug: "title".to_string(),
+            body: "body".to_string(),
+            published: false,
+            publish_at: None,
+            published_at: None,
+            created_at: Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+            author_id,
+        }
+    }
+
+    #[test]
+    fn only_author_or_admin_can_edit() {
+        let post = post_by(Some(1));
+        assert!(Caller::User(user(1, UserRole::Author)).can_edit(&post));
+        assert!(!Caller::User(user(2, UserRole::Author)).can_edit(&post));
+        assert!(Caller::User(user(2, UserRole::Admin)).can_edit(&post));
+        assert!(Caller::Admin.can_edit(&post));
+        assert!(!Caller::Anonymous.can_edit(&post));
+        // 作者のいない古い記事は管理者だけ
+        assert!(!Caller::User(user(1, UserRole::Author)).can_edit(&post_by(None)));
+    }
+
+    #[test]
+    fn require_editor_distinguishes_401_and_403() {
+        let post = post_by(Some(1));
+        assert!(matches!(
+            Caller::Anonymous.require_editor(&post),
+            Err(BlogError::Unauthorized)
+        ));
+        assert!(matches!(
+            Caller::User(user(2, UserRole::Author)).require_editor(&post),
+            Err(BlogError::Forbidden(_))
+        ));
+        assert!(Caller::User(user(1, UserRole::Author))
+            .require_editor(&post)
+            .is_ok());
+    }
+
+    #[test]
+    fn verifies_hashed_password() {
+        let hash = hash_password("correct horse").unwrap();
+        assert_ne!("correct horse", hash);
+        assert!(verify_password("correct horse", &hash));
+        assert!(!verify_password("wrong horse", &hash));
+        assert!(!verify_password("correct horse", "not a hash"));
+    }
+
+    #[tokio::test]
+    async fn verifies_unknown_user_against_dummy_hash() {
+        let hash = spawn_hash_password("correct horse".to_owned())
+            .await
+            .unwrap();
+        assert!(
+            spawn_verify_password("correct horse".to_owned(), Some(hash))
+                .await
+                .unwrap()
+        );
+        assert!(!spawn_verify_password("dummy password".to_owned(), None)
+            .await
+            .unwrap());
+        assert!(PasswordHash::new(dummy_password_hash()).is_ok());
+    }
+
+    #[test]
+    fn compares_admin_token() {
+        assert!(is_admin_token("secret-token", "secret-token"));
+        assert!(!is_admin_token("secret-token", "secret-tokem"));
+        assert!(!is_admin_token("secret-token", "secret"));
+        assert!(!is_admin_token("secret-token", ""));
+    }
+
+    #[test]
+    fn tokens_are_random_and_hashed() {
+        let token = generate_token();
+        assert_eq!(64, token.len());
+        assert_ne!(token, generate_token());
+        assert_eq!(64, hash_token(&token).len());
+        assert_ne!(token, hash_token(&token));
+    }
+}
@@ -0,0 +1,144 @@
+use sea_orm::ConnectOptions;
+use std::time::Duration;
+
+// アプリ全体の設定。ハンドラからは Extension<Arc<AppConfig>> で参照する
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    // 管理者として扱う Bearer トークン（None なら誰も管理者にならない）
+    pub admin_token: Option<String>,
+    pub site_title: String,
+    // フィードやサイトマップに載せる URL の起点（末尾の / なし）
+    pub base_url: String,
+    // フィードやサイトマップに載せる記事の URL（base_url からのパス）。{slug} と {id} を置き換える
+    pub permalink: String,
+    // ログインで発行したトークンの有効期間（時間）
+    pub session_ttl_hours: i64,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            admin_token: None,
+            site_title: "axum-web-blog".to_string(),
+            base_url: "http://localhost:3000".to_string(),
+            // フロントエンドが別にあるなら BLOG_PERMALINK でその記事ページを指す
+            permalink: "/posts/by-slug/{slug}".to_string(),
+            session_ttl_hours: 24 * 7,
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn from_env<F>(var: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let default = Self::default();
+        Self {
+            admin_token: var("ADMIN_TOKEN"),
+            site_title: var("BLOG_TITLE").unwrap_or(default.site_title),
+            base_url: var("BLOG_BASE_URL")
+                .map(|url| url.trim_end_matches('/').to_string())
+                .unwrap_or(default.base_url),
+            permalink: var("BLOG_PERMALINK")
+                .filter(|path| path.starts_with('/'))
+                .unwrap_or(default.permalink),
+            session_ttl_hours: var("SESSION_TTL_HOURS")
+                .and_then(|hours| hours.parse().ok())
+                .filter(|hours| *hours > 0)
+                .unwrap_or(default.session_ttl_hours),
+        }
+    }
+}
+
+// 環境変数からプールの設定を組み立てる（テストしやすいよう取得関数を受け取る）
+pub fn connect_options<F>(var: F) -> Result<ConnectOptions, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let url = var("DATABASE_URL").ok_or("undefined [DATABASE_URL]")?;
+    let parse = |key: &str, default: u64| -> Result<u64, String> {
+        match var(key) {
+            Some(v) => v
+                .parse()
+                .map_err(|_| format!("[{}] must be a positive integer, got [{}]", key, v)),
+            None => Ok(default),
+        }
+    };
+    let max = parse("DATABASE_MAX_CONNECTIONS", 10)? as u32;
+    let min = parse("DATABASE_MIN_CONNECTIONS", 1)? as u32;
+    let timeout = parse("DATABASE_CONNECT_TIMEOUT", 8)?;
+    if max == 0 || min > max {
+        return Err(format!(
+            "[DATABASE_MIN_CONNECTIONS]({}) must be <= [DATABASE_MAX_CONNECTIONS]({}) and max must be > 0",
+            min, max
+        ));
+    }
+
+    let mut options = ConnectOptions::new(url);
+    options
+        .max_connections(max)
+        .min_connections(min)
+        .connect_timeout(Duration::from_secs(timeout));
+    Ok(options)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| map.get(key).cloned()
+    }
+
+    #[test]
+    fn connect_options_uses_pool_settings() {
+        let options = connect_options(vars(&[
+            ("DATABASE_URL", "sqlite::memory:"),
+            ("DATABASE_MAX_CONNECTIONS", "20"),
+            ("DATABASE_MIN_CONNECTIONS", "5"),
+        ]))
+        .unwrap();
+        assert_eq!(Some(20), options.get_max_connections());
+        assert_eq!(Some(5), options.get_min_connections());
+        assert_eq!(Some(Duration::from_secs(8)), options.get_connect_timeout());
+    }
+
+    #[test]
+    fn connect_options_rejects_invalid_settings() {
+        assert!(connect_options(vars(&[])).is_err());
+        assert!(connect_options(vars(&[
+            ("DATABASE_URL", "sqlite::memory:"),
+            ("DATABASE_MAX_CONNECTIONS", "many"),
+        ]))
+        .is_err());
+        assert!(connect_options(vars(&[
+            ("DATABASE_URL", "sqlite::memory:"),
+            ("DATABASE_MAX_CONNECTIONS", "2"),
+            ("DATABASE_MIN_CONNECTIONS", "3"),
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn app_config_from_env() {
+        let config = AppConfig::from_env(vars(&[("BLOG_BASE_URL", "https://blog.example.com/")]));
+        assert_eq!("https://blog.example.com", config.base_url);
+        assert_eq!("axum-web-blog", config.site_title);
+        assert_eq!(None, config.admin_token);
+        assert_eq!(24 * 7, config.session_ttl_hours);
+        assert_eq!("/posts/by-slug/{slug}", config.permalink);
+
+        let config = AppConfig::from_env(vars(&[
+            ("SESSION_TTL_HOURS", "12"),
+            ("BLOG_PERMALINK", "/articles/{id}"),
+        ]));
+        assert_eq!(12, config.session_ttl_hours);
+        assert_eq!("/articles/{id}", config.permalink);
+    }
+}
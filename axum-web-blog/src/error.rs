@@ -0,0 +1,152 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sea_orm::DbErr;
+use serde_json::json;
+use std::collections::BTreeMap;
+use thiserror::Error;
+use validator::ValidationErrors;
+
+#[derive(Debug, Error)]
+pub enum BlogError {
+    #[error("{0} not found")]
+    NotFound(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("validation failed")]
+    Validation(BTreeMap<String, Vec<String>>),
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("authentication required")]
+    Unauthorized,
+    #[error("invalid username or password")]
+    InvalidCredentials,
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("database error: {0}")]
+    Database(DbErr),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl BlogError {
+    fn status(&self) -> StatusCode {
+        match self {
+            BlogError::NotFound(_) => StatusCode::NOT_FOUND,
+            BlogError::Conflict(_) => StatusCode::CONFLICT,
+            BlogError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            BlogError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            BlogError::Unauthorized | BlogError::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            BlogError::Forbidden(_) => StatusCode::FORBIDDEN,
+            BlogError::Database(_) | BlogError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            BlogError::NotFound(_) => "not_found",
+            BlogError::Conflict(_) => "conflict",
+            BlogError::Validation(_) => "validation",
+            BlogError::BadRequest(_) => "bad_request",
+            BlogError::Unauthorized => "unauthorized",
+            BlogError::InvalidCredentials => "invalid_credentials",
+            BlogError::Forbidden(_) => "forbidden",
+            BlogError::Database(_) | BlogError::Internal(_) => "internal",
+        }
+    }
+}
+
+// DbErr の中身からステータスを決める。一意制約違反はメッセージで判別するしかない
+impl From<DbErr> for BlogError {
+    fn from(e: DbErr) -> Self {
+        match e {
+            DbErr::RecordNotFound(what) => BlogError::NotFound(what),
+            DbErr::Exec(ref err) | DbErr::Query(ref err)
+                if is_unique_violation(&err.to_string()) =>
+            {
+                BlogError::Conflict("resource already exists".to_string())
+            }
+            e => BlogError::Database(e),
+        }
+    }
+}
+
+fn is_unique_violation(message: &str) -> bool {
+    message.contains("UNIQUE constraint failed") || message.contains("duplicate key value")
+}
+
+impl From<ValidationErrors> for BlogError {
+    fn from(errors: ValidationErrors) -> Self {
+        let fields = errors
+            .field_errors()
+            .into_iter()
+            .map(|(field, errors)| {
+                let messages = errors
+                    .iter()
+                    .map(|e| match &e.message {
+                        Some(message) => message.to_string(),
+                        None => e.code.to_string(),
+                    })
+                    .collect();
+                (field.to_string(), messages)
+            })
+            .collect();
+        BlogError::Validation(fields)
+    }
+}
+
+impl IntoResponse for BlogError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        // 内部エラーの詳細はクライアントに返さない
+        let message = match &self {
+            BlogError::Database(_) | BlogError::Internal(_) => "internal server error".to_string(),
+            e => e.to_string(),
+        };
+        let body = match &self {
+            BlogError::Validation(fields) => {
+                json!({ "error": self.code(), "message": message, "fields": fields })
+            }
+            _ => json!({ "error": self.code(), "message": message }),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sea_orm::RuntimeErr;
+
+    #[test]
+    fn maps_db_errors() {
+        assert!(matches!(
+            BlogError::from(DbErr::RecordNotFound("post 1".to_string())),
+            BlogError::NotFound(_)
+        ));
+        assert!(matches!(
+            BlogError::from(DbErr::Exec(RuntimeErr::Internal(
+                "UNIQUE constraint failed: posts.slug".to_string()
+            ))),
+            BlogError::Conflict(_)
+        ));
+        assert!(matches!(
+            BlogError::from(DbErr::Conn(RuntimeErr::Internal("down".to_string()))),
+            BlogError::Database(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn renders_json_body() {
+        let res = BlogError::NotFound("post 1".to_string()).into_response();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(
+            json!({ "error": "not_found", "message": "post 1 not found" }),
+            body
+        );
+    }
+}
@@ -0,0 +1,345 @@
+use crate::{config::AppConfig, markdown};
+use ::entity::post;
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::HashMap;
+
+// フィードに載せる件数
+const FEED_LIMIT: usize = 20;
+
+pub struct FeedPost {
+    pub post: post::Model,
+    pub published: DateTime<Utc>,
+    // 公開日時と、最後に内容を保存した日時の新しい方
+    pub updated: DateTime<Utc>,
+}
+
+// 公開中の記事を公開日時の新しい順に並べる（同時刻なら id の大きい方を先に）。
+// revised は記事ごとの最後の保存日時
+pub fn prepare(
+    posts: Vec<post::Model>,
+    revised: &HashMap<i32, DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Vec<FeedPost> {
+    let mut posts: Vec<FeedPost> = posts
+        .into_iter()
+        .filter(|p| p.is_public(now))
+        .filter_map(|post| {
+            let published = post.published_time(now)?;
+            let updated = revised
+                .get(&post.id)
+                .map_or(published, |revised| published.max(*revised));
+            Some(FeedPost {
+                post,
+                published,
+                updated,
+            })
+        })
+        .collect();
+    posts.sort_by(|a, b| {
+        b.published
+            .cmp(&a.published)
+            .then_with(|| b.post.id.cmp(&a.post.id))
+    });
+    posts
+}
+
+// 記事がないときも値が変わらないよう UNIX エポックにする
+pub fn last_modified(posts: &[FeedPost]) -> DateTime<Utc> {
+    posts
+        .iter()
+        .map(|p| p.updated)
+        .max()
+        .unwrap_or_else(|| Utc.timestamp_opt(0, 0).unwrap())
+}
+
+fn post_url(config: &AppConfig, post: &post::Model) -> String {
+    let path = config
+        .permalink
+        .replace("{slug}", &post.slug)
+        .replace("{id}", &post.id.to_string());
+    format!("{}{}", config.base_url, path)
+}
+
+pub fn atom(config: &AppConfig, posts: &[FeedPost]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!(
+        "  <title>{}</title>\n",
+        escape(&config.site_title)
+    ));
+    xml.push_str(&format!("  <id>{}/</id>\n", escape(&config.base_url)));
+    xml.push_str(&format!(
+        "  <link rel=\"alternate\" href=\"{}/\"/>\n",
+        escape(&config.base_url)
+    ));
+    xml.push_str(&format!(
+        "  <link rel=\"self\" href=\"{}/feed.xml\"/>\n",
+        escape(&config.base_url)
+    ));
+    xml.push_str(&format!(
+        "  <updated>{}</updated>\n",
+        last_modified(posts).to_rfc3339()
+    ));
+    for p in posts.iter().take(FEED_LIMIT) {
+        let url = escape(&post_url(config, &p.post));
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", escape(&p.post.title)));
+        xml.push_str(&format!("    <id>{}</id>\n", url));
+        xml.push_str(&format!("    <link rel=\"alternate\" href=\"{}\"/>\n", url));
+        xml.push_str(&format!(
+            "    <published>{}</published>\n",
+            p.published.to_rfc3339()
+        ));
+        xml.push_str(&format!(
+            "    <updated>{}</updated>\n",
+            p.updated.to_rfc3339()
+        ));
+        xml.push_str(&format!(
+            "    <summary>{}</summary>\n",
+            escape(&markdown::excerpt(&p.post.body, 200))
+        ));
+        xml.push_str(&format!(
+            "    <content type=\"html\">{}</content>\n",
+            escape(&markdown::render_html(&p.post.body))
+        ));
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+pub fn rss(config: &AppConfig, posts: &[FeedPost]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\">\n");
+    xml.push_str("  <channel>\n");
+    xml.push_str(&format!(
+        "    <title>{}</title>\n",
+        escape(&config.site_title)
+    ));
+    xml.push_str(&format!("    <link>{}/</link>\n", escape(&config.base_url)));
+    xml.push_str(&format!(
+        "    <description>{}</description>\n",
+        escape(&config.site_title)
+    ));
+    xml.push_str(&format!(
+        "    <lastBuildDate>{}</lastBuildDate>\n",
+        last_modified(posts).to_rfc2822()
+    ));
+    for p in posts.iter().take(FEED_LIMIT) {
+        let url = escape(&post_url(config, &p.post));
+        xml.push_str("    <item>\n");
+        xml.push_str(&format!("      <title>{}</title>\n", escape(&p.post.title)));
+        xml.push_str(&format!("      <link>{}</link>\n", url));
+        xml.push_str(&format!(
+            "      <guid isPermaLink=\"true\">{}</guid>\n",
+            url
+        ));
+        xml.push_str(&format!(
+            "      <pubDate>{}</pubDate>\n",
+            p.published.to_rfc2822()
+        ));
+        xml.push_str(&format!(
+            "      <description>{}</description>\n",
+            escape(&markdown::excerpt(&p.post.body, 200))
+        ));
+        xml.push_str("    </item>\n");
+    }
+    xml.push_str("  </channel>\n");
+    xml.push_str("</rss>\n");
+    xml
+}
+
+// サイトマップには件数制限をかけず全記事を載せる
+pub fn sitemap(config: &AppConfig, posts: &[FeedPost]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    xml.push_str(&format!(
+        "  <url>\n    <loc>{}/</loc>\n    <lastmod>{}</lastmod>\n  </url>\n",
+        escape(&config.base_url),
+        last_modified(posts).format("%Y-%m-%d")
+    ));
+    for p in posts {
+        xml.push_str(&format!(
+            "  <url>\n    <loc>{}</loc>\n    <lastmod>{}</lastmod>\n  </url>\n",
+            escape(&post_url(config, &p.post)),
+            p.updated.format("%Y-%m-%d")
+        ));
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config() -> AppConfig {
+        AppConfig {
+            site_title: "Tom & Jerry's blog".to_string(),
+            base_url: "https://blog.example.com".to_string(),
+            ..AppConfig::default()
+        }
+    }
+
+    // フィクスチャの記事。下書きと予約中の記事はフィードに出ない
+    fn fixture_posts() -> Vec<post::Model> {
+        let at = |d: u32, h: u32| Utc.with_ymd_and_hms(2026, 10, d, h, 0, 0).unwrap();
+        let base = post::Model {
+            id: 0,
+            title: String::new(),
+            slug: String::new(),
+            body: String::new(),
+            published: true,
+            publish_at: None,
+            published_at: None,
+            created_at: at(1, 0),
+            author_id: None,
+        };
+        vec![
+            post::Model {
+                id: 1,
+                title: "Hello World".to_string(),
+                slug: "hello-world".to_string(),
+                body: "First *post*.".to_string(),
+                published_at: Some(at(1, 9)),
+                ..base.clone()
+            },
+            post::Model {
+                id: 2,
+                title: "Escaping <tags> & \"quotes\"".to_string(),
+                slug: "escaping-tags-quotes".to_string(),
+                body: "Use `a < b` & <b>bold</b>".to_string(),
+                published_at: Some(at(5, 12)),
+                ..base.clone()
+            },
+            post::Model {
+                id: 3,
+                title: "Draft".to_string(),
+                slug: "draft".to_string(),
+                body: "not yet".to_string(),
+                published: false,
+                ..base.clone()
+            },
+            post::Model {
+                id: 4,
+                title: "Scheduled".to_string(),
+                slug: "scheduled".to_string(),
+                body: "later".to_string(),
+                published: false,
+                publish_at: Some(at(30, 0)),
+                ..base
+            },
+        ]
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn prepare_orders_public_posts() {
+        let posts = prepare(fixture_posts(), &HashMap::new(), now());
+        assert_eq!(
+            vec![2, 1],
+            posts.iter().map(|p| p.post.id).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Utc.with_ymd_and_hms(2026, 10, 5, 12, 0, 0).unwrap(),
+            last_modified(&posts)
+        );
+    }
+
+    #[test]
+    fn edits_after_publishing_bump_updated() {
+        let edited = Utc.with_ymd_and_hms(2026, 10, 10, 8, 0, 0).unwrap();
+        // 公開前の保存は公開日時より古いので影響しない
+        let revised = HashMap::from([
+            (1, edited),
+            (2, Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap()),
+        ]);
+        let posts = prepare(fixture_posts(), &revised, now());
+        assert_eq!(
+            vec![2, 1],
+            posts.iter().map(|p| p.post.id).collect::<Vec<_>>()
+        );
+        assert_eq!(edited, posts[1].updated);
+        assert_eq!(posts[0].published, posts[0].updated);
+        assert_eq!(edited, last_modified(&posts));
+        assert!(atom(&config(), &posts).contains("<updated>2026-10-10T08:00:00+00:00</updated>"));
+    }
+
+    #[test]
+    fn atom_matches_fixture() {
+        let posts = prepare(fixture_posts(), &HashMap::new(), now());
+        assert_eq!(
+            include_str!("../fixtures/feed/atom.xml"),
+            atom(&config(), &posts)
+        );
+    }
+
+    #[test]
+    fn rss_matches_fixture() {
+        let posts = prepare(fixture_posts(), &HashMap::new(), now());
+        assert_eq!(
+            include_str!("../fixtures/feed/rss.xml"),
+            rss(&config(), &posts)
+        );
+    }
+
+    #[test]
+    fn sitemap_matches_fixture() {
+        let posts = prepare(fixture_posts(), &HashMap::new(), now());
+        assert_eq!(
+            include_str!("../fixtures/feed/sitemap.xml"),
+            sitemap(&config(), &posts)
+        );
+    }
+
+    #[test]
+    fn post_url_follows_permalink() {
+        let post = &fixture_posts()[0];
+        assert_eq!(
+            format!("https://blog.example.com/posts/by-slug/{}", post.slug),
+            post_url(&config(), post)
+        );
+        let config = AppConfig {
+            permalink: "/#/posts/{id}".to_string(),
+            ..config()
+        };
+        assert_eq!(
+            format!("https://blog.example.com/#/posts/{}", post.id),
+            post_url(&config, post)
+        );
+    }
+
+    #[test]
+    fn outputs_are_well_formed_xml() {
+        let posts = prepare(fixture_posts(), &HashMap::new(), now());
+        for xml in [
+            atom(&config(), &posts),
+            rss(&config(), &posts),
+            sitemap(&config(), &posts),
+            atom(&config(), &[]),
+        ] {
+            roxmltree::Document::parse(&xml).unwrap_or_else(|e| panic!("{}: {}", e, xml));
+        }
+    }
+}
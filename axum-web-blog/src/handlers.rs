@@ -0,0 +1,101 @@
+use crate::error::BlogError;
+use axum::{
+    async_trait,
+    body::{Bytes, HttpBody},
+    extract::{FromRequest, Json, Query, RequestParts},
+    BoxError,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use validator::Validate;
+
+pub mod comment;
+pub mod draft;
+pub mod feed;
+pub mod post;
+pub mod revision;
+pub mod tag;
+pub mod user;
+
+// JSON を読み込んだあと validator で検証するエクストラクタ
+#[derive(Debug)]
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, B> FromRequest<B> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = BlogError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req).await.map_err(|rejection| {
+            BlogError::BadRequest(format!("Json parse error: [{}]", rejection))
+        })?;
+        value.validate()?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+// 本文が空なら None、あれば JSON として読み込むエクストラクタ。
+// 読めない本文を None 扱いにして、省略したときの動作に流れないようにする
+#[derive(Debug)]
+pub struct OptionalJson<T>(pub Option<T>);
+
+#[async_trait]
+impl<T, B> FromRequest<B> for OptionalJson<T>
+where
+    T: DeserializeOwned,
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = BlogError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let bytes = Bytes::from_request(req)
+            .await
+            .map_err(|rejection| BlogError::BadRequest(rejection.to_string()))?;
+        if bytes.is_empty() {
+            return Ok(OptionalJson(None));
+        }
+        let value = serde_json::from_slice(&bytes)
+            .map_err(|e| BlogError::BadRequest(format!("Json parse error: [{}]", e)))?;
+        Ok(OptionalJson(Some(value)))
+    }
+}
+
+// クエリ文字列を読み込んだあと validator で検証するエクストラクタ
+#[derive(Debug)]
+pub struct ValidatedQuery<T>(pub T);
+
+#[async_trait]
+impl<T, B> FromRequest<B> for ValidatedQuery<T>
+where
+    T: DeserializeOwned + Validate,
+    B: Send,
+{
+    type Rejection = BlogError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request(req).await.map_err(|rejection| {
+            BlogError::BadRequest(format!("Query parse error: [{}]", rejection))
+        })?;
+        value.validate()?;
+        Ok(ValidatedQuery(value))
+    }
+}
+
+pub async fn hello_world() -> Json<HelloWorld> {
+    let hello = HelloWorld {
+        text: "Hello World".to_string(),
+    };
+    Json(hello)
+}
+
+#[derive(Serialize)]
+pub struct HelloWorld {
+    text: String,
+}
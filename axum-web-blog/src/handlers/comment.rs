@@ -0,0 +1,135 @@
+use super::{post::find_visible_post, ValidatedJson};
+use crate::auth::Caller;
+use crate::error::BlogError;
+use crate::repository::comment::{CommentCreate, CommentMutation, CommentQuery};
+use ::entity::comment::{self, CommentStatus};
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use sea_orm::ConnectionTrait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use validator::Validate;
+
+#[derive(Deserialize, Validate)]
+pub struct RequestCreateComment {
+    #[validate(length(min = 1, max = 50, message = "author must be 1-50 characters"))]
+    author: String,
+    #[validate(length(min = 1, max = 2000, message = "body must be 1-2000 characters"))]
+    body: String,
+}
+
+#[derive(Deserialize)]
+pub struct CommentListQuery {
+    status: Option<CommentStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ResponseComment {
+    pub id: i32,
+    pub post_id: i32,
+    pub author: String,
+    pub body: String,
+    pub status: CommentStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<comment::Model> for ResponseComment {
+    fn from(comment: comment::Model) -> Self {
+        Self {
+            id: comment.id,
+            post_id: comment.post_id,
+            author: comment.author,
+            body: comment.body,
+            status: comment.status,
+            created_at: comment.created_at,
+        }
+    }
+}
+
+// 匿名ユーザーには承認済みのみ、管理者には status で絞り込んだ一覧を返す
+pub async fn all_comment<C: ConnectionTrait + Send + 'static>(
+    Path(post_id): Path<i32>,
+    Query(query): Query<CommentListQuery>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    find_visible_post(db.as_ref(), post_id, &caller, Utc::now()).await?;
+    let status = if caller.is_admin() {
+        query.status
+    } else {
+        Some(CommentStatus::Approved)
+    };
+    let comments = CommentQuery::find_comments_by_post(db.as_ref(), post_id, status).await?;
+
+    Ok(Json(
+        comments
+            .into_iter()
+            .map(ResponseComment::from)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+// 投稿されたコメントは承認されるまで pending
+pub async fn create_comment<C: ConnectionTrait + Send + 'static>(
+    Path(post_id): Path<i32>,
+    caller: Caller,
+    ValidatedJson(payload): ValidatedJson<RequestCreateComment>,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    let now = Utc::now();
+    find_visible_post(db.as_ref(), post_id, &caller, now).await?;
+    let data = CommentCreate {
+        author: payload.author,
+        body: payload.body,
+    };
+    let comment = CommentMutation::create_comment(db.as_ref(), post_id, data, now).await?;
+
+    Ok((StatusCode::CREATED, Json(ResponseComment::from(comment))))
+}
+
+pub async fn approve_comment<C: ConnectionTrait + Send + 'static>(
+    Path((post_id, id)): Path<(i32, i32)>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    moderate(db.as_ref(), &caller, post_id, id, CommentStatus::Approved).await
+}
+
+pub async fn reject_comment<C: ConnectionTrait + Send + 'static>(
+    Path((post_id, id)): Path<(i32, i32)>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    moderate(db.as_ref(), &caller, post_id, id, CommentStatus::Rejected).await
+}
+
+async fn moderate<C: ConnectionTrait>(
+    db: &C,
+    caller: &Caller,
+    post_id: i32,
+    id: i32,
+    status: CommentStatus,
+) -> Result<Json<ResponseComment>, BlogError> {
+    caller.require_admin()?;
+    let comment = CommentMutation::moderate_comment(db, post_id, id, status).await?;
+
+    Ok(Json(ResponseComment::from(comment)))
+}
+
+pub async fn delete_comment<C: ConnectionTrait + Send + 'static>(
+    Path((post_id, id)): Path<(i32, i32)>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<StatusCode, BlogError> {
+    caller.require_admin()?;
+    let res = CommentMutation::delete_comment(db.as_ref(), post_id, id).await?;
+    if res.rows_affected == 0 {
+        return Err(BlogError::NotFound(format!("comment {}", id)));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,88 @@
+use super::post::find_editable_post;
+use super::ValidatedJson;
+use crate::auth::Caller;
+use crate::error::BlogError;
+use crate::repository::draft::{DraftMutation, DraftQuery, DraftSave};
+use ::entity::post_draft;
+use axum::{
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use sea_orm::ConnectionTrait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use validator::Validate;
+
+// 書きかけを自動保存するので、公開中の記事とは違い空でも受け付ける
+#[derive(Deserialize, Validate)]
+pub struct RequestSaveDraft {
+    #[validate(length(max = 100, message = "title must be at most 100 characters"))]
+    title: String,
+    #[validate(length(max = 10000, message = "body must be at most 10000 characters"))]
+    body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ResponseDraft {
+    pub post_id: i32,
+    pub title: String,
+    pub body: String,
+    pub editor_id: Option<i32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<post_draft::Model> for ResponseDraft {
+    fn from(draft: post_draft::Model) -> Self {
+        Self {
+            post_id: draft.post_id,
+            title: draft.title,
+            body: draft.body,
+            editor_id: draft.editor_id,
+            updated_at: draft.updated_at,
+        }
+    }
+}
+
+pub async fn find_draft<C: ConnectionTrait + Send + 'static>(
+    Path(id): Path<i32>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    find_editable_post(db.as_ref(), id, &caller).await?;
+    let draft = DraftQuery::find_draft(db.as_ref(), id)
+        .await?
+        .ok_or_else(|| BlogError::NotFound(format!("draft of post {}", id)))?;
+
+    Ok(Json(ResponseDraft::from(draft)))
+}
+
+// 公開中の記事には触れずに編集中の内容だけを保存する。反映するときは PATCH /posts/:id を使う
+pub async fn save_draft<C: ConnectionTrait + Send + 'static>(
+    Path(id): Path<i32>,
+    caller: Caller,
+    ValidatedJson(payload): ValidatedJson<RequestSaveDraft>,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    find_editable_post(db.as_ref(), id, &caller).await?;
+    let data = DraftSave {
+        title: payload.title,
+        body: payload.body,
+        editor_id: caller.user_id(),
+    };
+    let draft = DraftMutation::save_draft(db.as_ref(), id, data, Utc::now()).await?;
+
+    Ok(Json(ResponseDraft::from(draft)))
+}
+
+pub async fn delete_draft<C: ConnectionTrait + Send + 'static>(
+    Path(id): Path<i32>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    find_editable_post(db.as_ref(), id, &caller).await?;
+    DraftMutation::delete_draft(db.as_ref(), id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,135 @@
+use crate::config::AppConfig;
+use crate::error::BlogError;
+use crate::feed::{self, FeedPost};
+use crate::repository::{feed::FeedQuery, post::PostQuery, revision::RevisionQuery};
+use axum::{
+    extract::Extension,
+    http::{
+        header::{self, HeaderMap, HeaderValue},
+        StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use sea_orm::ConnectionTrait;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+pub async fn atom_feed<C: ConnectionTrait + Send + 'static>(
+    headers: HeaderMap,
+    Extension(db): Extension<Arc<C>>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<Response, BlogError> {
+    let posts = public_posts(db.as_ref()).await?;
+    let last_modified = last_modified(db.as_ref(), &posts).await?;
+    let body = feed::atom(&config, &posts);
+    Ok(conditional(
+        &headers,
+        body,
+        "application/atom+xml; charset=utf-8",
+        last_modified,
+    ))
+}
+
+pub async fn rss_feed<C: ConnectionTrait + Send + 'static>(
+    headers: HeaderMap,
+    Extension(db): Extension<Arc<C>>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<Response, BlogError> {
+    let posts = public_posts(db.as_ref()).await?;
+    let last_modified = last_modified(db.as_ref(), &posts).await?;
+    let body = feed::rss(&config, &posts);
+    Ok(conditional(
+        &headers,
+        body,
+        "application/rss+xml; charset=utf-8",
+        last_modified,
+    ))
+}
+
+pub async fn sitemap<C: ConnectionTrait + Send + 'static>(
+    headers: HeaderMap,
+    Extension(db): Extension<Arc<C>>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<Response, BlogError> {
+    let posts = public_posts(db.as_ref()).await?;
+    let last_modified = last_modified(db.as_ref(), &posts).await?;
+    let body = feed::sitemap(&config, &posts);
+    Ok(conditional(
+        &headers,
+        body,
+        "application/xml; charset=utf-8",
+        last_modified,
+    ))
+}
+
+async fn public_posts<C: ConnectionTrait>(db: &C) -> Result<Vec<FeedPost>, BlogError> {
+    let now = Utc::now();
+    let posts = PostQuery::find_public_posts(db, now).await?;
+    let ids: Vec<i32> = posts.iter().map(|p| p.id).collect();
+    let revised = RevisionQuery::find_latest_revision_times(db, &ids).await?;
+    Ok(feed::prepare(posts, &revised, now))
+}
+
+// 公開中の記事の日時だけだと、記事を非公開・削除したときに値が戻ってしまう。
+// 公開・非公開・削除・編集のたびに進める feed_state の日時と合わせて、戻らないようにする。
+// 予約公開は書き込みなしで公開されるので、記事の公開日時も見る
+async fn last_modified<C: ConnectionTrait>(
+    db: &C,
+    posts: &[FeedPost],
+) -> Result<DateTime<Utc>, BlogError> {
+    let changed_at = FeedQuery::find_changed_at(db).await?;
+    Ok(changed_at.map_or(feed::last_modified(posts), |changed_at| {
+        changed_at.max(feed::last_modified(posts))
+    }))
+}
+
+// ETag は本文のハッシュ。If-None-Match があればそちらを優先し、なければ If-Modified-Since を見る
+fn conditional(
+    headers: &HeaderMap,
+    body: String,
+    content_type: &'static str,
+    last_modified: DateTime<Utc>,
+) -> Response {
+    let etag = etag(&body);
+
+    let not_modified = match headers.get(header::IF_NONE_MATCH) {
+        Some(value) => value
+            .to_str()
+            .map(|v| {
+                v.split(',')
+                    .any(|tag| tag.trim() == etag || tag.trim() == "*")
+            })
+            .unwrap_or(false),
+        None => headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+            .is_some_and(|since| last_modified.timestamp() <= since.timestamp()),
+    };
+
+    let mut res = if not_modified {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        let mut res = body.into_response();
+        res.headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        res
+    };
+    let res_headers = res.headers_mut();
+    res_headers.insert(header::ETAG, HeaderValue::from_str(&etag).unwrap());
+    res_headers.insert(
+        header::LAST_MODIFIED,
+        HeaderValue::from_str(&http_date(last_modified)).unwrap(),
+    );
+    res
+}
+
+// DefaultHasher はバージョンで結果が変わりうるので、再起動をまたいでも同じになる SHA-256 を使う
+fn etag(body: &str) -> String {
+    format!("\"{}\"", hex::encode(Sha256::digest(body.as_bytes())))
+}
+
+fn http_date(at: DateTime<Utc>) -> String {
+    at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
@@ -0,0 +1,408 @@
+use super::tag::ResponseTag;
+use super::{OptionalJson, ValidatedJson, ValidatedQuery};
+use crate::auth::Caller;
+use crate::config::AppConfig;
+use crate::error::BlogError;
+use crate::markdown;
+use crate::repository::comment::CommentQuery;
+use crate::repository::draft::DraftMutation;
+use crate::repository::post::{
+    PostCreate, PostListQuery, PostMutation, PostQuery, PostSort, PostUpdate, SortDirection,
+};
+use crate::repository::tag::TagQuery;
+use ::entity::post;
+use axum::{
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use validator::{Validate, ValidationError};
+
+#[derive(Deserialize, Validate)]
+pub struct RequestCreatePost {
+    #[validate(length(min = 1, max = 100, message = "title must be 1-100 characters"))]
+    title: String,
+    #[validate(length(min = 1, max = 10000, message = "body must be 1-10000 characters"))]
+    body: String,
+    #[serde(default)]
+    #[validate(custom = "validate_tags")]
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct RequestUpdatePost {
+    #[validate(length(min = 1, max = 100, message = "title must be 1-100 characters"))]
+    title: String,
+    #[validate(length(min = 1, max = 10000, message = "body must be 1-10000 characters"))]
+    body: String,
+    // 省略したときはタグを変更しない
+    #[validate(custom = "validate_tags")]
+    tags: Option<Vec<String>>,
+}
+
+fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    if tags.len() > 10 {
+        let mut e = ValidationError::new("tags");
+        e.message = Some("at most 10 tags are allowed".into());
+        return Err(e);
+    }
+    if tags
+        .iter()
+        .any(|t| t.trim().is_empty() || t.chars().count() > 30)
+    {
+        let mut e = ValidationError::new("tags");
+        e.message = Some("each tag must be 1-30 characters".into());
+        return Err(e);
+    }
+    Ok(())
+}
+
+// GET /posts のクエリ。どれも省略できる
+#[derive(Deserialize, Validate)]
+pub struct RequestListPosts {
+    #[serde(default = "default_page")]
+    #[validate(range(min = 1, message = "page must be 1 or greater"))]
+    page: u64,
+    #[serde(default = "default_per_page")]
+    #[validate(range(min = 1, max = 100, message = "per_page must be 1-100"))]
+    per_page: u64,
+    // created（作成日時）か published（公開日時）
+    #[serde(default)]
+    sort: PostSort,
+    // asc か desc。既定は新しい順
+    #[serde(default)]
+    order: SortDirection,
+    #[validate(length(max = 100, message = "q must be at most 100 characters"))]
+    q: Option<String>,
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_per_page() -> u64 {
+    20
+}
+
+#[derive(Deserialize)]
+pub struct RequestPublishPost {
+    publish_at: Option<DateTime<Utc>>,
+}
+
+const EXCERPT_CHARS: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ResponsePost {
+    pub id: i32,
+    pub slug: String,
+    pub title: String,
+    // body は Markdown のまま返し、表示用に html（サニタイズ済み）も返す
+    pub body: String,
+    pub html: String,
+    pub excerpt: String,
+    // 読了時間の目安（分）
+    pub reading_time: usize,
+    pub published: bool,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub author_id: Option<i32>,
+    // 承認済みコメントの数
+    pub comment_count: i64,
+    pub tags: Vec<ResponseTag>,
+}
+
+impl ResponsePost {
+    fn new(
+        post: post::Model,
+        comment_count: i64,
+        tags: Vec<ResponseTag>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: post.id,
+            html: markdown::render_html(&post.body),
+            excerpt: markdown::excerpt(&post.body, EXCERPT_CHARS),
+            reading_time: markdown::reading_time(&post.body),
+            comment_count,
+            tags,
+            published: post.is_public(now),
+            published_at: post.published_time(now),
+            publish_at: post.publish_at,
+            created_at: post.created_at,
+            author_id: post.author_id,
+            slug: post.slug,
+            title: post.title,
+            body: post.body,
+        }
+    }
+
+    // コメント数とタグを記事の数によらず 2 クエリで付け足す
+    pub(super) async fn load_many<C: ConnectionTrait>(
+        db: &C,
+        posts: Vec<post::Model>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Self>, BlogError> {
+        let ids: Vec<i32> = posts.iter().map(|p| p.id).collect();
+        let counts = CommentQuery::count_approved_by_posts(db, &ids).await?;
+        let mut tags = TagQuery::find_tags_by_posts(db, &ids).await?;
+        Ok(posts
+            .into_iter()
+            .map(|p| {
+                let count = counts.get(&p.id).copied().unwrap_or(0);
+                let tags = tags
+                    .remove(&p.id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(ResponseTag::from)
+                    .collect();
+                Self::new(p, count, tags, now)
+            })
+            .collect())
+    }
+
+    pub(super) async fn load<C: ConnectionTrait>(
+        db: &C,
+        post: post::Model,
+        now: DateTime<Utc>,
+    ) -> Result<Self, BlogError> {
+        let mut loaded = Self::load_many(db, vec![post], now).await?;
+        Ok(loaded.remove(0))
+    }
+}
+
+// GET /posts のレスポンス
+//
+// {
+//   "items": [ResponsePost, ...],
+//   "page": 2, "per_page": 20, "total_items": 45, "total_pages": 3,
+//   "links": {
+//     "self": "http://localhost:3000/posts?page=2&per_page=20&sort=created&order=desc",
+//     "first": "...page=1...", "last": "...page=3...",
+//     "prev": "...page=1...", "next": "...page=3..."
+//   }
+// }
+//
+// links は同じ sort / order / q を引き継ぐ。prev と next は先頭・末尾のページでは null になる
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ResponsePostPage {
+    pub items: Vec<ResponsePost>,
+    pub page: u64,
+    pub per_page: u64,
+    pub total_items: u64,
+    pub total_pages: u64,
+    pub links: ResponsePageLinks,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ResponsePageLinks {
+    #[serde(rename = "self")]
+    pub current: String,
+    pub first: String,
+    pub last: String,
+    pub prev: Option<String>,
+    pub next: Option<String>,
+}
+
+impl ResponsePageLinks {
+    fn new(base_url: &str, query: &RequestListPosts, total_pages: u64) -> Self {
+        #[derive(Serialize)]
+        struct LinkQuery<'a> {
+            page: u64,
+            per_page: u64,
+            sort: PostSort,
+            order: SortDirection,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            q: Option<&'a str>,
+        }
+        let link = |page: u64| {
+            let params = LinkQuery {
+                page,
+                per_page: query.per_page,
+                sort: query.sort,
+                order: query.order,
+                q: query.q.as_deref(),
+            };
+            // 数値と列挙子、文字列だけなので失敗しない
+            let params = serde_urlencoded::to_string(params).unwrap_or_default();
+            format!("{}/posts?{}", base_url, params)
+        };
+        // 記事が 0 件でも 1 ページ目は存在するものとして扱う
+        let last = total_pages.max(1);
+        Self {
+            current: link(query.page),
+            first: link(1),
+            last: link(last),
+            prev: (query.page > 1).then(|| link((query.page - 1).min(last))),
+            next: (query.page < last).then(|| link(query.page + 1)),
+        }
+    }
+}
+
+pub async fn create_post<C: ConnectionTrait + TransactionTrait + Send + 'static>(
+    caller: Caller,
+    ValidatedJson(payload): ValidatedJson<RequestCreatePost>,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    caller.require_login()?;
+    let data = PostCreate {
+        title: payload.title,
+        body: payload.body,
+        tags: payload.tags,
+        // ADMIN_TOKEN での投稿は作者なしになる
+        author_id: caller.user_id(),
+    };
+    let post = PostMutation::create_post(db.as_ref(), data).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ResponsePost::load(db.as_ref(), post, Utc::now()).await?),
+    ))
+}
+
+pub async fn find_post<C: ConnectionTrait + Send + 'static>(
+    Path(id): Path<i32>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    let now = Utc::now();
+    let post = find_visible_post(db.as_ref(), id, &caller, now).await?;
+
+    Ok(Json(ResponsePost::load(db.as_ref(), post, now).await?))
+}
+
+pub async fn find_post_by_slug<C: ConnectionTrait + Send + 'static>(
+    Path(slug): Path<String>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    let now = Utc::now();
+    let post = match PostQuery::find_post_by_slug(db.as_ref(), &slug).await? {
+        Some(post) if caller.can_edit(&post) || post.is_public(now) => post,
+        _ => return Err(BlogError::NotFound(format!("post {}", slug))),
+    };
+
+    Ok(Json(ResponsePost::load(db.as_ref(), post, now).await?))
+}
+
+// 下書きは作者と管理者以外には存在しないものとして扱う
+pub(super) async fn find_visible_post<C: ConnectionTrait>(
+    db: &C,
+    id: i32,
+    caller: &Caller,
+    now: DateTime<Utc>,
+) -> Result<post::Model, BlogError> {
+    match PostQuery::find_post_by_id(db, id).await? {
+        Some(post) if caller.can_edit(&post) || post.is_public(now) => Ok(post),
+        _ => Err(BlogError::NotFound(format!("post {}", id))),
+    }
+}
+
+// 変更系の操作の前に、記事があることと呼び出し元が作者か管理者であることを確かめる
+pub(super) async fn find_editable_post<C: ConnectionTrait>(
+    db: &C,
+    id: i32,
+    caller: &Caller,
+) -> Result<post::Model, BlogError> {
+    caller.require_login()?;
+    let post = PostQuery::find_post_by_id(db, id)
+        .await?
+        .ok_or_else(|| BlogError::NotFound(format!("post {}", id)))?;
+    caller.require_editor(&post)?;
+    Ok(post)
+}
+
+pub async fn update_post<C: ConnectionTrait + TransactionTrait + Send + 'static>(
+    Path(id): Path<i32>,
+    caller: Caller,
+    ValidatedJson(payload): ValidatedJson<RequestUpdatePost>,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    find_editable_post(db.as_ref(), id, &caller).await?;
+    let data = PostUpdate {
+        title: payload.title,
+        body: payload.body,
+        tags: payload.tags,
+        editor_id: caller.user_id(),
+    };
+    let post = PostMutation::update_post_by_id(db.as_ref(), id, data).await?;
+    // 編集画面から保存したので、自動保存していた下書きはもう要らない
+    DraftMutation::delete_draft(db.as_ref(), id).await?;
+
+    Ok(Json(
+        ResponsePost::load(db.as_ref(), post, Utc::now()).await?,
+    ))
+}
+
+pub async fn all_post<C: ConnectionTrait + Send + 'static>(
+    ValidatedQuery(query): ValidatedQuery<RequestListPosts>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, BlogError> {
+    let now = Utc::now();
+    let list = PostListQuery {
+        page: query.page,
+        per_page: query.per_page,
+        sort: query.sort,
+        direction: query.order,
+        q: query.q.clone(),
+        // 管理者には下書きも含めて返す
+        public_at: (!caller.is_admin()).then_some(now),
+    };
+    let page = PostQuery::find_posts_in_page(db.as_ref(), &list).await?;
+
+    Ok(Json(ResponsePostPage {
+        items: ResponsePost::load_many(db.as_ref(), page.posts, now).await?,
+        page: query.page,
+        per_page: query.per_page,
+        total_items: page.total_items,
+        total_pages: page.total_pages,
+        links: ResponsePageLinks::new(&config.base_url, &query, page.total_pages),
+    }))
+}
+
+pub async fn publish_post<C: ConnectionTrait + TransactionTrait + Send + 'static>(
+    Path(id): Path<i32>,
+    caller: Caller,
+    OptionalJson(payload): OptionalJson<RequestPublishPost>,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    find_editable_post(db.as_ref(), id, &caller).await?;
+    let now = Utc::now();
+    let publish_at = payload.and_then(|p| p.publish_at);
+    let post = PostMutation::publish_post_by_id(db.as_ref(), id, publish_at, now).await?;
+
+    Ok(Json(ResponsePost::load(db.as_ref(), post, now).await?))
+}
+
+pub async fn unpublish_post<C: ConnectionTrait + TransactionTrait + Send + 'static>(
+    Path(id): Path<i32>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    find_editable_post(db.as_ref(), id, &caller).await?;
+    let post = PostMutation::unpublish_post_by_id(db.as_ref(), id).await?;
+
+    Ok(Json(
+        ResponsePost::load(db.as_ref(), post, Utc::now()).await?,
+    ))
+}
+
+pub async fn delete_post<C: ConnectionTrait + TransactionTrait + Send + 'static>(
+    Path(id): Path<i32>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<StatusCode, BlogError> {
+    find_editable_post(db.as_ref(), id, &caller).await?;
+    let res = PostMutation::delete_post_by_id(db.as_ref(), id).await?;
+    if res.rows_affected == 0 {
+        return Err(BlogError::NotFound(format!("post {}", id)));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,153 @@
+use super::post::{find_editable_post, ResponsePost};
+use crate::auth::Caller;
+use crate::error::BlogError;
+use crate::repository::{post::PostMutation, revision::RevisionQuery};
+use ::entity::post_revision;
+use axum::{
+    extract::{Extension, Json, Path},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+use std::sync::Arc;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ResponseRevision {
+    pub rev: i32,
+    pub title: String,
+    pub body: String,
+    pub editor_id: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    // ひとつ前の版との差分。最初の版では null
+    pub diff: Option<ResponseRevisionDiff>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ResponseRevisionDiff {
+    pub from_rev: i32,
+    // タイトルが変わっていなければ null
+    pub title: Option<ResponseTitleChange>,
+    // 本文の unified diff。変わっていなければ空文字列
+    pub body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ResponseTitleChange {
+    pub from: String,
+    pub to: String,
+}
+
+impl ResponseRevisionDiff {
+    fn between(prev: &post_revision::Model, next: &post_revision::Model) -> Self {
+        let title = (prev.title != next.title).then(|| ResponseTitleChange {
+            from: prev.title.clone(),
+            to: next.title.clone(),
+        });
+        let body = TextDiff::from_lines(&prev.body, &next.body)
+            .unified_diff()
+            .header(&format!("rev {}", prev.rev), &format!("rev {}", next.rev))
+            .to_string();
+        Self {
+            from_rev: prev.rev,
+            title,
+            body,
+        }
+    }
+}
+
+// 古い順に並んだ版に、直前の版との差分を付ける
+fn with_diffs(revisions: Vec<post_revision::Model>) -> Vec<ResponseRevision> {
+    let diffs: Vec<Option<ResponseRevisionDiff>> = std::iter::once(None)
+        .chain(
+            revisions
+                .windows(2)
+                .map(|pair| Some(ResponseRevisionDiff::between(&pair[0], &pair[1]))),
+        )
+        .collect();
+    revisions
+        .into_iter()
+        .zip(diffs)
+        .map(|(revision, diff)| ResponseRevision {
+            rev: revision.rev,
+            title: revision.title,
+            body: revision.body,
+            editor_id: revision.editor_id,
+            created_at: revision.created_at,
+            diff,
+        })
+        .collect()
+}
+
+// 履歴は編集できる人にだけ見せる
+pub async fn all_revision<C: ConnectionTrait + Send + 'static>(
+    Path(id): Path<i32>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    find_editable_post(db.as_ref(), id, &caller).await?;
+    let revisions = RevisionQuery::find_revisions_by_post(db.as_ref(), id).await?;
+
+    Ok(Json(with_diffs(revisions)))
+}
+
+pub async fn restore_revision<C: ConnectionTrait + TransactionTrait + Send + 'static>(
+    Path((id, rev)): Path<(i32, i32)>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    find_editable_post(db.as_ref(), id, &caller).await?;
+    let post = PostMutation::restore_revision(db.as_ref(), id, rev, caller.user_id()).await?;
+
+    Ok(Json(
+        ResponsePost::load(db.as_ref(), post, Utc::now()).await?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn revision(rev: i32, title: &str, body: &str) -> post_revision::Model {
+        post_revision::Model {
+            id: rev,
+            post_id: 1,
+            rev,
+            title: title.to_string(),
+            body: body.to_string(),
+            editor_id: Some(1),
+            created_at: Utc.with_ymd_and_hms(2026, 10, rev as u32, 0, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn diffs_against_previous_revision() {
+        let revisions = with_diffs(vec![
+            revision(1, "title", "a\nb\n"),
+            revision(2, "title", "a\nc\n"),
+            revision(3, "new title", "a\nc\n"),
+        ]);
+
+        assert_eq!(None, revisions[0].diff);
+
+        let diff = revisions[1].diff.as_ref().unwrap();
+        assert_eq!(1, diff.from_rev);
+        assert_eq!(None, diff.title);
+        assert_eq!(
+            "--- rev 1\n+++ rev 2\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n",
+            diff.body
+        );
+
+        let diff = revisions[2].diff.as_ref().unwrap();
+        assert_eq!(
+            Some(ResponseTitleChange {
+                from: "title".to_string(),
+                to: "new title".to_string(),
+            }),
+            diff.title
+        );
+        assert_eq!("", diff.body);
+    }
+}
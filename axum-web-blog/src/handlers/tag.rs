@@ -0,0 +1,65 @@
+use super::post::ResponsePost;
+use crate::auth::Caller;
+use crate::error::BlogError;
+use crate::repository::tag::TagQuery;
+use ::entity::tag;
+use axum::{
+    extract::{Extension, Json, Path},
+    response::IntoResponse,
+};
+use chrono::Utc;
+use sea_orm::ConnectionTrait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ResponseTag {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+}
+
+impl From<tag::Model> for ResponseTag {
+    fn from(tag: tag::Model) -> Self {
+        Self {
+            id: tag.id,
+            name: tag.name,
+            slug: tag.slug,
+        }
+    }
+}
+
+// 匿名ユーザーには公開中の記事が付いているタグだけを返す
+pub async fn all_tag<C: ConnectionTrait + Send + 'static>(
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    let public_at = if caller.is_admin() {
+        None
+    } else {
+        Some(Utc::now())
+    };
+    let tags = TagQuery::find_all_tags(db.as_ref(), public_at).await?;
+
+    Ok(Json(
+        tags.into_iter().map(ResponseTag::from).collect::<Vec<_>>(),
+    ))
+}
+
+// 匿名ユーザーには公開中の記事だけを返す
+pub async fn tag_posts<C: ConnectionTrait + Send + 'static>(
+    Path(slug): Path<String>,
+    caller: Caller,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    let now = Utc::now();
+    let tag = TagQuery::find_tag_by_slug(db.as_ref(), &slug)
+        .await?
+        .ok_or_else(|| BlogError::NotFound(format!("tag {}", slug)))?;
+    let public_at = if caller.is_admin() { None } else { Some(now) };
+    let posts = TagQuery::find_posts_by_tag(db.as_ref(), &tag, public_at).await?;
+
+    Ok(Json(
+        ResponsePost::load_many(db.as_ref(), posts, now).await?,
+    ))
+}
@@ -0,0 +1,151 @@
+use super::ValidatedJson;
+use crate::auth::{self, Caller};
+use crate::config::AppConfig;
+use crate::error::BlogError;
+use crate::repository::user::{UserCreate, UserMutation, UserQuery};
+use ::entity::user::{self, UserRole};
+use axum::{
+    extract::{Extension, Json},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::ConnectionTrait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use validator::{Validate, ValidationError};
+
+#[derive(Deserialize, Validate)]
+pub struct RequestCreateUser {
+    #[validate(custom = "validate_username")]
+    username: String,
+    #[validate(length(min = 8, max = 128, message = "password must be 8-128 characters"))]
+    password: String,
+    // 省略したときは author
+    #[serde(default)]
+    role: Option<UserRole>,
+}
+
+// URL やログに出しても困らないよう英数字と - _ だけにする
+fn validate_username(username: &str) -> Result<(), ValidationError> {
+    let len = username.chars().count();
+    if !(3..=30).contains(&len)
+        || !username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        let mut e = ValidationError::new("username");
+        e.message = Some("username must be 3-30 characters of [A-Za-z0-9_-]".into());
+        return Err(e);
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct RequestLogin {
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ResponseUser {
+    pub id: i32,
+    pub username: String,
+    pub role: UserRole,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<user::Model> for ResponseUser {
+    fn from(user: user::Model) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            role: user.role,
+            created_at: user.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ResponseLogin {
+    // 以降のリクエストで Authorization: Bearer <token> として送る
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub user: ResponseUser,
+}
+
+// ユーザーの登録は管理者だけができる
+pub async fn create_user<C: ConnectionTrait + Send + 'static>(
+    caller: Caller,
+    ValidatedJson(payload): ValidatedJson<RequestCreateUser>,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<impl IntoResponse, BlogError> {
+    caller.require_admin()?;
+    let data = UserCreate {
+        username: payload.username,
+        password_hash: auth::spawn_hash_password(payload.password).await?,
+        role: payload.role.unwrap_or(UserRole::Author),
+    };
+    let user = UserMutation::create_user(db.as_ref(), data, Utc::now()).await?;
+
+    Ok((StatusCode::CREATED, Json(ResponseUser::from(user))))
+}
+
+pub async fn login<C: ConnectionTrait + Send + 'static>(
+    Json(payload): Json<RequestLogin>,
+    Extension(db): Extension<Arc<C>>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, BlogError> {
+    // ユーザー名とパスワードのどちらが違うかは区別して返さない
+    let user = UserQuery::find_user_by_username(db.as_ref(), &payload.username).await?;
+    let password_hash = user.as_ref().map(|user| user.password_hash.clone());
+    if !auth::spawn_verify_password(payload.password, password_hash).await? {
+        return Err(BlogError::InvalidCredentials);
+    }
+    let user = user.ok_or(BlogError::InvalidCredentials)?;
+    let now = Utc::now();
+    let expires_at = now + Duration::hours(config.session_ttl_hours);
+    let token = auth::generate_token();
+    UserMutation::create_session(
+        db.as_ref(),
+        user.id,
+        auth::hash_token(&token),
+        expires_at,
+        now,
+    )
+    .await?;
+
+    Ok(Json(ResponseLogin {
+        token,
+        expires_at,
+        user: ResponseUser::from(user),
+    }))
+}
+
+// 送られてきたトークンを無効にする
+pub async fn logout<C: ConnectionTrait + Send + 'static>(
+    // HeaderMap はリクエストからヘッダーを取り出してしまうので Caller より後に置く
+    caller: Caller,
+    headers: HeaderMap,
+    Extension(db): Extension<Arc<C>>,
+) -> Result<StatusCode, BlogError> {
+    if caller.user_id().is_none() {
+        return Err(BlogError::Unauthorized);
+    }
+    if let Some(token) = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    {
+        UserMutation::delete_session(db.as_ref(), &auth::hash_token(token)).await?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn me(caller: Caller) -> Result<impl IntoResponse, BlogError> {
+    match caller {
+        Caller::User(user) => Ok(Json(ResponseUser::from(user))),
+        _ => Err(BlogError::Unauthorized),
+    }
+}
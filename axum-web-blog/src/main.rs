@@ -1,150 +1,1325 @@
 use axum::{
-    extract,
-    extract::Path,
-    http::StatusCode,
-    response::IntoResponse,
-    routing::{get, post},
-    Json, Router,
+    extract::Extension,
+    routing::{delete, get, post},
+    Router,
 };
-use dotenv;
-use sea_orm::{Database, DbConn, DbErr};
-use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use sea_orm::{ConnectionTrait, Database, TransactionTrait};
+use std::{net::SocketAddr, process, sync::Arc};
+mod auth;
+mod config;
+mod error;
+mod feed;
+mod handlers;
+mod markdown;
 mod repository;
-use crate::repository::{PostCreate, PostMutation, PostQuery, PostUpdate};
+use crate::auth::SessionStore;
+use crate::config::{connect_options, AppConfig};
+use crate::handlers::{
+    comment::{all_comment, approve_comment, create_comment, delete_comment, reject_comment},
+    draft::{delete_draft, find_draft, save_draft},
+    feed::{atom_feed, rss_feed, sitemap},
+    hello_world,
+    post::{
+        all_post, create_post, delete_post, find_post, find_post_by_slug, publish_post,
+        unpublish_post, update_post,
+    },
+    revision::{all_revision, restore_revision},
+    tag::{all_tag, tag_posts},
+    user::{create_user, login, logout, me},
+};
 
 #[tokio::main]
 async fn main() {
+    dotenv::dotenv().ok();
+
+    // 接続はリクエストごとではなく起動時に一度だけ作り、プールをハンドラ間で共有する
+    let options = connect_options(|key| dotenv::var(key).ok()).unwrap_or_else(|e| {
+        eprintln!("invalid database config: {}", e);
+        process::exit(1);
+    });
+    let db = Database::connect(options).await.unwrap_or_else(|e| {
+        eprintln!("failed to connect database: {}", e);
+        process::exit(1);
+    });
+
+    let config = AppConfig::from_env(|key| dotenv::var(key).ok());
+
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
-    let router = Router::new()
-        .route("/", get(hello_world))
-        .route("/posts", post(create_post).get(all_post))
-        .route(
-            "/posts/:id",
-            get(find_post).patch(update_post).delete(delete_post),
-        );
     axum::Server::bind(&addr)
-        .serve(router.into_make_service())
+        .serve(create_app(db, config).into_make_service())
         .await
         .unwrap();
 }
 
-async fn hello_world() -> Json<HelloWorld> {
-    let hello = HelloWorld {
-        text: "Hello World".to_string(),
-    };
-    Json(hello)
+pub fn create_app<C: ConnectionTrait + TransactionTrait + Send + 'static>(
+    db: C,
+    config: AppConfig,
+) -> Router {
+    let db = Arc::new(db);
+    // Caller エクストラクタがトークンからユーザーを引くのに使う
+    let sessions: Arc<dyn SessionStore> = db.clone();
+    Router::new()
+        .route("/", get(hello_world))
+        .route("/users", post(create_user::<C>))
+        .route("/login", post(login::<C>))
+        .route("/logout", post(logout::<C>))
+        .route("/me", get(me))
+        .route("/posts", post(create_post::<C>).get(all_post::<C>))
+        .route(
+            "/posts/:id",
+            get(find_post::<C>)
+                .patch(update_post::<C>)
+                .delete(delete_post::<C>),
+        )
+        .route("/posts/by-slug/:slug", get(find_post_by_slug::<C>))
+        .route("/posts/:id/publish", post(publish_post::<C>))
+        .route("/posts/:id/unpublish", post(unpublish_post::<C>))
+        .route("/posts/:id/revisions", get(all_revision::<C>))
+        .route(
+            "/posts/:id/revisions/:rev/restore",
+            post(restore_revision::<C>),
+        )
+        .route(
+            "/posts/:id/draft",
+            get(find_draft::<C>)
+                .put(save_draft::<C>)
+                .delete(delete_draft::<C>),
+        )
+        .route("/feed.xml", get(atom_feed::<C>))
+        .route("/rss.xml", get(rss_feed::<C>))
+        .route("/sitemap.xml", get(sitemap::<C>))
+        .route("/tags", get(all_tag::<C>))
+        .route("/tags/:slug/posts", get(tag_posts::<C>))
+        .route(
+            "/posts/:id/comments",
+            get(all_comment::<C>).post(create_comment::<C>),
+        )
+        .route(
+            "/posts/:id/comments/:comment_id",
+            delete(delete_comment::<C>),
+        )
+        .route(
+            "/posts/:id/comments/:comment_id/approve",
+            post(approve_comment::<C>),
+        )
+        .route(
+            "/posts/:id/comments/:comment_id/reject",
+            post(reject_comment::<C>),
+        )
+        .layer(Extension(db))
+        .layer(Extension(sessions))
+        .layer(Extension(Arc::new(config)))
 }
 
-#[derive(Serialize)]
-struct HelloWorld {
-    text: String,
-}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::auth::hash_password;
+    use crate::handlers::{
+        comment::ResponseComment,
+        draft::ResponseDraft,
+        post::{ResponsePost, ResponsePostPage},
+        revision::ResponseRevision,
+        tag::ResponseTag,
+        user::{ResponseLogin, ResponseUser},
+    };
+    use ::entity::{
+        comment::{self, CommentStatus},
+        feed_state, post, post_draft, post_revision, session,
+        slug::slugify,
+        tag,
+        user::{self, UserRole},
+    };
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        response::Response,
+    };
+    use chrono::{Duration as ChronoDuration, TimeZone, Utc};
+    use sea_orm::{
+        DatabaseBackend, DatabaseConnection, DbErr, MockDatabase, MockExecResult, RuntimeErr, Value,
+    };
+    use std::collections::BTreeMap;
+    use tower::ServiceExt;
 
-#[derive(Deserialize)]
-struct RequestCreatePost {
-    title: String,
-    body: String,
-}
+    const ADMIN_TOKEN: &str = "secret";
+    const USER_TOKEN: &str = "user-token";
 
-#[derive(Deserialize)]
-struct RequestUpdatePost {
-    title: String,
-    body: String,
-}
+    fn post_model(id: i32, title: &str, body: &str) -> post::Model {
+        post::Model {
+            id,
+            title: title.to_owned(),
+            slug: slugify(title),
+            body: body.to_owned(),
+            published: true,
+            publish_at: None,
+            published_at: Some(Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap()),
+            created_at: Utc.with_ymd_and_hms(2026, 9, 30, 0, 0, 0).unwrap(),
+            author_id: Some(1),
+        }
+    }
 
-#[derive(Serialize)]
-struct ResponsePost {
-    id: i32,
-    title: String,
-    body: String,
-}
+    fn draft_model(id: i32) -> post::Model {
+        post::Model {
+            published: false,
+            published_at: None,
+            ..post_model(id, "draft", "draft body")
+        }
+    }
 
-async fn create_post(
-    extract::Json(payload): extract::Json<RequestCreatePost>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let db = connection().await.unwrap();
-    let data = PostCreate {
-        title: payload.title,
-        body: payload.body,
-    };
-    let post = PostMutation::create_post(&db, data).await.unwrap();
+    // 承認済みコメント数の集計クエリの結果
+    fn comment_counts(pairs: &[(i32, i64)]) -> Vec<BTreeMap<&'static str, Value>> {
+        pairs
+            .iter()
+            .map(|(post_id, count)| {
+                BTreeMap::from([
+                    ("post_id", Value::from(*post_id)),
+                    ("count", Value::from(*count)),
+                ])
+            })
+            .collect()
+    }
 
-    Ok(Json(ResponsePost {
-        id: post.id,
-        title: post.title.to_string(),
-        body: post.body.to_string(),
-    }))
-}
+    fn no_comments() -> Vec<BTreeMap<&'static str, Value>> {
+        comment_counts(&[])
+    }
 
-async fn find_post(Path(id): Path<i32>) -> Result<impl IntoResponse, StatusCode> {
-    let db = connection().await.unwrap();
-    let post = PostQuery::find_post_by_id(&db, id).await.unwrap();
+    // 記事ごとのタグを取るクエリの結果
+    fn post_tags(rows: &[(i32, i32, &str)]) -> Vec<BTreeMap<&'static str, Value>> {
+        rows.iter()
+            .map(|(post_id, id, name)| {
+                BTreeMap::from([
+                    ("post_id", Value::from(*post_id)),
+                    ("id", Value::from(*id)),
+                    ("name", Value::from(*name)),
+                    ("slug", Value::from(*name)),
+                ])
+            })
+            .collect()
+    }
+
+    fn no_tags() -> Vec<BTreeMap<&'static str, Value>> {
+        post_tags(&[])
+    }
 
-    match post {
-        Some(post) => Ok(Json(ResponsePost {
+    // 版番号を採番するクエリの結果。版の挿入には exec の結果も 1 つ要る
+    fn latest_rev(rev: Option<i32>) -> Vec<BTreeMap<&'static str, Value>> {
+        vec![BTreeMap::from([("rev", Value::from(rev))])]
+    }
+
+    fn exec_ok(last_insert_id: u64) -> MockExecResult {
+        MockExecResult {
+            last_insert_id,
+            rows_affected: 1,
+        }
+    }
+
+    fn admin_config() -> AppConfig {
+        AppConfig {
+            admin_token: Some(ADMIN_TOKEN.to_string()),
+            ..AppConfig::default()
+        }
+    }
+
+    fn as_admin(mut req: Request<Body>) -> Request<Body> {
+        req.headers_mut().insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", ADMIN_TOKEN).parse().unwrap(),
+        );
+        req
+    }
+
+    fn user_model(id: i32, role: UserRole) -> user::Model {
+        user::Model {
             id,
-            title: post.title.to_string(),
-            body: post.body.to_string(),
-        })),
-        None => Err(StatusCode::NOT_FOUND),
+            username: format!("user{}", id),
+            password_hash: String::new(),
+            role,
+            created_at: Utc.with_ymd_and_hms(2026, 9, 1, 0, 0, 0).unwrap(),
+        }
     }
-}
 
-async fn update_post(
-    Path(id): Path<i32>,
-    extract::Json(payload): extract::Json<RequestUpdatePost>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let db = connection().await.unwrap();
-    let data = PostUpdate {
-        title: payload.title,
-        body: payload.body,
-    };
-    let post = PostMutation::update_post_by_id(&db, id, data)
-        .await
-        .unwrap();
+    // セッションのトークンで認証する。モックの最初のクエリ結果にユーザーを積んでおくこと
+    fn as_user(mut req: Request<Body>) -> Request<Body> {
+        req.headers_mut().insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", USER_TOKEN).parse().unwrap(),
+        );
+        req
+    }
 
-    Ok(Json(ResponsePost {
-        id,
-        title: post.title.to_string(),
-        body: post.body.to_string(),
-    }))
-}
+    fn build_req_with_json(path: &str, method: Method, json_body: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json_body.to_string()))
+            .unwrap()
+    }
 
-async fn all_post() -> Result<impl IntoResponse, StatusCode> {
-    let db = connection().await.unwrap();
-    let posts = PostQuery::find_all_posts(&db).await.unwrap();
-    let mut accum: Vec<ResponsePost> = vec![];
-    for p in posts.iter() {
-        accum.push(ResponsePost {
-            id: p.id,
-            title: p.title.to_string(),
-            body: p.body.to_string(),
-        })
+    fn build_req_with_empty(method: Method, path: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .body(Body::empty())
+            .unwrap()
     }
-    Ok(Json(accum))
-}
 
-async fn delete_post(Path(id): Path<i32>) -> StatusCode {
-    let db = connection().await.unwrap();
-    PostMutation::delete_post_by_id(&db, id)
-        .await
-        .map(|res| {
-            // todo: もうちょっと良い書き方・・・
-            if res.rows_affected == 1 {
-                StatusCode::NO_CONTENT
-            } else {
-                StatusCode::NOT_FOUND
-            }
+    async fn send(db: DatabaseConnection, req: Request<Body>) -> Response {
+        create_app(db, admin_config()).oneshot(req).await.unwrap()
+    }
+
+    async fn res_to_json<T: serde::de::DeserializeOwned>(res: Response) -> T {
+        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = String::from_utf8(bytes.to_vec()).unwrap();
+        serde_json::from_str(&body).unwrap_or_else(|_| panic!("cannot convert body:{}", body))
+    }
+
+    #[tokio::test]
+    async fn should_create_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([Vec::<post::Model>::new()])
+            .append_query_results([[post_model(1, "title", "body")]])
+            .append_query_results([latest_rev(None)])
+            .append_query_results([no_comments()])
+            .append_query_results([no_tags()])
+            .append_exec_results([exec_ok(1), exec_ok(1)])
+            .into_connection();
+        let req = build_req_with_json("/posts", Method::POST, r#"{"title":"title","body":"body"}"#);
+        let res = send(db, as_admin(req)).await;
+        assert_eq!(StatusCode::CREATED, res.status());
+        let post: ResponsePost = res_to_json(res).await;
+        assert_eq!(1, post.id);
+        assert_eq!("title", post.title);
+        assert_eq!("body", post.body);
+        assert!(post.published);
+    }
+
+    #[tokio::test]
+    async fn should_find_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[post_model(15, "Test Post", "This is a test post")]])
+            .append_query_results([no_comments()])
+            .append_query_results([no_tags()])
+            .into_connection();
+        let res = send(db, build_req_with_empty(Method::GET, "/posts/15")).await;
+        let post: ResponsePost = res_to_json(res).await;
+        assert_eq!(15, post.id);
+        assert_eq!("Test Post", post.title);
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_for_missing_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([Vec::<post::Model>::new()])
+            .into_connection();
+        let res = send(db, build_req_with_empty(Method::GET, "/posts/99")).await;
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+
+    // ページネーションの件数クエリの結果
+    fn num_items(n: i32) -> Vec<BTreeMap<&'static str, Value>> {
+        vec![BTreeMap::from([("num_items", Value::from(n))])]
+    }
+
+    #[tokio::test]
+    async fn should_get_all_posts() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([num_items(2)])
+            .append_query_results([[post_model(1, "a", "x"), post_model(2, "b", "y")]])
+            .append_query_results([comment_counts(&[(2, 3)])])
+            .append_query_results([post_tags(&[(1, 7, "rust")])])
+            .into_connection();
+        let res = send(db, build_req_with_empty(Method::GET, "/posts")).await;
+        let page: ResponsePostPage = res_to_json(res).await;
+        let posts = page.items;
+        assert_eq!(vec![1, 2], posts.iter().map(|p| p.id).collect::<Vec<_>>());
+        assert_eq!(
+            vec![0, 3],
+            posts.iter().map(|p| p.comment_count).collect::<Vec<_>>()
+        );
+        assert_eq!("rust", posts[0].tags[0].slug);
+        assert!(posts[1].tags.is_empty());
+        assert_eq!(
+            (1, 20, 2, 1),
+            (page.page, page.per_page, page.total_items, page.total_pages)
+        );
+        assert_eq!(None, page.links.prev);
+        assert_eq!(None, page.links.next);
+    }
+
+    #[tokio::test]
+    async fn should_paginate_posts_with_links() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([num_items(5)])
+            .append_query_results([[post_model(3, "c", "z"), post_model(4, "d", "w")]])
+            .append_query_results([no_comments()])
+            .append_query_results([no_tags()])
+            .into_connection();
+        let res = send(
+            db,
+            build_req_with_empty(
+                Method::GET,
+                "/posts?page=2&per_page=2&sort=published&order=asc&q=hello%20world",
+            ),
+        )
+        .await;
+        assert_eq!(StatusCode::OK, res.status());
+        let page: ResponsePostPage = res_to_json(res).await;
+        assert_eq!(2, page.items.len());
+        assert_eq!(
+            (2, 2, 5, 3),
+            (page.page, page.per_page, page.total_items, page.total_pages)
+        );
+        let link = |page: u64| {
+            format!(
+                "http://localhost:3000/posts?page={}&per_page=2&sort=published&order=asc&q=hello+world",
+                page
+            )
+        };
+        assert_eq!(link(2), page.links.current);
+        assert_eq!(link(1), page.links.first);
+        assert_eq!(link(3), page.links.last);
+        assert_eq!(Some(link(1)), page.links.prev);
+        assert_eq!(Some(link(3)), page.links.next);
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_pagination() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+        let res = send(db, build_req_with_empty(Method::GET, "/posts?per_page=101")).await;
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, res.status());
+        let body: serde_json::Value = res_to_json(res).await;
+        assert_eq!("per_page must be 1-100", body["fields"]["per_page"][0]);
+
+        let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+        let res = send(db, build_req_with_empty(Method::GET, "/posts?sort=title")).await;
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_update_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            // 権限チェックのための取得
+            .append_query_results([[post_model(15, "old", "old body")]])
+            .append_query_results([[post_model(15, "old", "old body")]])
+            .append_query_results([[post_model(15, "new", "new body")]])
+            .append_query_results([latest_rev(Some(1))])
+            // 記事の update、版の insert、feed_state の更新、保存後の下書きの delete
+            .append_exec_results([exec_ok(15), exec_ok(2), exec_ok(1), exec_ok(1)])
+            .append_query_results([no_comments()])
+            .append_query_results([no_tags()])
+            .into_connection();
+        let req = build_req_with_json(
+            "/posts/15",
+            Method::PATCH,
+            r#"{"title":"new","body":"new body"}"#,
+        );
+        let post: ResponsePost = res_to_json(send(db, as_admin(req)).await).await;
+        assert_eq!("new", post.title);
+        assert_eq!("new body", post.body);
+    }
+
+    #[tokio::test]
+    async fn should_delete_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[post_model(15, "a", "b")]])
+            .append_query_results([Vec::<post::Model>::new()])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            // feed_state の更新
+            .append_exec_results([exec_ok(1)])
+            .into_connection();
+        let app = create_app(db, admin_config());
+        let res = app
+            .clone()
+            .oneshot(as_admin(build_req_with_empty(Method::DELETE, "/posts/15")))
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+        let res = app
+            .oneshot(as_admin(build_req_with_empty(Method::DELETE, "/posts/15")))
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+        let long_title = "a".repeat(101);
+        let req = build_req_with_json(
+            "/posts",
+            Method::POST,
+            &format!(r#"{{"title":"{}","body":""}}"#, long_title),
+        );
+        let res = send(db, req).await;
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, res.status());
+        let body: serde_json::Value = res_to_json(res).await;
+        assert_eq!("validation", body["error"]);
+        assert_eq!("title must be 1-100 characters", body["fields"]["title"][0]);
+        assert_eq!("body must be 1-10000 characters", body["fields"]["body"][0]);
+    }
+
+    #[tokio::test]
+    async fn should_reject_malformed_json() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+        let req = build_req_with_json("/posts", Method::POST, r#"{"title":"a""#);
+        let res = send(db, req).await;
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let body: serde_json::Value = res_to_json(res).await;
+        assert_eq!("bad_request", body["error"]);
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_updating_missing_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([Vec::<post::Model>::new()])
+            .into_connection();
+        let req = build_req_with_json("/posts/9", Method::PATCH, r#"{"title":"a","body":"b"}"#);
+        let res = send(db, as_admin(req)).await;
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+        let body: serde_json::Value = res_to_json(res).await;
+        assert_eq!("not_found", body["error"]);
+    }
+
+    #[tokio::test]
+    async fn should_hide_database_error_details() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_errors([DbErr::Query(RuntimeErr::Internal("disk I/O error".into()))])
+            .into_connection();
+        let res = send(db, build_req_with_empty(Method::GET, "/posts")).await;
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, res.status());
+        let body: serde_json::Value = res_to_json(res).await;
+        assert_eq!("internal server error", body["message"]);
+    }
+
+    #[tokio::test]
+    async fn should_hide_draft_from_anonymous() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[draft_model(3)]])
+            .into_connection();
+        let res = send(db, build_req_with_empty(Method::GET, "/posts/3")).await;
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_show_draft_to_admin() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[draft_model(3)]])
+            .append_query_results([no_comments()])
+            .append_query_results([no_tags()])
+            .into_connection();
+        let res = send(db, as_admin(build_req_with_empty(Method::GET, "/posts/3"))).await;
+        let post: ResponsePost = res_to_json(res).await;
+        assert!(!post.published);
+        assert_eq!(None, post.published_at);
+    }
+
+    #[tokio::test]
+    async fn should_reject_anonymous_publish() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+        let res = send(db, build_req_with_empty(Method::POST, "/posts/3/publish")).await;
+        assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_publish_post() {
+        let now = Utc::now();
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[draft_model(3)]])
+            .append_query_results([[draft_model(3)]])
+            .append_query_results([[post::Model {
+                published: true,
+                published_at: Some(now),
+                ..draft_model(3)
+            }]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 3,
+                rows_affected: 1,
+            }])
+            // feed_state の更新
+            .append_exec_results([exec_ok(1)])
+            .append_query_results([no_comments()])
+            .append_query_results([no_tags()])
+            .into_connection();
+        let res = send(
+            db,
+            as_admin(build_req_with_empty(Method::POST, "/posts/3/publish")),
+        )
+        .await;
+        let post: ResponsePost = res_to_json(res).await;
+        assert!(post.published);
+        assert_eq!(Some(now), post.published_at);
+    }
+
+    #[tokio::test]
+    async fn should_schedule_post() {
+        let at = Utc::now() + ChronoDuration::days(1);
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[draft_model(3)]])
+            .append_query_results([[draft_model(3)]])
+            .append_query_results([[post::Model {
+                publish_at: Some(at),
+                ..draft_model(3)
+            }]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 3,
+                rows_affected: 1,
+            }])
+            // feed_state の更新
+            .append_exec_results([exec_ok(1)])
+            .append_query_results([no_comments()])
+            .append_query_results([no_tags()])
+            .into_connection();
+        let req = build_req_with_json(
+            "/posts/3/publish",
+            Method::POST,
+            &format!(r#"{{"publish_at":"{}"}}"#, at.to_rfc3339()),
+        );
+        let post: ResponsePost = res_to_json(send(db, as_admin(req)).await).await;
+        assert!(!post.published);
+        assert_eq!(Some(at), post.publish_at);
+        assert_eq!(None, post.published_at);
+    }
+
+    #[tokio::test]
+    async fn should_reject_malformed_publish_body() {
+        // 読めない本文で即時公開してしまわないよう、DB に触れる前に 400 にする
+        for body in [r#"{"publish_at":"tomorrow"}"#, "{", "null"] {
+            let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+            let req = build_req_with_json("/posts/3/publish", Method::POST, body);
+            let res = send(db, as_admin(req)).await;
+            assert_eq!(StatusCode::BAD_REQUEST, res.status(), "{}", body);
+            let body: serde_json::Value = res_to_json(res).await;
+            assert_eq!("bad_request", body["error"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_unpublish_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[post_model(3, "a", "b")]])
+            .append_query_results([[post_model(3, "a", "b")]])
+            .append_query_results([[draft_model(3)]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 3,
+                rows_affected: 1,
+            }])
+            // feed_state の更新
+            .append_exec_results([exec_ok(1)])
+            .append_query_results([no_comments()])
+            .append_query_results([no_tags()])
+            .into_connection();
+        let res = send(
+            db,
+            as_admin(build_req_with_empty(Method::POST, "/posts/3/unpublish")),
+        )
+        .await;
+        let post: ResponsePost = res_to_json(res).await;
+        assert!(!post.published);
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_publishing_missing_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([Vec::<post::Model>::new()])
+            .into_connection();
+        let res = send(
+            db,
+            as_admin(build_req_with_empty(Method::POST, "/posts/9/publish")),
+        )
+        .await;
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+
+    fn comment_model(id: i32, post_id: i32, status: CommentStatus) -> comment::Model {
+        comment::Model {
+            id,
+            post_id,
+            author: "alice".to_owned(),
+            body: "nice post".to_owned(),
+            status,
+            created_at: Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_list_comments() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[post_model(1, "a", "b")]])
+            .append_query_results([[comment_model(5, 1, CommentStatus::Approved)]])
+            .into_connection();
+        let res = send(db, build_req_with_empty(Method::GET, "/posts/1/comments")).await;
+        let comments: Vec<ResponseComment> = res_to_json(res).await;
+        assert_eq!(1, comments.len());
+        assert_eq!(CommentStatus::Approved, comments[0].status);
+    }
+
+    #[tokio::test]
+    async fn should_create_pending_comment() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[post_model(1, "a", "b")]])
+            .append_query_results([[comment_model(5, 1, CommentStatus::Pending)]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 5,
+                rows_affected: 1,
+            }])
+            .into_connection();
+        let req = build_req_with_json(
+            "/posts/1/comments",
+            Method::POST,
+            r#"{"author":"alice","body":"nice post"}"#,
+        );
+        let res = send(db, req).await;
+        assert_eq!(StatusCode::CREATED, res.status());
+        let comment: ResponseComment = res_to_json(res).await;
+        assert_eq!(CommentStatus::Pending, comment.status);
+        assert_eq!(1, comment.post_id);
+    }
+
+    #[tokio::test]
+    async fn should_not_comment_on_draft() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[draft_model(3)]])
+            .into_connection();
+        let req = build_req_with_json(
+            "/posts/3/comments",
+            Method::POST,
+            r#"{"author":"alice","body":"nice post"}"#,
+        );
+        let res = send(db, req).await;
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_reject_anonymous_moderation() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+        let res = send(
+            db,
+            build_req_with_empty(Method::POST, "/posts/1/comments/5/approve"),
+        )
+        .await;
+        assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_approve_comment() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[comment_model(5, 1, CommentStatus::Pending)]])
+            .append_query_results([[comment_model(5, 1, CommentStatus::Approved)]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 5,
+                rows_affected: 1,
+            }])
+            .into_connection();
+        let res = send(
+            db,
+            as_admin(build_req_with_empty(
+                Method::POST,
+                "/posts/1/comments/5/approve",
+            )),
+        )
+        .await;
+        let comment: ResponseComment = res_to_json(res).await;
+        assert_eq!(CommentStatus::Approved, comment.status);
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_deleting_missing_comment() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 0,
+            }])
+            .into_connection();
+        let res = send(
+            db,
+            as_admin(build_req_with_empty(Method::DELETE, "/posts/1/comments/5")),
+        )
+        .await;
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_find_post_by_slug() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[post_model(
+                15,
+                "Hello World",
+                "# Hi\n\nThis is **markdown** <script>x</script>",
+            )]])
+            .append_query_results([no_comments()])
+            .append_query_results([no_tags()])
+            .into_connection();
+        let res = send(
+            db,
+            build_req_with_empty(Method::GET, "/posts/by-slug/hello-world"),
+        )
+        .await;
+        let post: ResponsePost = res_to_json(res).await;
+        assert_eq!(15, post.id);
+        assert_eq!("hello-world", post.slug);
+        assert!(post.html.contains("<strong>markdown</strong>"));
+        assert!(!post.html.contains("<script>"));
+        assert_eq!("Hi This is markdown x", post.excerpt);
+        assert_eq!(1, post.reading_time);
+    }
+
+    #[tokio::test]
+    async fn should_hide_draft_by_slug_from_anonymous() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[draft_model(3)]])
+            .into_connection();
+        let res = send(
+            db,
+            build_req_with_empty(Method::GET, "/posts/by-slug/draft"),
+        )
+        .await;
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_list_tags() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[tag::Model {
+                id: 7,
+                name: "Rust".to_owned(),
+                slug: "rust".to_owned(),
+            }]])
+            .into_connection();
+        let res = send(db, build_req_with_empty(Method::GET, "/tags")).await;
+        let tags: Vec<ResponseTag> = res_to_json(res).await;
+        assert_eq!("rust", tags[0].slug);
+    }
+
+    #[tokio::test]
+    async fn should_list_posts_by_tag() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[tag::Model {
+                id: 7,
+                name: "Rust".to_owned(),
+                slug: "rust".to_owned(),
+            }]])
+            .append_query_results([[post_model(1, "a", "b")]])
+            .append_query_results([no_comments()])
+            .append_query_results([post_tags(&[(1, 7, "rust")])])
+            .into_connection();
+        let res = send(db, build_req_with_empty(Method::GET, "/tags/rust/posts")).await;
+        let posts: Vec<ResponsePost> = res_to_json(res).await;
+        assert_eq!(1, posts.len());
+        assert_eq!("rust", posts[0].tags[0].slug);
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_for_missing_tag() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([Vec::<tag::Model>::new()])
+            .into_connection();
+        let res = send(db, build_req_with_empty(Method::GET, "/tags/nope/posts")).await;
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_reject_too_many_tags() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+        let tags: Vec<String> = (0..11).map(|i| format!("tag{}", i)).collect();
+        let req = build_req_with_json(
+            "/posts",
+            Method::POST,
+            &serde_json::json!({ "title": "a", "body": "b", "tags": tags }).to_string(),
+        );
+        let res = send(db, req).await;
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, res.status());
+        let body: serde_json::Value = res_to_json(res).await;
+        assert_eq!("at most 10 tags are allowed", body["fields"]["tags"][0]);
+    }
+
+    fn feed_posts() -> Vec<post::Model> {
+        vec![post_model(1, "Hello World", "First *post*.")]
+    }
+
+    // 記事ごとの最後の保存日時を取るクエリの結果
+    fn revised_at(day: u32) -> Vec<BTreeMap<&'static str, Value>> {
+        let at = Utc.with_ymd_and_hms(2026, 10, day, 0, 0, 0).unwrap();
+        vec![BTreeMap::from([
+            ("post_id", Value::from(1)),
+            ("created_at", Value::from(at)),
+        ])]
+    }
+
+    // 公開・非公開・削除・編集で最後にフィードが変わった日時
+    fn feed_changed_at(day: Option<u32>) -> Vec<feed_state::Model> {
+        day.map(|day| feed_state::Model {
+            id: 1,
+            changed_at: Utc.with_ymd_and_hms(2026, 10, day, 0, 0, 0).unwrap(),
         })
-        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
-}
+        .into_iter()
+        .collect()
+    }
 
-pub async fn connection() -> Result<DbConn, DbErr> {
-    let database_url = dotenv::var("DATABASE_URL").unwrap();
-    let db = Database::connect(&database_url)
-        .await
-        .expect("Failed to setup the database");
+    #[tokio::test]
+    async fn should_serve_feeds() {
+        for (path, content_type, marker) in [
+            ("/feed.xml", "application/atom+xml; charset=utf-8", "<feed"),
+            ("/rss.xml", "application/rss+xml; charset=utf-8", "<rss"),
+            ("/sitemap.xml", "application/xml; charset=utf-8", "<urlset"),
+        ] {
+            let db = MockDatabase::new(DatabaseBackend::Sqlite)
+                .append_query_results([feed_posts()])
+                .append_query_results([revised_at(1)])
+                .append_query_results([feed_changed_at(None)])
+                .into_connection();
+            let res = send(db, build_req_with_empty(Method::GET, path)).await;
+            assert_eq!(StatusCode::OK, res.status());
+            assert_eq!(content_type, res.headers()[header::CONTENT_TYPE]);
+            assert_eq!(
+                "Thu, 01 Oct 2026 00:00:00 GMT",
+                res.headers()[header::LAST_MODIFIED]
+            );
+            assert!(res.headers().contains_key(header::ETAG));
+            let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let body = String::from_utf8(bytes.to_vec()).unwrap();
+            assert!(body.contains(marker));
+            assert!(body.contains("http://localhost:3000/posts/by-slug/hello-world"));
+        }
+    }
 
-    Ok(db)
+    #[tokio::test]
+    async fn should_return_not_modified_for_matching_etag() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([feed_posts()])
+            .append_query_results([revised_at(1)])
+            .append_query_results([feed_changed_at(Some(1))])
+            .append_query_results([feed_posts()])
+            .append_query_results([revised_at(1)])
+            .append_query_results([feed_changed_at(Some(1))])
+            .into_connection();
+        let app = create_app(db, AppConfig::default());
+        let res = app
+            .clone()
+            .oneshot(build_req_with_empty(Method::GET, "/feed.xml"))
+            .await
+            .unwrap();
+        let etag = res.headers()[header::ETAG].clone();
+
+        let mut req = build_req_with_empty(Method::GET, "/feed.xml");
+        req.headers_mut().insert(header::IF_NONE_MATCH, etag);
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::NOT_MODIFIED, res.status());
+        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert!(bytes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_honor_if_modified_since() {
+        // 10/1 に公開し、10/5 に本文を直した記事
+        for (since, expected) in [
+            ("Mon, 05 Oct 2026 00:00:00 GMT", StatusCode::NOT_MODIFIED),
+            ("Thu, 01 Oct 2026 00:00:00 GMT", StatusCode::OK),
+        ] {
+            let db = MockDatabase::new(DatabaseBackend::Sqlite)
+                .append_query_results([feed_posts()])
+                .append_query_results([revised_at(5)])
+                .append_query_results([feed_changed_at(Some(5))])
+                .into_connection();
+            let mut req = build_req_with_empty(Method::GET, "/rss.xml");
+            req.headers_mut()
+                .insert(header::IF_MODIFIED_SINCE, since.parse().unwrap());
+            let res = send(db, req).await;
+            assert_eq!(expected, res.status());
+        }
+    }
+
+    #[tokio::test]
+    async fn should_not_return_not_modified_after_unpublishing() {
+        // 10/5 に公開した記事 2 を 10/7 に非公開にした。公開中は 10/1 の記事 1 だけ
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([feed_posts()])
+            .append_query_results([revised_at(1)])
+            .append_query_results([feed_changed_at(Some(7))])
+            .into_connection();
+        let mut req = build_req_with_empty(Method::GET, "/feed.xml");
+        // 記事 2 が公開されていたときの Last-Modified
+        req.headers_mut().insert(
+            header::IF_MODIFIED_SINCE,
+            "Mon, 05 Oct 2026 00:00:00 GMT".parse().unwrap(),
+        );
+        let res = send(db, req).await;
+        assert_eq!(StatusCode::OK, res.status());
+        assert_eq!(
+            "Wed, 07 Oct 2026 00:00:00 GMT",
+            res.headers()[header::LAST_MODIFIED]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_create_post_as_author() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[user_model(1, UserRole::Author)]])
+            .append_query_results([Vec::<post::Model>::new()])
+            .append_query_results([[post_model(1, "title", "body")]])
+            .append_query_results([latest_rev(None)])
+            .append_query_results([no_comments()])
+            .append_query_results([no_tags()])
+            .append_exec_results([exec_ok(1), exec_ok(1)])
+            .into_connection();
+        let req = build_req_with_json("/posts", Method::POST, r#"{"title":"title","body":"body"}"#);
+        let res = send(db, as_user(req)).await;
+        assert_eq!(StatusCode::CREATED, res.status());
+        let post: ResponsePost = res_to_json(res).await;
+        assert_eq!(Some(1), post.author_id);
+    }
+
+    #[tokio::test]
+    async fn should_reject_anonymous_mutations() {
+        for (method, path, body) in [
+            (Method::POST, "/posts", r#"{"title":"a","body":"b"}"#),
+            (Method::PATCH, "/posts/1", r#"{"title":"a","body":"b"}"#),
+            (Method::DELETE, "/posts/1", ""),
+        ] {
+            let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+            let res = send(db, build_req_with_json(path, method, body)).await;
+            assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+        }
+    }
+
+    #[tokio::test]
+    async fn should_forbid_editing_others_post() {
+        // post_model の作者は user1
+        for (method, body) in [
+            (Method::PATCH, r#"{"title":"a","body":"b"}"#),
+            (Method::DELETE, ""),
+        ] {
+            let db = MockDatabase::new(DatabaseBackend::Sqlite)
+                .append_query_results([[user_model(2, UserRole::Author)]])
+                .append_query_results([[post_model(1, "a", "b")]])
+                .into_connection();
+            let res = send(db, as_user(build_req_with_json("/posts/1", method, body))).await;
+            assert_eq!(StatusCode::FORBIDDEN, res.status());
+            let body: serde_json::Value = res_to_json(res).await;
+            assert_eq!("forbidden", body["error"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_let_author_delete_own_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[user_model(1, UserRole::Author)]])
+            .append_query_results([[post_model(1, "a", "b")]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            // feed_state の更新
+            .append_exec_results([exec_ok(1)])
+            .into_connection();
+        let req = build_req_with_empty(Method::DELETE, "/posts/1");
+        let res = send(db, as_user(req)).await;
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_let_admin_user_update_others_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[user_model(9, UserRole::Admin)]])
+            .append_query_results([[post_model(1, "old", "old body")]])
+            .append_query_results([[post_model(1, "old", "old body")]])
+            .append_query_results([[post_model(1, "new", "new body")]])
+            .append_query_results([latest_rev(Some(1))])
+            .append_exec_results([exec_ok(1), exec_ok(2), exec_ok(1), exec_ok(1)])
+            .append_query_results([no_comments()])
+            .append_query_results([no_tags()])
+            .into_connection();
+        let req = build_req_with_json(
+            "/posts/1",
+            Method::PATCH,
+            r#"{"title":"new","body":"new body"}"#,
+        );
+        let res = send(db, as_user(req)).await;
+        assert_eq!(StatusCode::OK, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_login_and_issue_token() {
+        let user = user::Model {
+            password_hash: hash_password("password123").unwrap(),
+            ..user_model(1, UserRole::Author)
+        };
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[user.clone()]])
+            .append_query_results([[session::Model {
+                token_hash: "hash".to_string(),
+                user_id: 1,
+                expires_at: Utc::now(),
+                created_at: Utc::now(),
+            }]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            .append_query_results([[user]])
+            .into_connection();
+        let app = create_app(db, AppConfig::default());
+        let req = build_req_with_json(
+            "/login",
+            Method::POST,
+            r#"{"username":"user1","password":"password123"}"#,
+        );
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let login: ResponseLogin = res_to_json(res).await;
+        assert_eq!(64, login.token.len());
+        assert_eq!("user1", login.user.username);
+        assert!(login.expires_at > Utc::now() + ChronoDuration::days(6));
+
+        // 発行したトークンで自分の情報が取れる
+        let mut req = build_req_with_empty(Method::GET, "/me");
+        req.headers_mut().insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", login.token).parse().unwrap(),
+        );
+        let res = app.oneshot(req).await.unwrap();
+        let me: ResponseUser = res_to_json(res).await;
+        assert_eq!(1, me.id);
+    }
+
+    #[tokio::test]
+    async fn should_reject_wrong_password() {
+        let user = user::Model {
+            password_hash: hash_password("password123").unwrap(),
+            ..user_model(1, UserRole::Author)
+        };
+        for (users, password) in [(vec![user], "wrong-password"), (vec![], "password123")] {
+            let db = MockDatabase::new(DatabaseBackend::Sqlite)
+                .append_query_results([users])
+                .into_connection();
+            let req = build_req_with_json(
+                "/login",
+                Method::POST,
+                &format!(r#"{{"username":"user1","password":"{}"}}"#, password),
+            );
+            let res = send(db, req).await;
+            assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+            let body: serde_json::Value = res_to_json(res).await;
+            assert_eq!("invalid_credentials", body["error"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_only_let_admin_create_users() {
+        let payload = r#"{"username":"alice","password":"password123"}"#;
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[user_model(1, UserRole::Author)]])
+            .into_connection();
+        let res = send(
+            db,
+            as_user(build_req_with_json("/users", Method::POST, payload)),
+        )
+        .await;
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[user::Model {
+                username: "alice".to_string(),
+                ..user_model(2, UserRole::Author)
+            }]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 2,
+                rows_affected: 1,
+            }])
+            .into_connection();
+        let res = send(
+            db,
+            as_admin(build_req_with_json("/users", Method::POST, payload)),
+        )
+        .await;
+        assert_eq!(StatusCode::CREATED, res.status());
+        let user: ResponseUser = res_to_json(res).await;
+        assert_eq!("alice", user.username);
+        assert_eq!(UserRole::Author, user.role);
+    }
+
+    #[tokio::test]
+    async fn should_validate_new_user() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+        let req = build_req_with_json(
+            "/users",
+            Method::POST,
+            r#"{"username":"a b","password":"short"}"#,
+        );
+        let res = send(db, as_admin(req)).await;
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, res.status());
+        let body: serde_json::Value = res_to_json(res).await;
+        assert!(body["fields"]["username"].is_array());
+        assert_eq!(
+            "password must be 8-128 characters",
+            body["fields"]["password"][0]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_logout() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[user_model(1, UserRole::Author)]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            .into_connection();
+        let res = send(db, as_user(build_req_with_empty(Method::POST, "/logout"))).await;
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+
+        let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+        let res = send(db, build_req_with_empty(Method::POST, "/logout")).await;
+        assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+    }
+
+    fn revision_model(rev: i32, title: &str, body: &str) -> post_revision::Model {
+        post_revision::Model {
+            id: rev,
+            post_id: 1,
+            rev,
+            title: title.to_owned(),
+            body: body.to_owned(),
+            editor_id: Some(1),
+            created_at: Utc.with_ymd_and_hms(2026, 10, rev as u32, 0, 0, 0).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_list_revisions_with_diffs() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[user_model(1, UserRole::Author)]])
+            .append_query_results([[post_model(1, "new", "new body")]])
+            .append_query_results([[
+                revision_model(1, "old", "old body"),
+                revision_model(2, "new", "new body"),
+            ]])
+            .into_connection();
+        let req = build_req_with_empty(Method::GET, "/posts/1/revisions");
+        let res = send(db, as_user(req)).await;
+        assert_eq!(StatusCode::OK, res.status());
+        let revisions: Vec<ResponseRevision> = res_to_json(res).await;
+        assert_eq!(
+            vec![1, 2],
+            revisions.iter().map(|r| r.rev).collect::<Vec<_>>()
+        );
+        assert_eq!(None, revisions[0].diff);
+        let diff = revisions[1].diff.as_ref().unwrap();
+        assert_eq!("old", diff.title.as_ref().unwrap().from);
+        assert!(diff.body.contains("-old body") && diff.body.contains("+new body"));
+    }
+
+    #[tokio::test]
+    async fn should_hide_revisions_from_others() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+        let req = build_req_with_empty(Method::GET, "/posts/1/revisions");
+        assert_eq!(StatusCode::UNAUTHORIZED, send(db, req).await.status());
+
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[user_model(2, UserRole::Author)]])
+            .append_query_results([[post_model(1, "a", "b")]])
+            .into_connection();
+        let req = build_req_with_empty(Method::GET, "/posts/1/revisions");
+        assert_eq!(StatusCode::FORBIDDEN, send(db, as_user(req)).await.status());
+    }
+
+    #[tokio::test]
+    async fn should_restore_revision() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[user_model(1, UserRole::Author)]])
+            // 権限チェックのための取得
+            .append_query_results([[post_model(1, "new", "new body")]])
+            .append_query_results([[revision_model(1, "old", "old body")]])
+            .append_query_results([[post_model(1, "new", "new body")]])
+            .append_query_results([[post_model(1, "old", "old body")]])
+            .append_query_results([latest_rev(Some(2))])
+            .append_query_results([no_comments()])
+            .append_query_results([no_tags()])
+            // 記事の update、版の insert、feed_state の更新。編集中の下書きは消さない
+            .append_exec_results([exec_ok(1), exec_ok(3), exec_ok(1)])
+            .into_connection();
+        let req = build_req_with_empty(Method::POST, "/posts/1/revisions/1/restore");
+        let res = send(db, as_user(req)).await;
+        assert_eq!(StatusCode::OK, res.status());
+        let post: ResponsePost = res_to_json(res).await;
+        assert_eq!("old", post.title);
+        assert_eq!("old body", post.body);
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_for_missing_revision() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[post_model(1, "a", "b")]])
+            .append_query_results([Vec::<post_revision::Model>::new()])
+            .into_connection();
+        let req = build_req_with_empty(Method::POST, "/posts/1/revisions/9/restore");
+        let res = send(db, as_admin(req)).await;
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_autosave_draft() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[user_model(1, UserRole::Author)]])
+            .append_query_results([[post_model(1, "a", "b")]])
+            .append_exec_results([exec_ok(1)])
+            .into_connection();
+        let req = build_req_with_json(
+            "/posts/1/draft",
+            Method::PUT,
+            r#"{"title":"a","body":"half written"}"#,
+        );
+        let res = send(db, as_user(req)).await;
+        assert_eq!(StatusCode::OK, res.status());
+        let draft: ResponseDraft = res_to_json(res).await;
+        assert_eq!("half written", draft.body);
+        assert_eq!(Some(1), draft.editor_id);
+    }
+
+    #[tokio::test]
+    async fn should_find_and_delete_draft() {
+        let saved = post_draft::Model {
+            post_id: 1,
+            title: "a".to_owned(),
+            body: "wip".to_owned(),
+            editor_id: Some(1),
+            updated_at: Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap(),
+        };
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[post_model(1, "a", "b")]])
+            .append_query_results([[saved]])
+            .into_connection();
+        let res = send(
+            db,
+            as_admin(build_req_with_empty(Method::GET, "/posts/1/draft")),
+        )
+        .await;
+        let draft: ResponseDraft = res_to_json(res).await;
+        assert_eq!("wip", draft.body);
+
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[post_model(1, "a", "b")]])
+            .append_query_results([Vec::<post_draft::Model>::new()])
+            .into_connection();
+        let res = send(
+            db,
+            as_admin(build_req_with_empty(Method::GET, "/posts/1/draft")),
+        )
+        .await;
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[post_model(1, "a", "b")]])
+            .append_exec_results([exec_ok(0)])
+            .into_connection();
+        let req = build_req_with_empty(Method::DELETE, "/posts/1/draft");
+        assert_eq!(
+            StatusCode::NO_CONTENT,
+            send(db, as_admin(req)).await.status()
+        );
+    }
 }
@@ -0,0 +1,118 @@
+use pulldown_cmark::{html, Event, Options, Parser, Tag};
+
+// 1 分あたりに読める量の目安
+const WORDS_PER_MINUTE: usize = 200;
+const CJK_CHARS_PER_MINUTE: usize = 500;
+
+fn parser(markdown: &str) -> Parser<'_, '_> {
+    Parser::new_ext(
+        markdown,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+    )
+}
+
+// Markdown を HTML にして ammonia で危険なタグや属性を取り除く
+pub fn render_html(markdown: &str) -> String {
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser(markdown));
+    ammonia::clean(&unsafe_html)
+}
+
+// 装飾を外した本文テキスト。ブロックの区切りは空白にする
+pub fn plain_text(markdown: &str) -> String {
+    let mut text = String::new();
+    for event in parser(markdown) {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak
+            | Event::HardBreak
+            | Event::End(
+                Tag::Paragraph
+                | Tag::Heading(..)
+                | Tag::Item
+                | Tag::CodeBlock(_)
+                | Tag::BlockQuote
+                | Tag::TableRow
+                | Tag::TableCell,
+            ) => text.push(' '),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// 先頭 max_chars 文字までの抜粋。途中で切るときは単語の切れ目で止めて … を付ける
+pub fn excerpt(markdown: &str, max_chars: usize) -> String {
+    let text = plain_text(markdown);
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    let cut = match cut.rfind(' ') {
+        Some(i) if i > 0 => &cut[..i],
+        _ => cut.as_str(),
+    };
+    format!("{}…", cut.trim_end())
+}
+
+// 読了時間（分）。英語などは単語数、日本語などは文字数で見積もる。最低 1 分
+pub fn reading_time(markdown: &str) -> usize {
+    let text = plain_text(markdown);
+    let mut words = 0;
+    let mut cjk_chars = 0;
+    for word in text.split_whitespace() {
+        let cjk = word
+            .chars()
+            .filter(|c| !c.is_ascii() && c.is_alphanumeric())
+            .count();
+        cjk_chars += cjk;
+        if cjk < word.chars().count() {
+            words += 1;
+        }
+    }
+    let minutes =
+        words as f64 / WORDS_PER_MINUTE as f64 + cjk_chars as f64 / CJK_CHARS_PER_MINUTE as f64;
+    (minutes.ceil() as usize).max(1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn renders_and_sanitizes_html() {
+        let html =
+            render_html("# Title\n\n**bold** <script>alert(1)</script> [x](javascript:alert(1))");
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<strong>bold</strong>"));
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("javascript:"));
+    }
+
+    #[test]
+    fn builds_excerpt_from_plain_text() {
+        assert_eq!("Title Some text", excerpt("# Title\n\nSome *text*", 100));
+        assert_eq!("one two…", excerpt("one two three", 9));
+        assert_eq!("あいう…", excerpt("あいうえお", 3));
+    }
+
+    #[test]
+    fn separates_blocks_in_plain_text() {
+        assert_eq!(
+            "hi one two 100% done_x",
+            plain_text("# hi\n\n- one\n- two\n\n100% done_x")
+        );
+        assert_eq!(
+            "a b c d quoted",
+            plain_text("| a | b |\n|---|---|\n| c | d |\n\n> quoted")
+        );
+        assert_eq!("let x; after", plain_text("```\nlet x;\n```\nafter"));
+    }
+
+    #[test]
+    fn estimates_reading_time() {
+        assert_eq!(1, reading_time(""));
+        assert_eq!(2, reading_time(&"word ".repeat(201)));
+        assert_eq!(2, reading_time(&"あ".repeat(501)));
+    }
+}
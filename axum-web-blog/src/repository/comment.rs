@@ -0,0 +1,192 @@
+use super::find_or_not_found;
+use ::entity::{
+    comment,
+    comment::{ActiveModel, CommentStatus, Entity as Comment},
+};
+use sea_orm::{prelude::DateTimeUtc, *};
+use std::collections::HashMap;
+
+pub struct CommentCreate {
+    pub author: String,
+    pub body: String,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct CommentCount {
+    post_id: i32,
+    count: i64,
+}
+
+pub struct CommentQuery;
+pub struct CommentMutation;
+
+impl CommentQuery {
+    // status が None なら全件（管理者向け）
+    pub async fn find_comments_by_post<C: ConnectionTrait>(
+        db: &C,
+        post_id: i32,
+        status: Option<CommentStatus>,
+    ) -> Result<Vec<comment::Model>, DbErr> {
+        let mut query = Comment::find().filter(comment::Column::PostId.eq(post_id));
+        if let Some(status) = status {
+            query = query.filter(comment::Column::Status.eq(status));
+        }
+        query
+            .order_by_asc(comment::Column::CreatedAt)
+            .order_by_asc(comment::Column::Id)
+            .all(db)
+            .await
+    }
+
+    // 承認済みコメント数を記事ごとにまとめて数える（記事一覧で N+1 にならないように）
+    pub async fn count_approved_by_posts<C: ConnectionTrait>(
+        db: &C,
+        post_ids: &[i32],
+    ) -> Result<HashMap<i32, i64>, DbErr> {
+        if post_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let counts = Comment::find()
+            .select_only()
+            .column(comment::Column::PostId)
+            .column_as(comment::Column::Id.count(), "count")
+            .filter(comment::Column::PostId.is_in(post_ids.iter().copied()))
+            .filter(comment::Column::Status.eq(CommentStatus::Approved))
+            .group_by(comment::Column::PostId)
+            .into_model::<CommentCount>()
+            .all(db)
+            .await?;
+
+        Ok(counts.into_iter().map(|c| (c.post_id, c.count)).collect())
+    }
+}
+
+impl CommentMutation {
+    pub async fn create_comment<C: ConnectionTrait>(
+        db: &C,
+        post_id: i32,
+        payload: CommentCreate,
+        now: DateTimeUtc,
+    ) -> Result<comment::Model, DbErr> {
+        let comment = ActiveModel {
+            id: ActiveValue::NotSet,
+            post_id: ActiveValue::set(post_id),
+            author: ActiveValue::set(payload.author),
+            body: ActiveValue::set(payload.body),
+            status: ActiveValue::set(CommentStatus::Pending),
+            created_at: ActiveValue::set(now),
+        };
+
+        comment.insert(db).await
+    }
+
+    pub async fn moderate_comment<C: ConnectionTrait>(
+        db: &C,
+        post_id: i32,
+        id: i32,
+        status: CommentStatus,
+    ) -> Result<comment::Model, DbErr> {
+        let comment = find_or_not_found::<Comment, _>(db, id, "comment").await?;
+        // 別の記事のコメント ID を指定された場合も存在しない扱いにする
+        if comment.post_id != post_id {
+            return Err(DbErr::RecordNotFound(format!("comment {}", id)));
+        }
+        let mut comment: ActiveModel = comment.into();
+        comment.status = Set(status);
+
+        comment.update(db).await
+    }
+
+    pub async fn delete_comment<C: ConnectionTrait>(
+        db: &C,
+        post_id: i32,
+        id: i32,
+    ) -> Result<DeleteResult, DbErr> {
+        Comment::delete_many()
+            .filter(comment::Column::Id.eq(id))
+            .filter(comment::Column::PostId.eq(post_id))
+            .exec(db)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Transaction};
+    use std::collections::BTreeMap;
+
+    fn comment_model(id: i32, post_id: i32, status: CommentStatus) -> comment::Model {
+        comment::Model {
+            id,
+            post_id,
+            author: "alice".to_owned(),
+            body: "nice post".to_owned(),
+            status,
+            created_at: Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn count_approved_by_posts() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[
+                BTreeMap::from([("post_id", Value::from(1)), ("count", Value::from(2i64))]),
+                BTreeMap::from([("post_id", Value::from(3)), ("count", Value::from(1i64))]),
+            ]])
+            .into_connection();
+
+        let counts = CommentQuery::count_approved_by_posts(&db, &[1, 2, 3])
+            .await
+            .expect("[count] returned Err");
+        assert_eq!(Some(&2), counts.get(&1));
+        assert_eq!(None, counts.get(&2));
+        assert_eq!(Some(&1), counts.get(&3));
+
+        assert_eq!(
+            db.into_transaction_log(),
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Sqlite,
+                r#"SELECT "comments"."post_id", COUNT("comments"."id") AS "count" FROM "comments" WHERE "comments"."post_id" IN (?, ?, ?) AND "comments"."status" = ? GROUP BY "comments"."post_id""#,
+                [1.into(), 2.into(), 3.into(), "approved".into()],
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn count_without_posts_skips_query() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite).into_connection();
+        let counts = CommentQuery::count_approved_by_posts(&db, &[])
+            .await
+            .expect("[count] returned Err");
+        assert!(counts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn moderate_comment() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[comment_model(5, 1, CommentStatus::Pending)]])
+            .append_query_results([[comment_model(5, 1, CommentStatus::Approved)]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 5,
+                rows_affected: 1,
+            }])
+            .into_connection();
+
+        let comment = CommentMutation::moderate_comment(&db, 1, 5, CommentStatus::Approved)
+            .await
+            .expect("[moderate] returned Err");
+        assert_eq!(CommentStatus::Approved, comment.status);
+    }
+
+    #[tokio::test]
+    async fn moderate_comment_of_other_post_is_not_found() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[comment_model(5, 2, CommentStatus::Pending)]])
+            .into_connection();
+
+        let res = CommentMutation::moderate_comment(&db, 1, 5, CommentStatus::Approved).await;
+        assert!(matches!(res, Err(DbErr::RecordNotFound(_))));
+    }
+}
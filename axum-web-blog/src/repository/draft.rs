@@ -0,0 +1,102 @@
+use ::entity::post_draft::{self, ActiveModel, Entity as PostDraft};
+use sea_orm::{prelude::DateTimeUtc, sea_query::OnConflict, *};
+
+pub struct DraftSave {
+    pub title: String,
+    pub body: String,
+    pub editor_id: Option<i32>,
+}
+
+pub struct DraftQuery;
+pub struct DraftMutation;
+
+impl DraftQuery {
+    pub async fn find_draft<C: ConnectionTrait>(
+        db: &C,
+        post_id: i32,
+    ) -> Result<Option<post_draft::Model>, DbErr> {
+        PostDraft::find_by_id(post_id).one(db).await
+    }
+}
+
+impl DraftMutation {
+    // 記事ごとに 1 件だけ持ち、保存のたびに上書きする
+    pub async fn save_draft<C: ConnectionTrait>(
+        db: &C,
+        post_id: i32,
+        payload: DraftSave,
+        now: DateTimeUtc,
+    ) -> Result<post_draft::Model, DbErr> {
+        let draft = post_draft::Model {
+            post_id,
+            title: payload.title,
+            body: payload.body,
+            editor_id: payload.editor_id,
+            updated_at: now,
+        };
+        PostDraft::insert(ActiveModel::from(draft.clone()))
+            .on_conflict(
+                OnConflict::column(post_draft::Column::PostId)
+                    .update_columns([
+                        post_draft::Column::Title,
+                        post_draft::Column::Body,
+                        post_draft::Column::EditorId,
+                        post_draft::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await?;
+        Ok(draft)
+    }
+
+    pub async fn delete_draft<C: ConnectionTrait>(
+        db: &C,
+        post_id: i32,
+    ) -> Result<DeleteResult, DbErr> {
+        PostDraft::delete_by_id(post_id).exec(db).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Transaction};
+
+    #[tokio::test]
+    async fn save_draft_upserts() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_exec_results([MockExecResult {
+                last_insert_id: 15,
+                rows_affected: 1,
+            }])
+            .into_connection();
+
+        let payload = DraftSave {
+            title: "wip".to_owned(),
+            body: "half written".to_owned(),
+            editor_id: None,
+        };
+        let draft = DraftMutation::save_draft(&db, 15, payload, now)
+            .await
+            .expect("[save] returned Err");
+        assert_eq!("wip", draft.title);
+
+        assert_eq!(
+            db.into_transaction_log(),
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Sqlite,
+                r#"INSERT INTO "post_drafts" ("post_id", "title", "body", "editor_id", "updated_at") VALUES (?, ?, ?, ?, ?) ON CONFLICT ("post_id") DO UPDATE SET "title" = "excluded"."title", "body" = "excluded"."body", "editor_id" = "excluded"."editor_id", "updated_at" = "excluded"."updated_at""#,
+                [
+                    15.into(),
+                    "wip".into(),
+                    "half written".into(),
+                    None::<i32>.into(),
+                    now.into(),
+                ],
+            )]
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use ::entity::feed_state::{self, ActiveModel, Entity as FeedState};
+use sea_orm::{prelude::DateTimeUtc, sea_query::OnConflict, *};
+
+// 行は 1 件だけ持つ
+const FEED_STATE_ID: i32 = 1;
+
+pub struct FeedQuery;
+pub struct FeedMutation;
+
+impl FeedQuery {
+    // まだ一度も変わっていなければ None
+    pub async fn find_changed_at<C: ConnectionTrait>(db: &C) -> Result<Option<DateTimeUtc>, DbErr> {
+        Ok(FeedState::find_by_id(FEED_STATE_ID)
+            .one(db)
+            .await?
+            .map(|state| state.changed_at))
+    }
+}
+
+impl FeedMutation {
+    // フィードの中身が変わる操作（公開・非公開・削除・編集）のあとに、同じトランザクションで呼ぶ。
+    // 記事が消えても、この日時は戻らない
+    pub async fn touch<C: ConnectionTrait>(db: &C, now: DateTimeUtc) -> Result<(), DbErr> {
+        FeedState::insert(ActiveModel {
+            id: Set(FEED_STATE_ID),
+            changed_at: Set(now),
+        })
+        .on_conflict(
+            OnConflict::column(feed_state::Column::Id)
+                .update_column(feed_state::Column::ChangedAt)
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Transaction};
+
+    #[tokio::test]
+    async fn touch_upserts_single_row() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_exec_results([MockExecResult {
+                last_insert_id: 1,
+                rows_affected: 1,
+            }])
+            .into_connection();
+
+        FeedMutation::touch(&db, now)
+            .await
+            .expect("[touch] returned Err");
+        assert_eq!(
+            db.into_transaction_log(),
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Sqlite,
+                r#"INSERT INTO "feed_state" ("id", "changed_at") VALUES (?, ?) ON CONFLICT ("id") DO UPDATE SET "changed_at" = "excluded"."changed_at""#,
+                [1.into(), now.into()],
+            )]
+        );
+    }
+}
@@ -0,0 +1,720 @@
+use super::{
+    feed::FeedMutation,
+    find_or_not_found,
+    revision::{RevisionMutation, RevisionQuery},
+    tag::TagMutation,
+};
+use ::entity::{
+    post,
+    post::{ActiveModel, Entity as Post},
+    slug::slugify,
+};
+use chrono::Utc;
+use sea_orm::{
+    prelude::DateTimeUtc,
+    sea_query::{Expr, Func, LikeExpr, SimpleExpr},
+    *,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+pub struct PostUpdate {
+    pub title: String,
+    pub body: String,
+    // None ならタグは変更しない
+    pub tags: Option<Vec<String>>,
+    pub editor_id: Option<i32>,
+}
+
+pub struct PostCreate {
+    pub title: String,
+    pub body: String,
+    pub tags: Vec<String>,
+    pub author_id: Option<i32>,
+}
+
+// 一覧の並び順に使う日時
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PostSort {
+    #[default]
+    Created,
+    // 公開日時（予約公開なら予約日時）。未公開の記事は日時がないので NULL として並ぶ
+    Published,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl From<SortDirection> for Order {
+    fn from(direction: SortDirection) -> Self {
+        match direction {
+            SortDirection::Asc => Order::Asc,
+            SortDirection::Desc => Order::Desc,
+        }
+    }
+}
+
+pub struct PostListQuery {
+    // 1 始まり
+    pub page: u64,
+    pub per_page: u64,
+    pub sort: PostSort,
+    pub direction: SortDirection,
+    // タイトルと本文の部分一致で絞り込む
+    pub q: Option<String>,
+    // Some なら、その時点で公開されている記事だけに絞る
+    pub public_at: Option<DateTimeUtc>,
+}
+
+pub struct PostPage {
+    pub posts: Vec<post::Model>,
+    pub total_items: u64,
+    pub total_pages: u64,
+}
+
+pub struct PostQuery;
+pub struct PostMutation;
+
+impl PostQuery {
+    pub async fn find_post_by_id<C: ConnectionTrait>(
+        db: &C,
+        id: i32,
+    ) -> Result<Option<post::Model>, DbErr> {
+        Post::find_by_id(id).one(db).await
+    }
+
+    pub async fn find_post_by_slug<C: ConnectionTrait>(
+        db: &C,
+        slug: &str,
+    ) -> Result<Option<post::Model>, DbErr> {
+        Post::find()
+            .filter(post::Column::Slug.eq(slug))
+            .one(db)
+            .await
+    }
+
+    pub async fn find_posts_in_page<C: ConnectionTrait>(
+        db: &C,
+        query: &PostListQuery,
+    ) -> Result<PostPage, DbErr> {
+        let mut select = Post::find();
+        if let Some(now) = query.public_at {
+            select = select.filter(public_condition(now));
+        }
+        if let Some(q) = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+            select = select.filter(
+                Condition::any()
+                    .add(contains_literally(post::Column::Title, q))
+                    .add(contains_literally(post::Column::Body, q)),
+            );
+        }
+        let order = Order::from(query.direction);
+        select = match query.sort {
+            PostSort::Created => select.order_by(post::Column::CreatedAt, order.clone()),
+            PostSort::Published => select.order_by(
+                SimpleExpr::FunctionCall(Func::coalesce([
+                    Expr::col((Post, post::Column::PublishedAt)).into(),
+                    Expr::col((Post, post::Column::PublishAt)).into(),
+                ])),
+                order.clone(),
+            ),
+        };
+        // 同じ日時の記事があってもページをまたいで順序がぶれないよう id でも並べる
+        let paginator = select
+            .order_by(post::Column::Id, order)
+            .paginate(db, query.per_page);
+        let totals = paginator.num_items_and_pages().await?;
+        let posts = paginator.fetch_page(query.page.saturating_sub(1)).await?;
+
+        Ok(PostPage {
+            posts,
+            total_items: totals.number_of_items as u64,
+            total_pages: totals.number_of_pages as u64,
+        })
+    }
+
+    // 公開済みか、予約日時を過ぎた記事だけを返す
+    pub async fn find_public_posts<C: ConnectionTrait>(
+        db: &C,
+        now: DateTimeUtc,
+    ) -> Result<Vec<post::Model>, DbErr> {
+        post::Entity::find()
+            .filter(public_condition(now))
+            .all(db)
+            .await
+    }
+}
+
+// タイトルからスラッグを作り、既に使われていれば -2, -3 ... と空いている番号を付ける
+async fn unique_slug<C: ConnectionTrait>(db: &C, title: &str) -> Result<String, DbErr> {
+    let mut base = slugify(title);
+    if base.is_empty() {
+        base = "post".to_string();
+    }
+    let taken: HashSet<String> = Post::find()
+        .filter(
+            Condition::any()
+                .add(post::Column::Slug.eq(base.as_str()))
+                .add(post::Column::Slug.starts_with(&format!("{}-", base))),
+        )
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| p.slug)
+        .collect();
+
+    let mut slug = base.clone();
+    let mut n = 2;
+    while taken.contains(&slug) {
+        slug = format!("{}-{}", base, n);
+        n += 1;
+    }
+    Ok(slug)
+}
+
+// 部分一致の条件。q に含まれる % や _ はワイルドカードではなく文字として探す
+fn contains_literally(column: post::Column, q: &str) -> SimpleExpr {
+    let mut pattern = String::from("%");
+    for c in q.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    Expr::col((Post, column)).like(LikeExpr::new(pattern).escape('\\'))
+}
+
+pub(crate) fn public_condition(now: DateTimeUtc) -> Condition {
+    Condition::any()
+        .add(post::Column::Published.eq(true))
+        .add(post::Column::PublishAt.lte(now))
+}
+
+impl PostMutation {
+    // 記事・タグ・最初の版をまとめて 1 つのトランザクションで作る
+    pub async fn create_post<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        payload: PostCreate,
+    ) -> Result<post::Model, DbErr> {
+        let txn = db.begin().await?;
+        let slug = unique_slug(&txn, &payload.title).await?;
+        let post = ActiveModel {
+            id: ActiveValue::NotSet,
+            title: ActiveValue::set(payload.title.to_string()),
+            slug: ActiveValue::set(slug),
+            body: ActiveValue::set(payload.body.to_string()),
+            published: ActiveValue::set(false),
+            publish_at: ActiveValue::set(None),
+            published_at: ActiveValue::set(None),
+            created_at: ActiveValue::set(Utc::now()),
+            author_id: ActiveValue::set(payload.author_id),
+        };
+
+        let post = post.insert(&txn).await?;
+        if !payload.tags.is_empty() {
+            TagMutation::set_post_tags(&txn, post.id, &payload.tags).await?;
+        }
+        RevisionMutation::record_revision(&txn, &post, payload.author_id, post.created_at).await?;
+        txn.commit().await?;
+        Ok(post)
+    }
+
+    // 本文・タグ・版の記録のどれかが失敗したら、すべて元に戻す
+    pub async fn update_post_by_id<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        id: i32,
+        payload: PostUpdate,
+    ) -> Result<post::Model, DbErr> {
+        let txn = db.begin().await?;
+        let mut post: ActiveModel = find_or_not_found::<Post, _>(&txn, id, "post").await?.into();
+        post.title = Set(payload.title);
+        post.body = Set(payload.body);
+
+        let post = post.update(&txn).await?;
+        if let Some(tags) = payload.tags {
+            TagMutation::set_post_tags(&txn, post.id, &tags).await?;
+        }
+        let now = Utc::now();
+        RevisionMutation::record_revision(&txn, &post, payload.editor_id, now).await?;
+        FeedMutation::touch(&txn, now).await?;
+        txn.commit().await?;
+        Ok(post)
+    }
+
+    // 過去の版の内容で更新する。履歴は書き換えず、戻した内容を新しい版として積む
+    pub async fn restore_revision<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        id: i32,
+        rev: i32,
+        editor_id: Option<i32>,
+    ) -> Result<post::Model, DbErr> {
+        let revision = RevisionQuery::find_revision(db, id, rev).await?;
+        let payload = PostUpdate {
+            title: revision.title,
+            body: revision.body,
+            tags: None,
+            editor_id,
+        };
+        Self::update_post_by_id(db, id, payload).await
+    }
+
+    // publish_at が未来なら予約公開、そうでなければ即時公開する
+    pub async fn publish_post_by_id<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        id: i32,
+        publish_at: Option<DateTimeUtc>,
+        now: DateTimeUtc,
+    ) -> Result<post::Model, DbErr> {
+        let txn = db.begin().await?;
+        let mut post: ActiveModel = find_or_not_found::<Post, _>(&txn, id, "post").await?.into();
+        match publish_at.filter(|at| *at > now) {
+            Some(at) => {
+                post.published = Set(false);
+                post.publish_at = Set(Some(at));
+                post.published_at = Set(None);
+            }
+            None => {
+                post.published = Set(true);
+                post.publish_at = Set(None);
+                post.published_at = Set(Some(now));
+            }
+        }
+
+        let post = post.update(&txn).await?;
+        FeedMutation::touch(&txn, now).await?;
+        txn.commit().await?;
+        Ok(post)
+    }
+
+    pub async fn unpublish_post_by_id<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        id: i32,
+    ) -> Result<post::Model, DbErr> {
+        let txn = db.begin().await?;
+        let mut post: ActiveModel = find_or_not_found::<Post, _>(&txn, id, "post").await?.into();
+        post.published = Set(false);
+        post.publish_at = Set(None);
+        post.published_at = Set(None);
+
+        let post = post.update(&txn).await?;
+        FeedMutation::touch(&txn, Utc::now()).await?;
+        txn.commit().await?;
+        Ok(post)
+    }
+
+    pub async fn delete_post_by_id<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        id: i32,
+    ) -> Result<DeleteResult, DbErr> {
+        let txn = db.begin().await?;
+        let res = post::Entity::delete_by_id(id).exec(&txn).await?;
+        FeedMutation::touch(&txn, Utc::now()).await?;
+        txn.commit().await?;
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Transaction, Value};
+    use std::collections::BTreeMap;
+
+    #[tokio::test]
+    async fn find_post_by_id() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[post::Model {
+                id: 15,
+                title: "Test Post".to_owned(),
+                slug: "test-post".to_owned(),
+                body: "This is a test post".to_owned(),
+                published: false,
+                publish_at: None,
+                published_at: None,
+                created_at: Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+                author_id: Some(1),
+            }]])
+            .into_connection();
+
+        let post = PostQuery::find_post_by_id(&db, 15)
+            .await
+            .expect("[find] returned Err")
+            .unwrap();
+
+        assert_eq!("Test Post".to_owned(), post.title);
+        assert_eq!("This is a test post".to_owned(), post.body);
+    }
+
+    #[tokio::test]
+    async fn find_posts_in_page_searches_and_orders() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[BTreeMap::from([("num_items", Value::from(3))])]])
+            .append_query_results([[post::Model {
+                id: 15,
+                title: "Test Post".to_owned(),
+                slug: "test-post".to_owned(),
+                body: "This is a test post".to_owned(),
+                published: true,
+                publish_at: None,
+                published_at: Some(now),
+                created_at: Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+                author_id: Some(1),
+            }]])
+            .into_connection();
+
+        let page = PostQuery::find_posts_in_page(
+            &db,
+            &PostListQuery {
+                page: 2,
+                per_page: 2,
+                sort: PostSort::Published,
+                direction: SortDirection::Desc,
+                // % と _ はワイルドカードにしない
+                q: Some(" 100%_test ".to_owned()),
+                public_at: Some(now),
+            },
+        )
+        .await
+        .expect("[page] returned Err");
+        assert_eq!(3, page.total_items);
+        assert_eq!(2, page.total_pages);
+        assert_eq!(15, page.posts[0].id);
+
+        let select = r#"SELECT "posts"."id", "posts"."title", "posts"."slug", "posts"."body", "posts"."published", "posts"."publish_at", "posts"."published_at", "posts"."created_at", "posts"."author_id" FROM "posts" WHERE ("posts"."published" = ? OR "posts"."publish_at" <= ?) AND ("posts"."title" LIKE ? ESCAPE '\' OR "posts"."body" LIKE ? ESCAPE '\') ORDER BY COALESCE("posts"."published_at", "posts"."publish_at") DESC, "posts"."id" DESC"#;
+        let values = || -> Vec<Value> {
+            vec![
+                true.into(),
+                now.into(),
+                r"%100\%\_test%".into(),
+                r"%100\%\_test%".into(),
+            ]
+        };
+        let mut page_values = values();
+        page_values.extend([2u64.into(), 2u64.into()]);
+        assert_eq!(
+            db.into_transaction_log(),
+            vec![
+                Transaction::from_sql_and_values(
+                    DatabaseBackend::Sqlite,
+                    &format!(
+                        r#"SELECT COUNT(*) AS num_items FROM ({}) AS "sub_query""#,
+                        select
+                    ),
+                    values(),
+                ),
+                Transaction::from_sql_and_values(
+                    DatabaseBackend::Sqlite,
+                    &format!("{} LIMIT ? OFFSET ?", select),
+                    page_values,
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+
+    async fn test_create_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            // スラッグの重複チェック
+            .append_query_results([Vec::<post::Model>::new()])
+            .append_query_results([[post::Model {
+                id: 15,
+                title: "Test Post".to_owned(),
+                slug: "test-post".to_owned(),
+                body: "This is a test post".to_owned(),
+                published: false,
+                publish_at: None,
+                published_at: None,
+                created_at: Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+                author_id: Some(1),
+            }]])
+            // 版番号の採番
+            .append_query_results([Vec::<BTreeMap<&str, Value>>::new()])
+            .append_exec_results([
+                MockExecResult {
+                    last_insert_id: 15,
+                    rows_affected: 1,
+                },
+                MockExecResult {
+                    last_insert_id: 1,
+                    rows_affected: 1,
+                },
+            ])
+            .into_connection();
+
+        let payload = PostCreate {
+            title: "Test Post".to_owned(),
+            body: "This is a test post".to_owned(),
+            tags: vec![],
+            author_id: Some(1),
+        };
+
+        let created = PostMutation::create_post(&db, payload)
+            .await
+            .expect("[create] returned Err");
+        assert_eq!("Test Post".to_owned(), created.title);
+        assert_eq!("This is a test post".to_owned(), created.body);
+    }
+
+    #[tokio::test]
+    async fn create_post_with_tags_in_one_transaction() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([Vec::<post::Model>::new()])
+            .append_query_results([[post::Model {
+                id: 15,
+                title: "Test Post".to_owned(),
+                slug: "test-post".to_owned(),
+                body: "This is a test post".to_owned(),
+                published: false,
+                publish_at: None,
+                published_at: None,
+                created_at: Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+                author_id: Some(1),
+            }]])
+            // 既存タグの検索
+            .append_query_results([[::entity::tag::Model {
+                id: 3,
+                name: "rust".to_owned(),
+                slug: "rust".to_owned(),
+            }]])
+            .append_query_results([Vec::<BTreeMap<&str, Value>>::new()])
+            .append_exec_results([
+                // 記事、post_tags の delete と insert、版
+                MockExecResult {
+                    last_insert_id: 15,
+                    rows_affected: 1,
+                },
+                MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 0,
+                },
+                MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                },
+                MockExecResult {
+                    last_insert_id: 1,
+                    rows_affected: 1,
+                },
+            ])
+            .into_connection();
+
+        let payload = PostCreate {
+            title: "Test Post".to_owned(),
+            body: "This is a test post".to_owned(),
+            tags: vec!["Rust".to_owned()],
+            author_id: Some(1),
+        };
+        PostMutation::create_post(&db, payload)
+            .await
+            .expect("[create] returned Err");
+
+        // タグの付け替えまで含めて 1 つのトランザクションに入っている
+        let log = db.into_transaction_log();
+        assert_eq!(1, log.len());
+        let statements = format!("{:?}", log[0]);
+        assert!(statements.contains(r#"INSERT INTO \"posts\""#));
+        assert!(statements.contains(r#"DELETE FROM \"post_tags\""#));
+        assert!(statements.contains(r#"INSERT INTO \"post_tags\""#));
+        assert!(statements.contains("COMMIT"));
+    }
+
+    #[tokio::test]
+    async fn unique_slug_appends_suffix() {
+        let taken = |slug: &str| post::Model {
+            id: 1,
+            title: "Hello World".to_owned(),
+            slug: slug.to_owned(),
+            body: "".to_owned(),
+            published: false,
+            publish_at: None,
+            published_at: None,
+            created_at: Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+            author_id: Some(1),
+        };
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[
+                taken("hello-world"),
+                taken("hello-world-2"),
+                taken("hello-world-again"),
+            ]])
+            .append_query_results([Vec::<post::Model>::new()])
+            .into_connection();
+
+        assert_eq!(
+            "hello-world-3",
+            unique_slug(&db, "Hello World").await.unwrap()
+        );
+        assert_eq!("post", unique_slug(&db, "!!!").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_update_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[post::Model {
+                id: 15,
+                title: "Test Post".to_owned(),
+                slug: "test-post".to_owned(),
+                body: "This is a test post".to_owned(),
+                published: false,
+                publish_at: None,
+                published_at: None,
+                created_at: Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+                author_id: Some(1),
+            }]])
+            .append_query_results([[post::Model {
+                id: 15,
+                title: "Updated Test Post".to_owned(),
+                slug: "test-post".to_owned(),
+                body: "This is an updated test post".to_owned(),
+                published: false,
+                publish_at: None,
+                published_at: None,
+                created_at: Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+                author_id: Some(1),
+            }]])
+            .append_query_results([[BTreeMap::from([("rev", Value::from(1))])]])
+            .append_exec_results([
+                MockExecResult {
+                    last_insert_id: 15,
+                    rows_affected: 1,
+                },
+                MockExecResult {
+                    last_insert_id: 2,
+                    rows_affected: 1,
+                },
+                // feed_state の upsert
+                MockExecResult {
+                    last_insert_id: 1,
+                    rows_affected: 1,
+                },
+            ])
+            .into_connection();
+
+        let payload = PostUpdate {
+            title: "Updated Test Post".to_owned(),
+            body: "This is an updated test post".to_owned(),
+            tags: None,
+            editor_id: Some(1),
+        };
+
+        let updated = PostMutation::update_post_by_id(&db, 15, payload)
+            .await
+            .expect("[update] returned Err");
+        assert_eq!("Updated Test Post".to_owned(), updated.title);
+        assert_eq!("This is an updated test post".to_owned(), updated.body);
+
+        // 本文の更新・版の記録が 1 つのトランザクションに入っている。下書きはここでは消さない
+        let log = db.into_transaction_log();
+        assert_eq!(1, log.len());
+        let statements = format!("{:?}", log[0]);
+        assert!(statements.contains(r#"UPDATE \"posts\""#));
+        assert!(statements.contains(r#"INSERT INTO \"post_revisions\""#));
+        assert!(!statements.contains(r#"\"post_drafts\""#));
+        assert!(statements.contains(r#"INSERT INTO \"feed_state\""#));
+        assert!(statements.contains("COMMIT"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_post() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_exec_results([
+                MockExecResult {
+                    last_insert_id: 15,
+                    rows_affected: 1,
+                },
+                MockExecResult {
+                    last_insert_id: 1,
+                    rows_affected: 1,
+                },
+            ])
+            .into_connection();
+
+        let deleted = PostMutation::delete_post_by_id(&db, 15)
+            .await
+            .expect("[delete] returned Err");
+        assert_eq!(1, deleted.rows_affected);
+    }
+
+    #[tokio::test]
+    async fn find_public_posts_filters_drafts() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([Vec::<post::Model>::new()])
+            .into_connection();
+
+        PostQuery::find_public_posts(&db, now)
+            .await
+            .expect("[public] returned Err");
+
+        let log = db.into_transaction_log();
+        assert_eq!(
+            log,
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Sqlite,
+                r#"SELECT "posts"."id", "posts"."title", "posts"."slug", "posts"."body", "posts"."published", "posts"."publish_at", "posts"."published_at", "posts"."created_at", "posts"."author_id" FROM "posts" WHERE "posts"."published" = ? OR "posts"."publish_at" <= ?"#,
+                [true.into(), now.into()],
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn publish_post_in_future_is_scheduled() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let at = now + Duration::days(1);
+        let draft = post::Model {
+            id: 15,
+            title: "Test Post".to_owned(),
+            slug: "test-post".to_owned(),
+            body: "This is a test post".to_owned(),
+            published: false,
+            publish_at: None,
+            published_at: None,
+            created_at: Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+            author_id: Some(1),
+        };
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[draft.clone()]])
+            .append_query_results([[post::Model {
+                publish_at: Some(at),
+                ..draft
+            }]])
+            .append_exec_results([
+                MockExecResult {
+                    last_insert_id: 3,
+                    rows_affected: 1,
+                },
+                MockExecResult {
+                    last_insert_id: 1,
+                    rows_affected: 1,
+                },
+            ])
+            .into_connection();
+
+        let scheduled = PostMutation::publish_post_by_id(&db, 15, Some(at), now)
+            .await
+            .expect("[publish] returned Err");
+        assert!(!scheduled.is_public(now));
+        assert!(scheduled.is_public(at));
+        assert_eq!(Some(at), scheduled.published_time(at));
+    }
+
+    #[tokio::test]
+    async fn publish_missing_post_is_not_found() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([Vec::<post::Model>::new()])
+            .into_connection();
+
+        let res = PostMutation::publish_post_by_id(&db, 15, None, Utc::now()).await;
+        assert!(matches!(res, Err(DbErr::RecordNotFound(_))));
+    }
+}
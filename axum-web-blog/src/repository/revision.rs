@@ -0,0 +1,201 @@
+use ::entity::{
+    post,
+    post_revision::{self, ActiveModel, Entity as PostRevision},
+};
+use sea_orm::{prelude::DateTimeUtc, *};
+use std::collections::HashMap;
+
+#[derive(Debug, FromQueryResult)]
+struct LatestRev {
+    rev: Option<i32>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct LatestRevisionTime {
+    post_id: i32,
+    created_at: DateTimeUtc,
+}
+
+pub struct RevisionQuery;
+pub struct RevisionMutation;
+
+impl RevisionQuery {
+    // 古い順に返す
+    pub async fn find_revisions_by_post<C: ConnectionTrait>(
+        db: &C,
+        post_id: i32,
+    ) -> Result<Vec<post_revision::Model>, DbErr> {
+        PostRevision::find()
+            .filter(post_revision::Column::PostId.eq(post_id))
+            .order_by_asc(post_revision::Column::Rev)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_revision<C: ConnectionTrait>(
+        db: &C,
+        post_id: i32,
+        rev: i32,
+    ) -> Result<post_revision::Model, DbErr> {
+        PostRevision::find()
+            .filter(post_revision::Column::PostId.eq(post_id))
+            .filter(post_revision::Column::Rev.eq(rev))
+            .one(db)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("revision {} of post {}", rev, post_id)))
+    }
+
+    // 記事ごとに最後に内容を保存した日時を 1 クエリでまとめて取る
+    pub async fn find_latest_revision_times<C: ConnectionTrait>(
+        db: &C,
+        post_ids: &[i32],
+    ) -> Result<HashMap<i32, DateTimeUtc>, DbErr> {
+        if post_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows = PostRevision::find()
+            .select_only()
+            .column(post_revision::Column::PostId)
+            .column_as(post_revision::Column::CreatedAt.max(), "created_at")
+            .filter(post_revision::Column::PostId.is_in(post_ids.iter().copied()))
+            .group_by(post_revision::Column::PostId)
+            .into_model::<LatestRevisionTime>()
+            .all(db)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.post_id, row.created_at))
+            .collect())
+    }
+}
+
+impl RevisionMutation {
+    // 保存後の記事の内容を次の rev として残す。
+    // 記事の insert / update と同じトランザクションの中で、その後に呼ぶ。
+    // 先に記事の行を書き換えておけば同じ記事の保存は順番に待たされるので、
+    // MAX(rev) + 1 が (post_id, rev) の一意制約でぶつからない
+    pub async fn record_revision<C: ConnectionTrait>(
+        db: &C,
+        post: &post::Model,
+        editor_id: Option<i32>,
+        now: DateTimeUtc,
+    ) -> Result<i32, DbErr> {
+        let latest = PostRevision::find()
+            .select_only()
+            .column_as(post_revision::Column::Rev.max(), "rev")
+            .filter(post_revision::Column::PostId.eq(post.id))
+            .into_model::<LatestRev>()
+            .one(db)
+            .await?
+            .and_then(|l| l.rev)
+            .unwrap_or(0);
+        let rev = latest + 1;
+        // 戻り値のモデルは使わないので、挿入後に読み直さない exec で済ませる
+        PostRevision::insert(ActiveModel {
+            id: ActiveValue::NotSet,
+            post_id: Set(post.id),
+            rev: Set(rev),
+            title: Set(post.title.clone()),
+            body: Set(post.body.clone()),
+            editor_id: Set(editor_id),
+            created_at: Set(now),
+        })
+        .exec(db)
+        .await?;
+        Ok(rev)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Transaction, Value};
+    use std::collections::BTreeMap;
+
+    #[tokio::test]
+    async fn record_revision_uses_next_rev() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let post = post::Model {
+            id: 15,
+            title: "Test Post".to_owned(),
+            slug: "test-post".to_owned(),
+            body: "This is a test post".to_owned(),
+            published: false,
+            publish_at: None,
+            published_at: None,
+            created_at: now,
+            author_id: Some(1),
+        };
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[BTreeMap::from([("rev", Value::from(2))])]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 7,
+                rows_affected: 1,
+            }])
+            .into_connection();
+
+        let rev = RevisionMutation::record_revision(&db, &post, Some(1), now)
+            .await
+            .expect("[record] returned Err");
+        assert_eq!(3, rev);
+
+        assert_eq!(
+            db.into_transaction_log(),
+            vec![
+                Transaction::from_sql_and_values(
+                    DatabaseBackend::Sqlite,
+                    r#"SELECT MAX("post_revisions"."rev") AS "rev" FROM "post_revisions" WHERE "post_revisions"."post_id" = ? LIMIT ?"#,
+                    [15.into(), 1u64.into()],
+                ),
+                Transaction::from_sql_and_values(
+                    DatabaseBackend::Sqlite,
+                    r#"INSERT INTO "post_revisions" ("post_id", "rev", "title", "body", "editor_id", "created_at") VALUES (?, ?, ?, ?, ?, ?)"#,
+                    [
+                        15.into(),
+                        3.into(),
+                        "Test Post".into(),
+                        "This is a test post".into(),
+                        Some(1).into(),
+                        now.into(),
+                    ],
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn find_latest_revision_times_groups_by_post() {
+        let at = Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[BTreeMap::from([
+                ("post_id", Value::from(15)),
+                ("created_at", Value::from(at)),
+            ])]])
+            .into_connection();
+
+        let times = RevisionQuery::find_latest_revision_times(&db, &[15, 16])
+            .await
+            .expect("[times] returned Err");
+        assert_eq!(HashMap::from([(15, at)]), times);
+
+        assert_eq!(
+            db.into_transaction_log(),
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Sqlite,
+                r#"SELECT "post_revisions"."post_id", MAX("post_revisions"."created_at") AS "created_at" FROM "post_revisions" WHERE "post_revisions"."post_id" IN (?, ?) GROUP BY "post_revisions"."post_id""#,
+                [15.into(), 16.into()],
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_revision_is_not_found() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([Vec::<post_revision::Model>::new()])
+            .into_connection();
+
+        let err = RevisionQuery::find_revision(&db, 15, 9).await.unwrap_err();
+        assert!(matches!(err, DbErr::RecordNotFound(_)));
+    }
+}
@@ -0,0 +1,261 @@
+use super::post::public_condition;
+use ::entity::{
+    post, post_tag,
+    slug::slugify,
+    tag::{self, Entity as Tag, TaggedPosts},
+};
+use sea_orm::{prelude::DateTimeUtc, *};
+use std::collections::HashMap;
+
+#[derive(Debug, FromQueryResult)]
+struct PostTagRow {
+    post_id: i32,
+    id: i32,
+    name: String,
+    slug: String,
+}
+
+pub struct TagQuery;
+pub struct TagMutation;
+
+impl TagQuery {
+    // now が Some なら公開中の記事が 1 件以上あるタグだけに絞る
+    pub async fn find_all_tags<C: ConnectionTrait>(
+        db: &C,
+        now: Option<DateTimeUtc>,
+    ) -> Result<Vec<tag::Model>, DbErr> {
+        let mut query = Tag::find();
+        if let Some(now) = now {
+            let public_tag_ids = post_tag::Entity::find()
+                .select_only()
+                .column(post_tag::Column::TagId)
+                .join(JoinType::InnerJoin, post_tag::Relation::Post.def())
+                .filter(public_condition(now))
+                .into_query();
+            query = query.filter(tag::Column::Id.in_subquery(public_tag_ids));
+        }
+        query.order_by_asc(tag::Column::Name).all(db).await
+    }
+
+    pub async fn find_tag_by_slug<C: ConnectionTrait>(
+        db: &C,
+        slug: &str,
+    ) -> Result<Option<tag::Model>, DbErr> {
+        Tag::find().filter(tag::Column::Slug.eq(slug)).one(db).await
+    }
+
+    // now が Some なら公開中の記事だけに絞る
+    pub async fn find_posts_by_tag<C: ConnectionTrait>(
+        db: &C,
+        tag: &tag::Model,
+        now: Option<DateTimeUtc>,
+    ) -> Result<Vec<post::Model>, DbErr> {
+        let mut query = tag.find_linked(TaggedPosts);
+        if let Some(now) = now {
+            query = query.filter(public_condition(now));
+        }
+        query.order_by_asc(post::Column::Id).all(db).await
+    }
+
+    // 記事ごとのタグを 1 クエリでまとめて取る
+    pub async fn find_tags_by_posts<C: ConnectionTrait>(
+        db: &C,
+        post_ids: &[i32],
+    ) -> Result<HashMap<i32, Vec<tag::Model>>, DbErr> {
+        if post_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows = Tag::find()
+            .select_only()
+            .column(post_tag::Column::PostId)
+            .columns([tag::Column::Id, tag::Column::Name, tag::Column::Slug])
+            .join(JoinType::InnerJoin, tag::Relation::PostTag.def())
+            .filter(post_tag::Column::PostId.is_in(post_ids.iter().copied()))
+            .order_by_asc(tag::Column::Name)
+            .into_model::<PostTagRow>()
+            .all(db)
+            .await?;
+
+        let mut tags: HashMap<i32, Vec<tag::Model>> = HashMap::new();
+        for row in rows {
+            tags.entry(row.post_id).or_default().push(tag::Model {
+                id: row.id,
+                name: row.name,
+                slug: row.slug,
+            });
+        }
+        Ok(tags)
+    }
+}
+
+impl TagMutation {
+    // 記事のタグを names で置き換える。まだないタグは作る。
+    // 消してから入れ直すので、呼び出し側のトランザクションの中で呼ぶ
+    pub async fn set_post_tags<C: ConnectionTrait>(
+        db: &C,
+        post_id: i32,
+        names: &[String],
+    ) -> Result<Vec<tag::Model>, DbErr> {
+        let mut wanted: Vec<(String, String)> = vec![];
+        for name in names {
+            let slug = slugify(name);
+            if !slug.is_empty() && !wanted.iter().any(|(s, _)| *s == slug) {
+                wanted.push((slug, name.trim().to_string()));
+            }
+        }
+
+        let mut tags = if wanted.is_empty() {
+            vec![]
+        } else {
+            Tag::find()
+                .filter(tag::Column::Slug.is_in(wanted.iter().map(|(slug, _)| slug.clone())))
+                .all(db)
+                .await?
+        };
+        for (slug, name) in wanted {
+            if tags.iter().any(|t| t.slug == slug) {
+                continue;
+            }
+            let tag = tag::ActiveModel {
+                id: ActiveValue::NotSet,
+                name: ActiveValue::set(name),
+                slug: ActiveValue::set(slug),
+            };
+            tags.push(tag.insert(db).await?);
+        }
+
+        post_tag::Entity::delete_many()
+            .filter(post_tag::Column::PostId.eq(post_id))
+            .exec(db)
+            .await?;
+        if !tags.is_empty() {
+            post_tag::Entity::insert_many(tags.iter().map(|tag| post_tag::ActiveModel {
+                post_id: ActiveValue::set(post_id),
+                tag_id: ActiveValue::set(tag.id),
+            }))
+            .exec(db)
+            .await?;
+        }
+
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Transaction};
+
+    fn tag_model(id: i32, name: &str) -> tag::Model {
+        tag::Model {
+            id,
+            name: name.to_owned(),
+            slug: slugify(name),
+        }
+    }
+
+    #[tokio::test]
+    async fn find_all_tags_skips_tags_without_public_posts() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([Vec::<tag::Model>::new()])
+            .into_connection();
+
+        TagQuery::find_all_tags(&db, Some(now))
+            .await
+            .expect("[all tags] returned Err");
+
+        assert_eq!(
+            db.into_transaction_log(),
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Sqlite,
+                r#"SELECT "tags"."id", "tags"."name", "tags"."slug" FROM "tags" WHERE "tags"."id" IN (SELECT "post_tags"."tag_id" FROM "post_tags" INNER JOIN "posts" ON "post_tags"."post_id" = "posts"."id" WHERE "posts"."published" = ? OR "posts"."publish_at" <= ?) ORDER BY "tags"."name" ASC"#,
+                [true.into(), now.into()],
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn find_posts_by_tag_uses_link() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([Vec::<post::Model>::new()])
+            .into_connection();
+
+        TagQuery::find_posts_by_tag(&db, &tag_model(2, "rust"), Some(now))
+            .await
+            .expect("[posts by tag] returned Err");
+
+        assert_eq!(
+            db.into_transaction_log(),
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Sqlite,
+                r#"SELECT "posts"."id", "posts"."title", "posts"."slug", "posts"."body", "posts"."published", "posts"."publish_at", "posts"."published_at", "posts"."created_at", "posts"."author_id" FROM "posts" INNER JOIN "post_tags" AS "r0" ON "r0"."post_id" = "posts"."id" INNER JOIN "tags" AS "r1" ON "r1"."id" = "r0"."tag_id" WHERE "r1"."id" = ? AND ("posts"."published" = ? OR "posts"."publish_at" <= ?) ORDER BY "posts"."id" ASC"#,
+                [2.into(), true.into(), now.into()],
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn find_tags_by_posts_groups_rows() {
+        let row = |post_id: i32, id: i32, name: &str| {
+            std::collections::BTreeMap::from([
+                ("post_id", Value::from(post_id)),
+                ("id", Value::from(id)),
+                ("name", Value::from(name)),
+                ("slug", Value::from(slugify(name))),
+            ])
+        };
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([[row(1, 2, "axum"), row(1, 3, "rust"), row(2, 3, "rust")]])
+            .into_connection();
+
+        let tags = TagQuery::find_tags_by_posts(&db, &[1, 2])
+            .await
+            .expect("[tags by posts] returned Err");
+        assert_eq!(vec![tag_model(2, "axum"), tag_model(3, "rust")], tags[&1]);
+        assert_eq!(vec![tag_model(3, "rust")], tags[&2]);
+    }
+
+    #[tokio::test]
+    async fn set_post_tags_creates_missing_tags() {
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            // 既存タグの検索
+            .append_query_results([[tag_model(3, "rust")]])
+            // 新しく作ったタグ
+            .append_query_results([[tag_model(4, "Web API")]])
+            .append_exec_results([
+                // タグの insert
+                MockExecResult {
+                    last_insert_id: 4,
+                    rows_affected: 1,
+                },
+                // post_tags の delete
+                MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 0,
+                },
+                // post_tags の insert
+                MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 2,
+                },
+            ])
+            .into_connection();
+
+        let names = vec![
+            "Rust".to_string(),
+            "Web API".to_string(),
+            "rust".to_string(),
+        ];
+        let tags = TagMutation::set_post_tags(&db, 1, &names)
+            .await
+            .expect("[set tags] returned Err");
+        assert_eq!(
+            vec!["Web API", "rust"],
+            tags.iter().map(|t| t.name.as_str()).collect::<Vec<_>>()
+        );
+    }
+}
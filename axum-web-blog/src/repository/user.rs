@@ -0,0 +1,113 @@
+use ::entity::{
+    session,
+    user::{self, ActiveModel, Entity as User, UserRole},
+};
+use sea_orm::{prelude::DateTimeUtc, *};
+
+pub struct UserCreate {
+    pub username: String,
+    pub password_hash: String,
+    pub role: UserRole,
+}
+
+pub struct UserQuery;
+pub struct UserMutation;
+
+impl UserQuery {
+    pub async fn find_user_by_username<C: ConnectionTrait>(
+        db: &C,
+        username: &str,
+    ) -> Result<Option<user::Model>, DbErr> {
+        User::find()
+            .filter(user::Column::Username.eq(username))
+            .one(db)
+            .await
+    }
+
+    // 期限内のセッションを持つユーザーを返す
+    pub async fn find_user_by_token_hash<C: ConnectionTrait>(
+        db: &C,
+        token_hash: &str,
+        now: DateTimeUtc,
+    ) -> Result<Option<user::Model>, DbErr> {
+        User::find()
+            .inner_join(session::Entity)
+            .filter(session::Column::TokenHash.eq(token_hash))
+            .filter(session::Column::ExpiresAt.gt(now))
+            .one(db)
+            .await
+    }
+}
+
+impl UserMutation {
+    pub async fn create_user<C: ConnectionTrait>(
+        db: &C,
+        payload: UserCreate,
+        now: DateTimeUtc,
+    ) -> Result<user::Model, DbErr> {
+        ActiveModel {
+            id: ActiveValue::NotSet,
+            username: Set(payload.username),
+            password_hash: Set(payload.password_hash),
+            role: Set(payload.role),
+            created_at: Set(now),
+        }
+        .insert(db)
+        .await
+    }
+
+    pub async fn create_session<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        token_hash: String,
+        expires_at: DateTimeUtc,
+        now: DateTimeUtc,
+    ) -> Result<session::Model, DbErr> {
+        session::ActiveModel {
+            token_hash: Set(token_hash),
+            user_id: Set(user_id),
+            expires_at: Set(expires_at),
+            created_at: Set(now),
+        }
+        .insert(db)
+        .await
+    }
+
+    pub async fn delete_session<C: ConnectionTrait>(
+        db: &C,
+        token_hash: &str,
+    ) -> Result<DeleteResult, DbErr> {
+        session::Entity::delete_by_id(token_hash.to_string())
+            .exec(db)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use sea_orm::{DatabaseBackend, MockDatabase, Transaction};
+
+    #[tokio::test]
+    async fn find_user_by_token_hash_ignores_expired_sessions() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let db = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([Vec::<user::Model>::new()])
+            .into_connection();
+
+        let user = UserQuery::find_user_by_token_hash(&db, "abc", now)
+            .await
+            .expect("[find] returned Err");
+        assert_eq!(None, user);
+
+        assert_eq!(
+            db.into_transaction_log(),
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Sqlite,
+                r#"SELECT "users"."id", "users"."username", "users"."password_hash", "users"."role", "users"."created_at" FROM "users" INNER JOIN "sessions" ON "users"."id" = "sessions"."user_id" WHERE "sessions"."token_hash" = ? AND "sessions"."expires_at" > ? LIMIT ?"#,
+                ["abc".into(), now.into(), 1u64.into()],
+            )]
+        );
+    }
+}
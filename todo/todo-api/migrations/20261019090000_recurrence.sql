@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE todos ADD COLUMN due_date DATE;
+ALTER TABLE todos ADD COLUMN recurrence JSONB;
@@ -0,0 +1,7 @@
+-- Add migration script here
+ALTER TABLE todos
+  ADD COLUMN search_vector tsvector
+  GENERATED ALWAYS AS (setweight(to_tsvector('simple', text), 'A')) STORED;
+
+CREATE INDEX todos_search_vector_idx ON todos USING GIN (search_vector);
+CREATE INDEX labels_name_tsvector_idx ON labels USING GIN (to_tsvector('simple', name));
@@ -0,0 +1,3 @@
+-- Add migration script here
+-- ラベル名は検索時に string_agg でまとめてから to_tsvector するので、この索引は使われない
+DROP INDEX IF EXISTS labels_name_tsvector_idx;
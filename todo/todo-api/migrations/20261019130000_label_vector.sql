@@ -0,0 +1,52 @@
+-- Add migration script here
+-- ラベル名を todos 側の tsvector に持たせ、本文と合わせた式に GIN 索引を張る
+ALTER TABLE todos ADD COLUMN label_vector tsvector NOT NULL DEFAULT ''::tsvector;
+
+CREATE FUNCTION refresh_todo_label_vector(target integer) RETURNS void AS $$
+  UPDATE todos
+  SET label_vector = coalesce((
+    SELECT setweight(to_tsvector('simple', string_agg(labels.name, ' ')), 'B')
+    FROM todo_labels tl
+    JOIN labels ON labels.id = tl.label_id
+    WHERE tl.todo_id = target
+  ), ''::tsvector)
+  WHERE id = target;
+$$ LANGUAGE sql;
+
+CREATE FUNCTION todo_labels_refresh_label_vector() RETURNS trigger AS $$
+BEGIN
+  IF TG_OP IN ('UPDATE', 'DELETE') THEN
+    PERFORM refresh_todo_label_vector(OLD.todo_id);
+  END IF;
+  IF TG_OP IN ('INSERT', 'UPDATE') THEN
+    PERFORM refresh_todo_label_vector(NEW.todo_id);
+  END IF;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER todo_labels_label_vector
+  AFTER INSERT OR UPDATE OR DELETE ON todo_labels
+  FOR EACH ROW EXECUTE FUNCTION todo_labels_refresh_label_vector();
+
+CREATE FUNCTION labels_refresh_label_vector() RETURNS trigger AS $$
+BEGIN
+  PERFORM refresh_todo_label_vector(tl.todo_id)
+  FROM todo_labels tl
+  WHERE tl.label_id = OLD.id;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER labels_label_vector
+  AFTER UPDATE OF name OR DELETE ON labels
+  FOR EACH ROW EXECUTE FUNCTION labels_refresh_label_vector();
+
+UPDATE todos SET label_vector = coalesce((
+  SELECT setweight(to_tsvector('simple', string_agg(labels.name, ' ')), 'B')
+  FROM todo_labels tl
+  JOIN labels ON labels.id = tl.label_id
+  WHERE tl.todo_id = todos.id
+), ''::tsvector);
+
+CREATE INDEX todos_text_and_labels_idx ON todos USING GIN ((search_vector || label_vector));
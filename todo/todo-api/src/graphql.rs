@@ -0,0 +1,533 @@
+use crate::repositories::{
+    label::LabelRepository,
+    todo::{update_and_spawn_next, TodoRepository},
+    RepositoryError,
+};
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    Context, EmptySubscription, Enum, InputObject, Object, Result, Schema, SimpleObject,
+};
+use chrono::NaiveDate;
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+use todo_core::v1::{label, recurrence, todo};
+use validator::Validate;
+
+pub type TodoSchema<T, L> = Schema<QueryRoot<T, L>, MutationRoot<T, L>, EmptySubscription>;
+
+// リクエストごとに DataLoader を作るため、リポジトリだけを Schema に持たせる
+pub fn build_schema<T: TodoRepository, L: LabelRepository>(
+    todo_repository: Arc<T>,
+    label_repository: Arc<L>,
+) -> TodoSchema<T, L> {
+    Schema::build(
+        QueryRoot(PhantomData),
+        MutationRoot(PhantomData),
+        EmptySubscription,
+    )
+    .data(todo_repository)
+    .data(label_repository)
+    .finish()
+}
+
+pub fn todos_by_label_loader<T: TodoRepository>(
+    repository: Arc<T>,
+) -> DataLoader<TodosByLabelLoader<T>> {
+    DataLoader::new(TodosByLabelLoader(repository), tokio::spawn)
+}
+
+fn validate<V: Validate>(payload: &V) -> Result<()> {
+    payload.validate().map_err(|rejection| {
+        let message = format!("Validation error: [{}]", rejection).replace('\n', ", ");
+        async_graphql::Error::new(message)
+    })
+}
+
+// Label.todos をラベルごとに問い合わせると N+1 になるため、
+// 同じリクエスト内のラベル id をまとめて 1 回の all() で解決する
+pub struct TodosByLabelLoader<T>(Arc<T>);
+
+impl<T: TodoRepository> Loader<i32> for TodosByLabelLoader<T> {
+    type Value = Vec<todo::TodoEntity>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let todos = self.0.all().await.map_err(Arc::new)?;
+        let grouped = keys
+            .iter()
+            .map(|id| {
+                let filter = todo::TodoFilter {
+                    label: Some(*id),
+                    completed: None,
+                };
+                let todos = todos
+                    .iter()
+                    .filter(|todo| filter.matches(todo))
+                    .cloned()
+                    .collect();
+                (*id, todos)
+            })
+            .collect();
+        Ok(grouped)
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+#[graphql(remote = "recurrence::Frequency")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(SimpleObject, InputObject, Clone)]
+#[graphql(input_name = "RecurrenceInput")]
+pub struct Recurrence {
+    frequency: Frequency,
+    #[graphql(default = 1)]
+    interval: i32,
+    until: Option<NaiveDate>,
+    count: Option<i32>,
+    // 読み出した値をそのまま送り返せば、更新しても起点が失われない
+    anchor: Option<NaiveDate>,
+}
+
+impl From<recurrence::Recurrence> for Recurrence {
+    fn from(recurrence: recurrence::Recurrence) -> Self {
+        Self {
+            frequency: recurrence.frequency.into(),
+            interval: recurrence.interval,
+            until: recurrence.until,
+            count: recurrence.count,
+            anchor: recurrence.anchor,
+        }
+    }
+}
+
+impl From<Recurrence> for recurrence::Recurrence {
+    fn from(recurrence: Recurrence) -> Self {
+        Self {
+            frequency: recurrence.frequency.into(),
+            interval: recurrence.interval,
+            until: recurrence.until,
+            count: recurrence.count,
+            anchor: recurrence.anchor,
+        }
+    }
+}
+
+pub struct Todo<T>(todo::TodoEntity, PhantomData<T>);
+
+impl<T> From<todo::TodoEntity> for Todo<T> {
+    fn from(todo: todo::TodoEntity) -> Self {
+        Self(todo, PhantomData)
+    }
+}
+
+#[Object(name = "Todo")]
+impl<T: TodoRepository> Todo<T> {
+    async fn id(&self) -> i32 {
+        self.0.id
+    }
+
+    async fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    async fn completed(&self) -> bool {
+        self.0.completed
+    }
+
+    async fn due_date(&self) -> Option<NaiveDate> {
+        self.0.due_date
+    }
+
+    async fn recurrence(&self) -> Option<Recurrence> {
+        self.0.recurrence.clone().map(Recurrence::from)
+    }
+
+    // ラベルはリポジトリが Todo と join 済みなので追加の問い合わせは発生しない
+    async fn labels(&self) -> Vec<Label<T>> {
+        self.0.labels.iter().cloned().map(Label::from).collect()
+    }
+
+    async fn occurrences(
+        &self,
+        #[graphql(default = 5, validator(minimum = 1, maximum = 100))] count: usize,
+    ) -> Vec<NaiveDate> {
+        match (self.0.due_date, &self.0.recurrence) {
+            (Some(due_date), Some(recurrence)) => recurrence.occurrences(due_date, count),
+            _ => vec![],
+        }
+    }
+}
+
+pub struct Label<T>(label::Label, PhantomData<T>);
+
+impl<T> From<label::Label> for Label<T> {
+    fn from(label: label::Label) -> Self {
+        Self(label, PhantomData)
+    }
+}
+
+#[Object(name = "Label")]
+impl<T: TodoRepository> Label<T> {
+    async fn id(&self) -> i32 {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn todos(&self, ctx: &Context<'_>) -> Result<Vec<Todo<T>>> {
+        let loader = ctx.data_unchecked::<DataLoader<TodosByLabelLoader<T>>>();
+        let todos = loader.load_one(self.0.id).await?.unwrap_or_default();
+        Ok(todos.into_iter().map(Todo::from).collect())
+    }
+}
+
+#[derive(InputObject)]
+pub struct CreateTodoInput {
+    text: String,
+    #[graphql(default)]
+    labels: Vec<i32>,
+    due_date: Option<NaiveDate>,
+    recurrence: Option<Recurrence>,
+}
+
+impl From<CreateTodoInput> for todo::CreateTodo {
+    fn from(input: CreateTodoInput) -> Self {
+        Self {
+            text: input.text,
+            labels: input.labels,
+            due_date: input.due_date,
+            recurrence: input.recurrence.map(recurrence::Recurrence::from),
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct UpdateTodoInput {
+    text: Option<String>,
+    completed: Option<bool>,
+    labels: Option<Vec<i32>>,
+    due_date: Option<NaiveDate>,
+    recurrence: Option<Recurrence>,
+}
+
+impl From<UpdateTodoInput> for todo::UpdateTodo {
+    fn from(input: UpdateTodoInput) -> Self {
+        Self {
+            text: input.text,
+            completed: input.completed,
+            labels: input.labels,
+            due_date: input.due_date,
+            recurrence: input.recurrence.map(recurrence::Recurrence::from),
+        }
+    }
+}
+
+pub struct QueryRoot<T, L>(PhantomData<(T, L)>);
+
+#[Object]
+impl<T: TodoRepository, L: LabelRepository> QueryRoot<T, L> {
+    async fn todos(
+        &self,
+        ctx: &Context<'_>,
+        label: Option<i32>,
+        completed: Option<bool>,
+    ) -> Result<Vec<Todo<T>>> {
+        let filter = todo::TodoFilter { label, completed };
+        let todos = ctx.data_unchecked::<Arc<T>>().all().await?;
+        Ok(todos
+            .into_iter()
+            .filter(|todo| filter.matches(todo))
+            .map(Todo::from)
+            .collect())
+    }
+
+    async fn todo(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Todo<T>>> {
+        match ctx.data_unchecked::<Arc<T>>().find(id).await {
+            Ok(todo) => Ok(Some(todo.into())),
+            Err(e) => match e.downcast_ref::<RepositoryError>() {
+                Some(RepositoryError::NotFound(_)) => Ok(None),
+                _ => Err(e.into()),
+            },
+        }
+    }
+
+    async fn labels(&self, ctx: &Context<'_>) -> Result<Vec<Label<T>>> {
+        let labels = ctx.data_unchecked::<Arc<L>>().all().await?;
+        Ok(labels.into_iter().map(Label::from).collect())
+    }
+}
+
+pub struct MutationRoot<T, L>(PhantomData<(T, L)>);
+
+#[Object]
+impl<T: TodoRepository, L: LabelRepository> MutationRoot<T, L> {
+    async fn create_todo(&self, ctx: &Context<'_>, input: CreateTodoInput) -> Result<Todo<T>> {
+        let payload = todo::CreateTodo::from(input);
+        validate(&payload)?;
+        let todo = ctx.data_unchecked::<Arc<T>>().create(payload).await?;
+        Ok(todo.into())
+    }
+
+    async fn update_todo(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        input: UpdateTodoInput,
+    ) -> Result<Todo<T>> {
+        let payload = todo::UpdateTodo::from(input);
+        validate(&payload)?;
+        let repository = ctx.data_unchecked::<Arc<T>>();
+        let todo = update_and_spawn_next(repository.as_ref(), id, payload).await?;
+        Ok(todo.into())
+    }
+
+    async fn delete_todo(&self, ctx: &Context<'_>, id: i32) -> Result<bool> {
+        ctx.data_unchecked::<Arc<T>>().delete(id).await?;
+        Ok(true)
+    }
+
+    async fn create_label(&self, ctx: &Context<'_>, name: String) -> Result<Label<T>> {
+        let payload = label::CreateLabel { name };
+        validate(&payload)?;
+        let label = ctx.data_unchecked::<Arc<L>>().create(payload.name).await?;
+        Ok(label.into())
+    }
+
+    async fn delete_label(&self, ctx: &Context<'_>, id: i32) -> Result<bool> {
+        ctx.data_unchecked::<Arc<L>>().delete(id).await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::create_app;
+    use crate::repositories::{
+        label::test_utils::LabelRepositoryForMemory,
+        todo::{test_utils::TodoRepositoryForMemory, TodoEntity, TodoFilter, TodoSearchResult},
+    };
+    use axum::{
+        async_trait,
+        body::Body,
+        http::{header, Method, Request},
+        Router,
+    };
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use todo_core::v1::todo::{CreateTodo, UpdateTodo};
+    use tower::ServiceExt;
+
+    async fn execute(app: Router, query: &str) -> Value {
+        let req = Request::builder()
+            .uri("/graphql")
+            .method(Method::POST)
+            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(json!({ "query": query }).to_string()))
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    async fn seed() -> (TodoRepositoryForMemory, LabelRepositoryForMemory) {
+        let label_repository = LabelRepositoryForMemory::new();
+        let work = label_repository.create("work".to_string()).await.unwrap();
+        let home = label_repository.create("home".to_string()).await.unwrap();
+        let todo_repository = TodoRepositoryForMemory::new(vec![work.clone(), home.clone()]);
+        for (text, labels) in [
+            ("write report", vec![work.id]),
+            ("clean room", vec![home.id]),
+            ("pay bills", vec![work.id, home.id]),
+        ] {
+            todo_repository
+                .create(CreateTodo::new(text.to_string(), labels))
+                .await
+                .unwrap();
+        }
+        (todo_repository, label_repository)
+    }
+
+    #[tokio::test]
+    async fn should_query_todos_with_labels() {
+        let (todo_repository, label_repository) = seed().await;
+        let res = execute(
+            create_app(todo_repository, label_repository),
+            "{ todos(label: 2) { id text labels { name } } todo(id: 99) { id } }",
+        )
+        .await;
+        let mut todos = res["data"]["todos"].as_array().unwrap().clone();
+        todos.sort_by_key(|todo| todo["id"].as_i64());
+        assert_eq!(
+            todos,
+            vec![
+                json!({ "id": 2, "text": "clean room", "labels": [{ "name": "home" }] }),
+                json!({
+                    "id": 3,
+                    "text": "pay bills",
+                    "labels": [{ "name": "work" }, { "name": "home" }]
+                }),
+            ]
+        );
+        assert_eq!(res["data"]["todo"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn should_mutate_todos_and_labels() {
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        let app = create_app(todo_repository.clone(), label_repository.clone());
+
+        let res = execute(
+            app.clone(),
+            r#"mutation { createLabel(name: "work") { id name } }"#,
+        )
+        .await;
+        assert_eq!(
+            res["data"]["createLabel"],
+            json!({ "id": 1, "name": "work" })
+        );
+
+        let res = execute(
+            app.clone(),
+            r#"mutation {
+                createTodo(input: {
+                    text: "weekly report",
+                    dueDate: "2023-01-02",
+                    recurrence: { frequency: WEEKLY }
+                }) { id occurrences(count: 2) }
+            }"#,
+        )
+        .await;
+        assert_eq!(
+            res["data"]["createTodo"],
+            json!({ "id": 1, "occurrences": ["2023-01-09", "2023-01-16"] })
+        );
+
+        // 完了にすると次の回が作られる
+        let res = execute(
+            app.clone(),
+            "mutation { updateTodo(id: 1, input: { completed: true }) { completed } }",
+        )
+        .await;
+        assert_eq!(res["data"]["updateTodo"], json!({ "completed": true }));
+        let next = todo_repository.find(2).await.expect("next not spawned");
+        assert_eq!(next.due_date, NaiveDate::from_ymd_opt(2023, 1, 9));
+
+        // 次の回は最初の期日を起点に持ち、送り返しても失われない
+        let res = execute(
+            app.clone(),
+            "{ todo(id: 2) { recurrence { frequency anchor } } }",
+        )
+        .await;
+        assert_eq!(
+            res["data"]["todo"]["recurrence"],
+            json!({ "frequency": "WEEKLY", "anchor": "2023-01-02" })
+        );
+        let res = execute(
+            app.clone(),
+            r#"mutation {
+                updateTodo(id: 2, input: {
+                    recurrence: { frequency: WEEKLY, anchor: "2023-01-02" }
+                }) { recurrence { anchor } }
+            }"#,
+        )
+        .await;
+        assert_eq!(
+            res["data"]["updateTodo"]["recurrence"],
+            json!({ "anchor": "2023-01-02" })
+        );
+        let next = todo_repository.find(2).await.expect("next not found");
+        assert_eq!(
+            next.recurrence.and_then(|recurrence| recurrence.anchor),
+            NaiveDate::from_ymd_opt(2023, 1, 2)
+        );
+
+        let res = execute(
+            app.clone(),
+            "mutation { deleteTodo(id: 1) deleteLabel(id: 1) }",
+        )
+        .await;
+        assert_eq!(
+            res["data"],
+            json!({ "deleteTodo": true, "deleteLabel": true })
+        );
+
+        // REST と同じ検証ルール
+        let res = execute(
+            app,
+            r#"mutation { createTodo(input: { text: "" }) { id } }"#,
+        )
+        .await;
+        assert!(res["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Validation error"));
+    }
+
+    // all() の呼び出し回数を数えるリポジトリ
+    #[derive(Clone)]
+    struct CountingTodoRepository {
+        inner: TodoRepositoryForMemory,
+        all_calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl TodoRepository for CountingTodoRepository {
+        async fn create(&self, payload: CreateTodo) -> anyhow::Result<TodoEntity> {
+            self.inner.create(payload).await
+        }
+        async fn find(&self, id: i32) -> anyhow::Result<TodoEntity> {
+            self.inner.find(id).await
+        }
+        async fn all(&self) -> anyhow::Result<Vec<TodoEntity>> {
+            self.all_calls.fetch_add(1, Ordering::SeqCst);
+            self.inner.all().await
+        }
+        async fn search(
+            &self,
+            q: &str,
+            filter: &TodoFilter,
+        ) -> anyhow::Result<Vec<TodoSearchResult>> {
+            self.inner.search(q, filter).await
+        }
+        async fn update(&self, id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity> {
+            self.inner.update(id, payload).await
+        }
+        async fn delete(&self, id: i32) -> anyhow::Result<()> {
+            self.inner.delete(id).await
+        }
+        async fn spawn_next(
+            &self,
+            id: i32,
+            payload: CreateTodo,
+        ) -> anyhow::Result<Option<TodoEntity>> {
+            self.inner.spawn_next(id, payload).await
+        }
+    }
+
+    #[tokio::test]
+    async fn should_batch_todos_of_labels() {
+        let (todo_repository, label_repository) = seed().await;
+        let all_calls = Arc::new(AtomicUsize::new(0));
+        let todo_repository = CountingTodoRepository {
+            inner: todo_repository,
+            all_calls: all_calls.clone(),
+        };
+        let res = execute(
+            create_app(todo_repository, label_repository),
+            "{ labels { name todos { id } } }",
+        )
+        .await;
+        let labels = res["data"]["labels"].as_array().unwrap();
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels[0]["todos"].as_array().unwrap().len(), 2);
+        assert_eq!(labels[1]["todos"].as_array().unwrap().len(), 2);
+        assert_eq!(all_calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -7,6 +7,7 @@ use axum::{
 use serde::de::DeserializeOwned;
 use validator::Validate;
 
+pub mod graphql;
 pub mod label;
 pub mod todo;
 
@@ -0,0 +1,20 @@
+use crate::{
+    graphql::{todos_by_label_loader, TodoSchema},
+    repositories::{label::LabelRepository, todo::TodoRepository},
+};
+use async_graphql::http::GraphiQLSource;
+use axum::{extract::Extension, response::Html, Json};
+use std::sync::Arc;
+
+pub async fn graphql<T: TodoRepository, L: LabelRepository>(
+    Json(request): Json<async_graphql::Request>,
+    Extension(schema): Extension<TodoSchema<T, L>>,
+    Extension(repository): Extension<Arc<T>>,
+) -> Json<async_graphql::Response> {
+    let request = request.data(todos_by_label_loader(repository));
+    Json(schema.execute(request).await)
+}
+
+pub async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
@@ -4,11 +4,10 @@ use axum::{
     response::IntoResponse,
     Json,
 };
-use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use validator::Validate;
 
 use crate::repositories::label::LabelRepository;
+use todo_core::v1::label::CreateLabel;
 
 use super::ValidatedJson;
 
@@ -40,10 +39,3 @@ pub async fn delete_label<T: LabelRepository>(
         .map(|_| StatusCode::NO_CONTENT) // Return OK
         .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR) // ERR: Return 500
 }
-
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Validate)]
-pub struct CreateLabel {
-    #[validate(length(min = 1, message = "Can not be empty"))]
-    #[validate(length(max = 100, message = "Over text length"))]
-    name: String,
-}
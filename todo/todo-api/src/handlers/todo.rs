@@ -1,12 +1,17 @@
 use super::ValidatedJson;
-use crate::repositories::todo::{CreateTodo, TodoRepository, UpdateTodo};
+use crate::repositories::{
+    todo::{update_and_spawn_next, CreateTodo, TodoFilter, TodoRepository, UpdateTodo},
+    RepositoryError,
+};
 use axum::{
-    extract::{Extension, Path},
+    extract::{Extension, Path, Query},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
+use serde::Deserialize;
 use std::sync::Arc;
+use validator::Validate;
 
 pub async fn create_todo<T: TodoRepository>(
     ValidatedJson(payload): ValidatedJson<CreateTodo>, // request は deserialize
@@ -31,13 +36,31 @@ pub async fn find_todo<T: TodoRepository>(
 }
 
 pub async fn all_todo<T: TodoRepository>(
+    Query(filter): Query<TodoFilter>,
     Extension(repository): Extension<Arc<T>>,
 ) -> Result<impl IntoResponse, StatusCode> {
     //todo!();
     let todo = repository.all().await.unwrap();
+    let todo: Vec<_> = todo
+        .into_iter()
+        .filter(|todo| filter.matches(todo))
+        .collect();
     Ok((StatusCode::OK, Json(todo)))
 }
 
+pub async fn search_todo<T: TodoRepository>(
+    Query(query): Query<SearchQuery>,
+    Query(filter): Query<TodoFilter>,
+    Extension(repository): Extension<Arc<T>>,
+) -> Result<impl IntoResponse, StatusCode> {
+    query.validate().or(Err(StatusCode::BAD_REQUEST))?;
+    let results = repository
+        .search(&query.q, &filter)
+        .await
+        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+    Ok((StatusCode::OK, Json(results)))
+}
+
 pub async fn update_todo<T: TodoRepository>(
     Path(id): Path<i32>,
     ValidatedJson(payload): ValidatedJson<UpdateTodo>,
@@ -46,13 +69,29 @@ pub async fn update_todo<T: TodoRepository>(
     //todo!();
     // コンパイルエラーを通すため暫定でOKを返す
     //Ok(StatusCode::OK)
-    let todo = repository
-        .update(id, payload)
+    let todo = update_and_spawn_next(repository.as_ref(), id, payload)
         .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+        .map_err(|e| match e.downcast_ref::<RepositoryError>() {
+            Some(RepositoryError::MissingDueDate(_)) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::NOT_FOUND,
+        })?;
     Ok((StatusCode::OK, Json(todo)))
 }
 
+pub async fn preview_occurrences<T: TodoRepository>(
+    Path(id): Path<i32>,
+    Query(query): Query<OccurrencesQuery>,
+    Extension(repository): Extension<Arc<T>>,
+) -> Result<impl IntoResponse, StatusCode> {
+    query.validate().or(Err(StatusCode::BAD_REQUEST))?;
+    let todo = repository.find(id).await.or(Err(StatusCode::NOT_FOUND))?;
+    let occurrences = match (todo.due_date, todo.recurrence) {
+        (Some(due_date), Some(recurrence)) => recurrence.occurrences(due_date, query.count),
+        _ => vec![],
+    };
+    Ok((StatusCode::OK, Json(occurrences)))
+}
+
 pub async fn delete_todo<T: TodoRepository>(
     Path(id): Path<i32>,
     Extension(repository): Extension<Arc<T>>,
@@ -64,3 +103,21 @@ pub async fn delete_todo<T: TodoRepository>(
         .map(|_| StatusCode::NO_CONTENT) // Return OK
         .unwrap_or(StatusCode::NOT_FOUND) // ERR: Return 404
 }
+
+fn default_occurrences_count() -> usize {
+    5
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct OccurrencesQuery {
+    #[serde(default = "default_occurrences_count")]
+    #[validate(range(min = 1, max = 100, message = "Out of range"))]
+    count: usize,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SearchQuery {
+    #[validate(length(min = 1, message = "Can not be empty"))]
+    #[validate(length(max = 100, message = "Over text length"))]
+    q: String,
+}
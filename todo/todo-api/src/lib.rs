@@ -0,0 +1,526 @@
+pub mod graphql;
+pub mod handlers;
+pub mod repositories;
+
+use crate::repositories::todo::TodoRepository;
+use axum::{
+    extract::Extension,
+    routing::{delete, get, post},
+    Router,
+};
+use graphql::build_schema;
+use handlers::{
+    graphql::{graphiql, graphql},
+    label::{all_label, create_label, delete_label},
+    todo::{
+        all_todo, create_todo, delete_todo, find_todo, preview_occurrences, search_todo,
+        update_todo,
+    },
+};
+use hyper::header::CONTENT_TYPE;
+use repositories::label::LabelRepository;
+use std::sync::Arc;
+use tower_http::cors::{Any, CorsLayer, Origin};
+
+pub fn create_app<Todo: TodoRepository, Label: LabelRepository>(
+    todo_repository: Todo,
+    label_repository: Label,
+) -> Router {
+    let todo_repository = Arc::new(todo_repository);
+    let label_repository = Arc::new(label_repository);
+    let schema = build_schema(todo_repository.clone(), label_repository.clone());
+    Router::new()
+        .route("/", get(root))
+        // axum は同一パスをメソッドチェーンで記述
+        .route("/todos", post(create_todo::<Todo>).get(all_todo::<Todo>))
+        .route("/todos/search", get(search_todo::<Todo>))
+        .route(
+            "/todos/:id",
+            get(find_todo::<Todo>)
+                .delete(delete_todo::<Todo>)
+                .patch(update_todo::<Todo>),
+        )
+        .route("/todos/:id/occurrences", get(preview_occurrences::<Todo>))
+        .route(
+            "/labels",
+            post(create_label::<Label>).get(all_label::<Label>),
+        )
+        .route("/labels/:id", delete(delete_label::<Label>)) // なんか delete はメソッドチェーンしないとエラーになるのでとりあえずコメントアウト
+        .route("/graphql", post(graphql::<Todo, Label>).get(graphiql))
+        .layer(Extension(todo_repository))
+        .layer(Extension(label_repository))
+        .layer(Extension(schema))
+        .layer(
+            CorsLayer::new()
+                .allow_origin(Origin::exact("http://localhost:3001".parse().unwrap()))
+                .allow_methods(Any)
+                .allow_headers(vec![CONTENT_TYPE]),
+        )
+}
+
+async fn root() -> &'static str {
+    "Hello, world!"
+}
+
+// 以下プロダクションコードからは削除される（cfg）
+#[cfg(test)]
+#[allow(clippy::expect_fun_call)]
+mod test {
+    use super::*;
+    use crate::repositories::label::{test_utils::LabelRepositoryForMemory, Label};
+    use crate::repositories::todo::{
+        test_utils::TodoRepositoryForMemory, CreateTodo, TodoEntity, TodoSearchResult,
+    };
+    use axum::response::Response;
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+    };
+    use chrono::NaiveDate;
+    use todo_core::v1::recurrence::{Frequency, Recurrence};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn should_return_hello_world() {
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        let req = Request::builder().uri("/").body(Body::empty()).unwrap();
+        let res = create_app(todo_repository, label_repository)
+            .oneshot(req)
+            .await
+            .unwrap();
+        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body: String = String::from_utf8(bytes.to_vec()).unwrap();
+        assert_eq!(body, "Hello, world!");
+    }
+
+    fn build_todo_req_with_json(path: &str, method: Method, json_body: String) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(json_body))
+            .unwrap()
+    }
+
+    fn build_todo_req_with_empty(method: Method, path: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn res_to_todo(res: Response) -> TodoEntity {
+        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body: String = String::from_utf8(bytes.to_vec()).unwrap();
+        let todo: TodoEntity = serde_json::from_str(&body)
+            .expect(&format!("cannot convert Todo instance. body:{}", body));
+        todo
+    }
+
+    #[tokio::test]
+    async fn should_create_todo() {
+        let expected = TodoEntity::new(1, "shoud_return_created_todo".to_string(), vec![]);
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        let req = build_todo_req_with_json(
+            "/todos",
+            Method::POST,
+            r#"{"text":"shoud_return_created_todo", "labels": []}"#.to_string(),
+        );
+        let res = create_app(todo_repository, label_repository)
+            .oneshot(req)
+            .await
+            .unwrap();
+        let todo = res_to_todo(res).await;
+        assert_eq!(expected, todo);
+    }
+
+    #[tokio::test]
+    async fn should_find_todo() {
+        let expected = TodoEntity::new(1, "should_find_todo".to_string(), vec![]);
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        todo_repository
+            .create(CreateTodo::new("should_find_todo".to_string(), vec![]))
+            .await
+            .expect("failed create todo");
+        let req = build_todo_req_with_empty(Method::GET, "/todos/1");
+        let res = create_app(todo_repository, label_repository)
+            .oneshot(req)
+            .await
+            .unwrap();
+        let todo = res_to_todo(res).await;
+        assert_eq!(expected, todo);
+    }
+
+    #[tokio::test]
+    async fn shoud_get_all_todos() {
+        let expected = TodoEntity::new(1, "should_get_all_todos".to_string(), vec![]);
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        todo_repository
+            .create(CreateTodo::new("should_get_all_todos".to_string(), vec![]))
+            .await
+            .expect("failed create todo");
+        let req = build_todo_req_with_empty(Method::GET, "/todos");
+        let res = create_app(todo_repository, label_repository)
+            .oneshot(req)
+            .await
+            .unwrap();
+        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body: String = String::from_utf8(bytes.to_vec()).unwrap();
+        let todo: Vec<TodoEntity> = serde_json::from_str(&body)
+            .expect(&format!("cannot convert Todo instance. body:{}", body));
+        assert_eq!(vec![expected], todo);
+    }
+
+    #[tokio::test]
+    async fn should_update_todo() {
+        let expected = TodoEntity::new(1, "should_update_todo".to_string(), vec![]);
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        todo_repository
+            .create(CreateTodo::new("before_update_todo".to_string(), vec![]))
+            .await
+            .expect("failed create todo");
+        let req = build_todo_req_with_json(
+            "/todos/1",
+            Method::PATCH,
+            r#"{"id":1, "text":"should_update_todo", "completed":false}"#.to_string(),
+        );
+        let res = create_app(todo_repository, label_repository)
+            .oneshot(req)
+            .await
+            .unwrap();
+        let todo = res_to_todo(res).await;
+        assert_eq!(expected, todo);
+    }
+
+    #[tokio::test]
+    async fn should_delete_todo() {
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        todo_repository
+            .create(CreateTodo::new("should_delete_todo".to_string(), vec![]))
+            .await
+            .expect("failed create todo");
+        let req = build_todo_req_with_empty(Method::DELETE, "/todos/1");
+        let res = create_app(todo_repository, label_repository)
+            .oneshot(req)
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_not_delete_todo() {
+        // id が違ったら 404で削除されない
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        todo_repository
+            .create(CreateTodo::new("should_delete_todo".to_string(), vec![]))
+            .await
+            .expect("failed create todo");
+        let req = build_todo_req_with_empty(Method::DELETE, "/todos/2");
+        let res = create_app(todo_repository, label_repository)
+            .oneshot(req)
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+
+    fn weekly_until(until: Option<NaiveDate>) -> Recurrence {
+        Recurrence {
+            frequency: Frequency::Weekly,
+            interval: 1,
+            until,
+            count: None,
+            anchor: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_spawn_next_occurrence_on_complete() {
+        let label = Label {
+            id: 1,
+            name: String::from("chore"),
+        };
+        let todo_repository = TodoRepositoryForMemory::new(vec![label.clone()]);
+        let label_repository = LabelRepositoryForMemory::new();
+        let due_date = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+        todo_repository
+            .create(
+                CreateTodo::new("weekly report".to_string(), vec![label.id])
+                    .with_recurrence(due_date, weekly_until(None)),
+            )
+            .await
+            .expect("failed create todo");
+        let req = build_todo_req_with_json(
+            "/todos/1",
+            Method::PATCH,
+            r#"{"completed":true}"#.to_string(),
+        );
+        let res = create_app(todo_repository.clone(), label_repository)
+            .oneshot(req)
+            .await
+            .unwrap();
+        let todo = res_to_todo(res).await;
+        assert!(todo.completed);
+
+        let next = todo_repository.find(2).await.expect("next not spawned");
+        assert!(!next.completed);
+        assert_eq!(next.text, "weekly report");
+        assert_eq!(next.due_date, NaiveDate::from_ymd_opt(2023, 1, 9));
+        assert_eq!(next.labels, vec![label]);
+    }
+
+    #[tokio::test]
+    async fn should_not_spawn_after_until() {
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        let due_date = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+        todo_repository
+            .create(
+                CreateTodo::new("last report".to_string(), vec![])
+                    .with_recurrence(due_date, weekly_until(Some(due_date))),
+            )
+            .await
+            .expect("failed create todo");
+        let req = build_todo_req_with_json(
+            "/todos/1",
+            Method::PATCH,
+            r#"{"completed":true}"#.to_string(),
+        );
+        let res = create_app(todo_repository.clone(), label_repository)
+            .oneshot(req)
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        assert!(todo_repository.find(2).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_spawn_next_occurrence_once() {
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        let due_date = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+        todo_repository
+            .create(
+                CreateTodo::new("weekly report".to_string(), vec![])
+                    .with_recurrence(due_date, weekly_until(None)),
+            )
+            .await
+            .expect("failed create todo");
+        // 完了 -> 取り消し -> 再び完了
+        for completed in [true, false, true] {
+            let req = build_todo_req_with_json(
+                "/todos/1",
+                Method::PATCH,
+                format!(r#"{{"completed":{}}}"#, completed),
+            );
+            let res = create_app(todo_repository.clone(), label_repository.clone())
+                .oneshot(req)
+                .await
+                .unwrap();
+            assert_eq!(StatusCode::OK, res.status());
+        }
+        assert_eq!(todo_repository.all().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_reject_update_recurrence_without_due_date() {
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        todo_repository
+            .create(CreateTodo::new("no due date".to_string(), vec![]))
+            .await
+            .expect("failed create todo");
+        let req = build_todo_req_with_json(
+            "/todos/1",
+            Method::PATCH,
+            r#"{"recurrence": {"frequency": "daily"}}"#.to_string(),
+        );
+        let res = create_app(todo_repository.clone(), label_repository)
+            .oneshot(req)
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        assert_eq!(todo_repository.find(1).await.unwrap().recurrence, None);
+    }
+
+    #[tokio::test]
+    async fn should_preview_occurrences() {
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        let due_date = NaiveDate::from_ymd_opt(2023, 1, 31).unwrap();
+        todo_repository
+            .create(
+                CreateTodo::new("monthly invoice".to_string(), vec![]).with_recurrence(
+                    due_date,
+                    Recurrence {
+                        frequency: Frequency::Monthly,
+                        interval: 1,
+                        until: None,
+                        count: None,
+                        anchor: None,
+                    },
+                ),
+            )
+            .await
+            .expect("failed create todo");
+        let req = build_todo_req_with_empty(Method::GET, "/todos/1/occurrences?count=2");
+        let res = create_app(todo_repository, label_repository)
+            .oneshot(req)
+            .await
+            .unwrap();
+        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let dates: Vec<NaiveDate> = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(
+            dates,
+            vec![
+                NaiveDate::from_ymd_opt(2023, 2, 28).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 3, 31).unwrap(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_recurrence_without_due_date() {
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let label_repository = LabelRepositoryForMemory::new();
+        let req = build_todo_req_with_json(
+            "/todos",
+            Method::POST,
+            r#"{"text":"no due date", "labels": [], "recurrence": {"frequency": "daily"}}"#
+                .to_string(),
+        );
+        let res = create_app(todo_repository, label_repository)
+            .oneshot(req)
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+    }
+
+    async fn seed_search_todos() -> TodoRepositoryForMemory {
+        let todo_repository = TodoRepositoryForMemory::new(vec![Label {
+            id: 1,
+            name: String::from("report"),
+        }]);
+        for (text, labels) in [
+            ("write weekly report", vec![]),
+            ("send invoice", vec![1]),
+            ("buy milk", vec![]),
+        ] {
+            todo_repository
+                .create(CreateTodo::new(text.to_string(), labels))
+                .await
+                .expect("failed create todo");
+        }
+        todo_repository
+    }
+
+    async fn res_to_search_results(res: Response) -> Vec<TodoSearchResult> {
+        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        serde_json::from_slice(&bytes).expect("cannot convert search results")
+    }
+
+    #[tokio::test]
+    async fn should_search_todos_by_text_and_label() {
+        let todo_repository = seed_search_todos().await;
+        let req = build_todo_req_with_empty(Method::GET, "/todos/search?q=report");
+        let res = create_app(todo_repository, LabelRepositoryForMemory::new())
+            .oneshot(req)
+            .await
+            .unwrap();
+        let results = res_to_search_results(res).await;
+        let ids: Vec<i32> = results.iter().map(|result| result.todo.id).collect();
+        // text の一致がラベル名の一致より上位
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(results[0].snippet, "write weekly <mark>report</mark>");
+        assert_eq!(results[1].snippet, "send invoice");
+    }
+
+    #[tokio::test]
+    async fn should_require_all_search_terms() {
+        let todo_repository = seed_search_todos().await;
+        for (q, expected) in [
+            ("weekly+report", vec![1]),
+            ("report+invoice", vec![2]),
+            ("weekly+milk", vec![]),
+        ] {
+            let req = build_todo_req_with_empty(Method::GET, &format!("/todos/search?q={}", q));
+            let res = create_app(todo_repository.clone(), LabelRepositoryForMemory::new())
+                .oneshot(req)
+                .await
+                .unwrap();
+            let results = res_to_search_results(res).await;
+            let ids: Vec<i32> = results.iter().map(|result| result.todo.id).collect();
+            assert_eq!(ids, expected, "q={}", q);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_escape_search_snippet() {
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        todo_repository
+            .create(CreateTodo::new(
+                "<img src=x onerror=alert(1)> & report".to_string(),
+                vec![],
+            ))
+            .await
+            .expect("failed create todo");
+        let req = build_todo_req_with_empty(Method::GET, "/todos/search?q=report");
+        let res = create_app(todo_repository, LabelRepositoryForMemory::new())
+            .oneshot(req)
+            .await
+            .unwrap();
+        let results = res_to_search_results(res).await;
+        assert_eq!(
+            results[0].snippet,
+            "&lt;img src=x onerror=alert(1)&gt; &amp; <mark>report</mark>"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_filter_search_results() {
+        let todo_repository = seed_search_todos().await;
+        let req = build_todo_req_with_empty(Method::GET, "/todos/search?q=report&label=1");
+        let res = create_app(todo_repository.clone(), LabelRepositoryForMemory::new())
+            .oneshot(req)
+            .await
+            .unwrap();
+        let results = res_to_search_results(res).await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].todo.id, 2);
+
+        let req = build_todo_req_with_empty(Method::GET, "/todos/search?q=report&completed=true");
+        let res = create_app(todo_repository.clone(), LabelRepositoryForMemory::new())
+            .oneshot(req)
+            .await
+            .unwrap();
+        assert!(res_to_search_results(res).await.is_empty());
+
+        let req = build_todo_req_with_empty(Method::GET, "/todos/search?q=");
+        let res = create_app(todo_repository, LabelRepositoryForMemory::new())
+            .oneshot(req)
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_filter_all_todos() {
+        let todo_repository = seed_search_todos().await;
+        let req = build_todo_req_with_empty(Method::GET, "/todos?label=1&completed=false");
+        let res = create_app(todo_repository, LabelRepositoryForMemory::new())
+            .oneshot(req)
+            .await
+            .unwrap();
+        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let todos: Vec<TodoEntity> = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].text, "send invoice");
+    }
+}
@@ -1,28 +1,14 @@
-mod handlers;
-mod repositories;
-
-use crate::repositories::{
-    label::LabelRepositoryForDB,
-    todo::{TodoRepository, TodoRepositoryForDB},
-};
-use axum::{
-    extract::Extension,
-    routing::{delete, get, post},
-    Router,
-};
 use dotenv::dotenv;
-use handlers::{
-    label::{all_label, create_label, delete_label},
-    todo::{all_todo, create_todo, delete_todo, find_todo, update_todo},
-};
-use hyper::header::CONTENT_TYPE;
-use repositories::label::LabelRepository;
 use sqlx::PgPool;
+use std::env;
 use std::net::SocketAddr;
-use std::{env, sync::Arc};
-use tower_http::cors::{Any, CorsLayer, Origin};
+use todo::{
+    create_app,
+    repositories::{label::LabelRepositoryForDB, todo::TodoRepositoryForDB},
+};
 
 #[tokio::main]
+#[allow(clippy::expect_fun_call)]
 async fn main() {
     // logging
     let log_level = env::var("RUST_LOG").unwrap_or("info".to_string());
@@ -47,201 +33,3 @@ async fn main() {
         .await
         .unwrap();
 }
-
-fn create_app<Todo: TodoRepository, Label: LabelRepository>(
-    todo_repository: Todo,
-    label_repository: Label,
-) -> Router {
-    Router::new()
-        .route("/", get(root))
-        // axum は同一パスをメソッドチェーンで記述
-        .route("/todos", post(create_todo::<Todo>).get(all_todo::<Todo>))
-        .route(
-            "/todos/:id",
-            get(find_todo::<Todo>)
-                .delete(delete_todo::<Todo>)
-                .patch(update_todo::<Todo>),
-        )
-        .route(
-            "/labels",
-            post(create_label::<Label>).get(all_label::<Label>),
-        )
-        .route("/labels/:id", delete(delete_label::<Label>)) // なんか delete はメソッドチェーンしないとエラーになるのでとりあえずコメントアウト
-        .layer(Extension(Arc::new(todo_repository)))
-        .layer(Extension(Arc::new(label_repository)))
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Origin::exact("http://localhost:3001".parse().unwrap()))
-                .allow_methods(Any)
-                .allow_headers(vec![CONTENT_TYPE]),
-        )
-}
-
-async fn root() -> &'static str {
-    "Hello, world!"
-}
-
-// 以下プロダクションコードからは削除される（cfg）
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::repositories::label::test_utils::LabelRepositoryForMemory;
-    use crate::repositories::todo::{test_utils::TodoRepositoryForMemory, CreateTodo, TodoEntity};
-    use axum::response::Response;
-    use axum::{
-        body::Body,
-        http::{header, Method, Request, StatusCode},
-    };
-    use tower::ServiceExt;
-
-    #[tokio::test]
-    async fn should_return_hello_world() {
-        let todo_repository = TodoRepositoryForMemory::new(vec![]);
-        let label_repository = LabelRepositoryForMemory::new();
-        let req = Request::builder().uri("/").body(Body::empty()).unwrap();
-        let res = create_app(todo_repository, label_repository)
-            .oneshot(req)
-            .await
-            .unwrap();
-        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
-        let body: String = String::from_utf8(bytes.to_vec()).unwrap();
-        assert_eq!(body, "Hello, world!");
-    }
-
-    fn build_todo_req_with_json(path: &str, method: Method, json_body: String) -> Request<Body> {
-        Request::builder()
-            .uri(path)
-            .method(method)
-            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
-            .body(Body::from(json_body))
-            .unwrap()
-    }
-
-    fn build_todo_req_with_empty(method: Method, path: &str) -> Request<Body> {
-        Request::builder()
-            .uri(path)
-            .method(method)
-            .body(Body::empty())
-            .unwrap()
-    }
-
-    async fn res_to_todo(res: Response) -> TodoEntity {
-        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
-        let body: String = String::from_utf8(bytes.to_vec()).unwrap();
-        let todo: TodoEntity = serde_json::from_str(&body)
-            .expect(&format!("cannot convert Todo instance. body:{}", body));
-        todo
-    }
-
-    #[tokio::test]
-    async fn should_create_todo() {
-        let expected = TodoEntity::new(1, "shoud_return_created_todo".to_string(), vec![]);
-        let todo_repository = TodoRepositoryForMemory::new(vec![]);
-        let label_repository = LabelRepositoryForMemory::new();
-        let req = build_todo_req_with_json(
-            "/todos",
-            Method::POST,
-            r#"{"text":"shoud_return_created_todo", "labels": []}"#.to_string(),
-        );
-        let res = create_app(todo_repository, label_repository)
-            .oneshot(req)
-            .await
-            .unwrap();
-        let todo = res_to_todo(res).await;
-        assert_eq!(expected, todo);
-    }
-
-    #[tokio::test]
-    async fn should_find_todo() {
-        let expected = TodoEntity::new(1, "should_find_todo".to_string(), vec![]);
-        let todo_repository = TodoRepositoryForMemory::new(vec![]);
-        let label_repository = LabelRepositoryForMemory::new();
-        todo_repository
-            .create(CreateTodo::new("should_find_todo".to_string(), vec![]))
-            .await
-            .expect("failed create todo");
-        let req = build_todo_req_with_empty(Method::GET, "/todos/1");
-        let res = create_app(todo_repository, label_repository)
-            .oneshot(req)
-            .await
-            .unwrap();
-        let todo = res_to_todo(res).await;
-        assert_eq!(expected, todo);
-    }
-
-    #[tokio::test]
-    async fn shoud_get_all_todos() {
-        let expected = TodoEntity::new(1, "should_get_all_todos".to_string(), vec![]);
-        let todo_repository = TodoRepositoryForMemory::new(vec![]);
-        let label_repository = LabelRepositoryForMemory::new();
-        todo_repository
-            .create(CreateTodo::new("should_get_all_todos".to_string(), vec![]))
-            .await
-            .expect("failed create todo");
-        let req = build_todo_req_with_empty(Method::GET, "/todos");
-        let res = create_app(todo_repository, label_repository)
-            .oneshot(req)
-            .await
-            .unwrap();
-        let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
-        let body: String = String::from_utf8(bytes.to_vec()).unwrap();
-        let todo: Vec<TodoEntity> = serde_json::from_str(&body)
-            .expect(&format!("cannot convert Todo instance. body:{}", body));
-        assert_eq!(vec![expected], todo);
-    }
-
-    #[tokio::test]
-    async fn should_update_todo() {
-        let expected = TodoEntity::new(1, "should_update_todo".to_string(), vec![]);
-        let todo_repository = TodoRepositoryForMemory::new(vec![]);
-        let label_repository = LabelRepositoryForMemory::new();
-        todo_repository
-            .create(CreateTodo::new("before_update_todo".to_string(), vec![]))
-            .await
-            .expect("failed create todo");
-        let req = build_todo_req_with_json(
-            "/todos/1",
-            Method::PATCH,
-            r#"{"id":1, "text":"should_update_todo", "completed":false}"#.to_string(),
-        );
-        let res = create_app(todo_repository, label_repository)
-            .oneshot(req)
-            .await
-            .unwrap();
-        let todo = res_to_todo(res).await;
-        assert_eq!(expected, todo);
-    }
-
-    #[tokio::test]
-    async fn should_delete_todo() {
-        let todo_repository = TodoRepositoryForMemory::new(vec![]);
-        let label_repository = LabelRepositoryForMemory::new();
-        todo_repository
-            .create(CreateTodo::new("should_delete_todo".to_string(), vec![]))
-            .await
-            .expect("failed create todo");
-        let req = build_todo_req_with_empty(Method::DELETE, "/todos/1");
-        let res = create_app(todo_repository, label_repository)
-            .oneshot(req)
-            .await
-            .unwrap();
-        assert_eq!(StatusCode::NO_CONTENT, res.status());
-    }
-
-    #[tokio::test]
-    async fn should_not_delete_todo() {
-        // id が違ったら 404で削除されない
-        let todo_repository = TodoRepositoryForMemory::new(vec![]);
-        let label_repository = LabelRepositoryForMemory::new();
-        todo_repository
-            .create(CreateTodo::new("should_delete_todo".to_string(), vec![]))
-            .await
-            .expect("failed create todo");
-        let req = build_todo_req_with_empty(Method::DELETE, "/todos/2");
-        let res = create_app(todo_repository, label_repository)
-            .oneshot(req)
-            .await
-            .unwrap();
-        assert_eq!(StatusCode::NOT_FOUND, res.status());
-    }
-}
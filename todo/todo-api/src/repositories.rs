@@ -4,11 +4,13 @@ pub mod todo;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
-enum RepositoryError {
+pub(crate) enum RepositoryError {
     #[error("Unexpected Error: {0}")]
     Unexpected(String),
     #[error("NotFound, id is {0}")]
     NotFound(i32),
     #[error("Duplicate data, id is {0}")]
     Duplicate(i32),
+    #[error("Recurrence requires due_date, id is {0}")]
+    MissingDueDate(i32),
 }
@@ -1,6 +1,5 @@
 use super::RepositoryError;
 use axum::async_trait;
-use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 
 #[async_trait]
@@ -10,18 +9,23 @@ pub trait LabelRepository: Clone + std::marker::Send + std::marker::Sync + 'stat
     async fn delete(&self, id: i32) -> anyhow::Result<()>;
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, sqlx::FromRow)]
-pub struct Label {
-    pub id: i32,
-    pub name: String,
-}
+pub use todo_core::v1::label::Label;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
-pub struct UpdateLabel {
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct LabelFromRow {
     id: i32,
     name: String,
 }
 
+impl From<LabelFromRow> for Label {
+    fn from(row: LabelFromRow) -> Self {
+        Label {
+            id: row.id,
+            name: row.name,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LabelRepositoryForDB {
     pool: PgPool,
@@ -36,7 +40,7 @@ impl LabelRepositoryForDB {
 #[async_trait]
 impl LabelRepository for LabelRepositoryForDB {
     async fn create(&self, name: String) -> anyhow::Result<Label> {
-        let optional_label = sqlx::query_as::<_, Label>(
+        let optional_label = sqlx::query_as::<_, LabelFromRow>(
             r#"
             select * from labels where name=$1
         "#,
@@ -49,7 +53,7 @@ impl LabelRepository for LabelRepositoryForDB {
             return Err(RepositoryError::Duplicate(label.id).into());
         }
 
-        let label = sqlx::query_as::<_, Label>(
+        let label = sqlx::query_as::<_, LabelFromRow>(
             r#"
             insert into labels (name)
             values ($1)
@@ -60,11 +64,11 @@ impl LabelRepository for LabelRepositoryForDB {
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(label)
+        Ok(label.into())
     }
 
     async fn all(&self) -> anyhow::Result<Vec<Label>> {
-        let label = sqlx::query_as::<_, Label>(
+        let label = sqlx::query_as::<_, LabelFromRow>(
             r#"
             select * from labels order by labels.id asc
         "#,
@@ -72,7 +76,7 @@ impl LabelRepository for LabelRepositoryForDB {
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(label)
+        Ok(label.into_iter().map(Label::from).collect())
     }
 
     async fn delete(&self, id: i32) -> anyhow::Result<()> {
@@ -95,6 +99,7 @@ impl LabelRepository for LabelRepositoryForDB {
 
 #[cfg(test)]
 #[cfg(feature = "database-test")]
+#[allow(clippy::expect_fun_call)]
 mod test {
     use super::*;
     use dotenv::dotenv;
@@ -131,35 +136,49 @@ mod test {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils {
     use axum::async_trait;
+    use std::sync::{Arc, RwLock};
 
     use super::*;
 
-    impl Label {}
-
-    #[derive(Debug, Clone)]
-    pub struct LabelRepositoryForMemory {}
+    #[derive(Debug, Clone, Default)]
+    pub struct LabelRepositoryForMemory {
+        store: Arc<RwLock<Vec<Label>>>,
+    }
 
     impl LabelRepositoryForMemory {
         pub fn new() -> Self {
-            LabelRepositoryForMemory {}
+            LabelRepositoryForMemory::default()
         }
     }
 
     #[async_trait]
     impl LabelRepository for LabelRepositoryForMemory {
-        async fn create(&self, _name: String) -> anyhow::Result<Label> {
-            todo!()
+        async fn create(&self, name: String) -> anyhow::Result<Label> {
+            let mut store = self.store.write().unwrap();
+            if let Some(label) = store.iter().find(|label| label.name == name) {
+                return Err(RepositoryError::Duplicate(label.id).into());
+            }
+            let id = store.iter().map(|label| label.id).max().unwrap_or(0) + 1;
+            let label = Label { id, name };
+            store.push(label.clone());
+            Ok(label)
         }
 
         async fn all(&self) -> anyhow::Result<Vec<Label>> {
-            todo!()
+            Ok(self.store.read().unwrap().clone())
         }
 
-        async fn delete(&self, _id: i32) -> anyhow::Result<()> {
-            todo!()
+        async fn delete(&self, id: i32) -> anyhow::Result<()> {
+            let mut store = self.store.write().unwrap();
+            let index = store
+                .iter()
+                .position(|label| label.id == id)
+                .ok_or(RepositoryError::NotFound(id))?;
+            store.remove(index);
+            Ok(())
         }
     }
 }
@@ -1,16 +1,52 @@
 use super::{label::Label, RepositoryError};
 use axum::async_trait;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
-use validator::Validate;
+use sqlx::{types::Json, FromRow, PgPool, Postgres, Transaction};
+use todo_core::v1::recurrence::Recurrence;
+pub use todo_core::v1::todo::{CreateTodo, TodoEntity, TodoFilter, TodoSearchResult, UpdateTodo};
 
 #[async_trait]
 pub trait TodoRepository: Clone + std::marker::Send + std::marker::Sync + 'static {
     async fn create(&self, payload: CreateTodo) -> anyhow::Result<TodoEntity>;
     async fn find(&self, id: i32) -> anyhow::Result<TodoEntity>;
     async fn all(&self) -> anyhow::Result<Vec<TodoEntity>>;
+    async fn search(&self, q: &str, filter: &TodoFilter) -> anyhow::Result<Vec<TodoSearchResult>>;
     async fn update(&self, id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity>;
     async fn delete(&self, id: i32) -> anyhow::Result<()>;
+    // id の次の回をまだ作っていなければ作成する。作成済みなら None
+    async fn spawn_next(&self, id: i32, payload: CreateTodo) -> anyhow::Result<Option<TodoEntity>>;
+}
+
+// 更新し、繰り返し Todo が完了になったら次の回を作成する
+pub async fn update_and_spawn_next<T: TodoRepository>(
+    repository: &T,
+    id: i32,
+    payload: UpdateTodo,
+) -> anyhow::Result<TodoEntity> {
+    let mut payload = payload;
+    let before = repository.find(id).await?;
+    if payload.recurrence.is_some() && payload.due_date.or(before.due_date).is_none() {
+        return Err(RepositoryError::MissingDueDate(id).into());
+    }
+    // 期日を付け替えたら新しい期日から数え直す
+    if payload.due_date.is_some() {
+        payload.recurrence = payload
+            .recurrence
+            .or(before.recurrence.clone())
+            .map(|recurrence| Recurrence {
+                anchor: None,
+                ..recurrence
+            });
+    }
+    let todo = repository.update(id, payload).await?;
+    if !before.completed && todo.completed {
+        if let Some(next) = todo.next_occurrence() {
+            // 完了を取り消してから再び完了しても、次の回は 1 度しか作らない
+            repository.spawn_next(id, next).await?;
+        }
+    }
+    Ok(todo)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, FromRow)]
@@ -18,10 +54,45 @@ pub struct TodoWithLabelFromRow {
     id: i32,
     text: String,
     completed: bool,
+    due_date: Option<NaiveDate>,
+    recurrence: Option<Json<Recurrence>>,
     label_id: Option<i32>,
     label_name: Option<String>,
 }
 
+// ts_headline に付けさせる目印。本文をエスケープしてから <mark> に置き換える
+const MARK_START: char = '\u{2}';
+const MARK_STOP: char = '\u{3}';
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// スニペットは HTML として返すので、利用者の入力はエスケープして <mark> だけを残す
+fn render_snippet(raw: &str) -> String {
+    escape_html(raw)
+        .replace(MARK_START, "<mark>")
+        .replace(MARK_STOP, "</mark>")
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow)]
+struct TodoSearchFromRow {
+    id: i32,
+    rank: f32,
+    snippet: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, FromRow)]
 struct TodoFromRow {
     id: i32,
@@ -29,14 +100,7 @@ struct TodoFromRow {
     completed: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, FromRow)]
-pub struct TodoEntity {
-    pub id: i32,
-    pub text: String,
-    pub completed: bool,
-    pub labels: Vec<Label>,
-}
-
+#[allow(clippy::while_let_on_iterator, clippy::unnecessary_unwrap)]
 fn fold_entities(rows: Vec<TodoWithLabelFromRow>) -> Vec<TodoEntity> {
     let mut rows = rows.iter();
     let mut accum: Vec<TodoEntity> = vec![];
@@ -67,29 +131,14 @@ fn fold_entities(rows: Vec<TodoWithLabelFromRow>) -> Vec<TodoEntity> {
             id: row.id,
             text: row.text.clone(),
             completed: row.completed,
+            due_date: row.due_date,
+            recurrence: row.recurrence.clone().map(|Json(recurrence)| recurrence),
             labels,
         });
     }
     accum
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
-pub struct CreateTodo {
-    #[validate(length(min = 1, message = "Can not be empty"))]
-    #[validate(length(max = 100, message = "Over text length"))]
-    text: String,
-    labels: Vec<i32>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
-pub struct UpdateTodo {
-    #[validate(length(min = 1, message = "Can not be empty"))]
-    #[validate(length(max = 100, message = "Over text length"))]
-    text: Option<String>,
-    completed: Option<bool>,
-    labels: Option<Vec<i32>>,
-}
-
 #[derive(Debug, Clone)]
 pub struct TodoRepositoryForDB {
     pool: PgPool,
@@ -101,37 +150,48 @@ impl TodoRepositoryForDB {
     }
 }
 
+// todo とラベルの関連を挿入し、採番された id を返す
+async fn insert_todo(
+    tx: &mut Transaction<'_, Postgres>,
+    payload: CreateTodo,
+) -> anyhow::Result<i32> {
+    // 'returning *' を記述することで insert 結果のレコードを取得できる（pg の機能）
+    let row = sqlx::query_as::<_, TodoFromRow>(
+        r#"
+        insert into todos (text, completed, due_date, recurrence)
+        values ($1, false, $2, $3)
+        returning *;
+    "#,
+    )
+    .bind(payload.text.clone())
+    .bind(payload.due_date)
+    .bind(payload.recurrence.map(Json))
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        insert into todo_labels (todo_id, label_id)
+        select $1, id
+        from unnest($2) as t(id);
+    "#,
+    )
+    .bind(row.id)
+    .bind(payload.labels)
+    .execute(&mut *tx)
+    .await?;
+
+    Ok(row.id)
+}
+
 #[async_trait]
 impl TodoRepository for TodoRepositoryForDB {
     async fn create(&self, payload: CreateTodo) -> anyhow::Result<TodoEntity> {
-        let tx = self.pool.begin().await?;
-        // 'returning *' を記述することで insert 結果のレコードを取得できる（pg の機能）
-        let row = sqlx::query_as::<_, TodoFromRow>(
-            r#"
-            insert into todos (text, completed)
-            values ($1, false)
-            returning *;
-        "#,
-        )
-        .bind(payload.text.clone())
-        .fetch_one(&self.pool)
-        .await?;
-
-        sqlx::query(
-            r#"
-            insert into todo_labels (todo_id, label_id)
-            select $1, id
-            from unnest($2) as t(id);
-        "#,
-        )
-        .bind(row.id)
-        .bind(payload.labels)
-        .execute(&self.pool)
-        .await?;
-
+        let mut tx = self.pool.begin().await?;
+        let id = insert_todo(&mut tx, payload).await?;
         tx.commit().await?;
 
-        let todo = self.find(row.id).await?;
+        let todo = self.find(id).await?;
 
         Ok(todo)
     }
@@ -176,6 +236,64 @@ impl TodoRepository for TodoRepositoryForDB {
         Ok(fold_entities(todo))
     }
 
+    async fn search(&self, q: &str, filter: &TodoFilter) -> anyhow::Result<Vec<TodoSearchResult>> {
+        // text は search_vector（重み A）、ラベル名は label_vector（重み B）。
+        // 結合した式に GIN 索引 todos_text_and_labels_idx があるので、where 句は索引と同じ式にしておく
+        let rows = sqlx::query_as::<_, TodoSearchFromRow>(
+            r#"
+            select todos.id,
+                ts_rank(todos.search_vector || todos.label_vector, query) as rank,
+                ts_headline('simple', translate(todos.text, $4, ''), query, $5) as snippet
+            from todos
+            cross join websearch_to_tsquery('simple', $1) query
+            where (todos.search_vector || todos.label_vector) @@ query
+            and ($2::integer is null or exists (
+                select 1 from todo_labels tl where tl.todo_id = todos.id and tl.label_id = $2
+            ))
+            and ($3::boolean is null or todos.completed = $3)
+            order by rank desc, todos.id desc;
+        "#,
+        )
+        .bind(q)
+        .bind(filter.label)
+        .bind(filter.completed)
+        // 本文に目印と同じ文字があっても <mark> にならないよう取り除いておく
+        .bind(format!("{}{}", MARK_START, MARK_STOP))
+        .bind(format!("StartSel={}, StopSel={}", MARK_START, MARK_STOP))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let ids: Vec<i32> = rows.iter().map(|row| row.id).collect();
+        let items = sqlx::query_as::<_, TodoWithLabelFromRow>(
+            r#"
+            select todos.*, labels.id as label_id, labels.name as label_name
+            from todos
+            left outer join todo_labels tl on todos.id = tl.todo_id
+            left outer join labels on labels.id = tl.label_id
+            where todos.id = any($1);
+        "#,
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+        let todos = fold_entities(items);
+
+        // ランク順を保ったまま TodoEntity と組み合わせる
+        let results = rows
+            .into_iter()
+            .filter_map(|row| {
+                let todo = todos.iter().find(|todo| todo.id == row.id)?.clone();
+                Some(TodoSearchResult {
+                    todo,
+                    rank: row.rank,
+                    snippet: render_snippet(&row.snippet),
+                })
+            })
+            .collect();
+
+        Ok(results)
+    }
+
     async fn update(&self, id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity> {
         let tx = self.pool.begin().await?;
 
@@ -183,11 +301,13 @@ impl TodoRepository for TodoRepositoryForDB {
         let old_todo = self.find(id).await?;
         sqlx::query(
             r#"
-            update todos set text=$1, completed=$2 where id=$3;
+            update todos set text=$1, completed=$2, due_date=$3, recurrence=$4 where id=$5;
         "#,
         )
         .bind(payload.text.unwrap_or(old_todo.text)) // text が空の場合は元の情報を入れる
         .bind(payload.completed.unwrap_or(old_todo.completed))
+        .bind(payload.due_date.or(old_todo.due_date))
+        .bind(payload.recurrence.or(old_todo.recurrence).map(Json))
         .bind(id)
         .execute(&self.pool)
         .await?;
@@ -257,14 +377,40 @@ impl TodoRepository for TodoRepositoryForDB {
 
         Ok(())
     }
+
+    async fn spawn_next(&self, id: i32, payload: CreateTodo) -> anyhow::Result<Option<TodoEntity>> {
+        let mut tx = self.pool.begin().await?;
+        // 先に作成済みの印を立てる。同時に完了されても行ロックで片方は 0 件になる
+        let marked = sqlx::query(
+            r#"
+            update todos set next_spawned=true where id=$1 and not next_spawned;
+        "#,
+        )
+        .bind(id)
+        .execute(&mut tx)
+        .await?;
+        if marked.rows_affected() == 0 {
+            return Ok(None);
+        }
+        let next_id = insert_todo(&mut tx, payload).await?;
+        tx.commit().await?;
+
+        let todo = self.find(next_id).await?;
+
+        Ok(Some(todo))
+    }
 }
 
+#[cfg(test)]
 #[cfg(feature = "database-test")]
+#[allow(clippy::expect_fun_call, clippy::let_unit_value, clippy::len_zero)]
 mod test {
     use super::*;
+    use crate::repositories::label::LabelFromRow;
     use dotenv::dotenv;
     use sqlx::PgPool;
     use std::env;
+    use todo_core::v1::recurrence::Frequency;
 
     #[test]
     fn fold_entities_test() {
@@ -281,6 +427,8 @@ mod test {
                 id: 1,
                 text: String::from("todo 1"),
                 completed: false,
+                due_date: None,
+                recurrence: None,
                 label_id: Some(label_1.id),
                 label_name: Some(label_1.name.clone()),
             },
@@ -288,6 +436,8 @@ mod test {
                 id: 1,
                 text: String::from("todo 1"),
                 completed: false,
+                due_date: None,
+                recurrence: None,
                 label_id: Some(label_2.id),
                 label_name: Some(label_2.name.clone()),
             },
@@ -295,6 +445,8 @@ mod test {
                 id: 2,
                 text: String::from("todo 2"),
                 completed: false,
+                due_date: None,
+                recurrence: None,
                 label_id: Some(label_1.id),
                 label_name: Some(label_1.name.clone()),
             },
@@ -307,12 +459,16 @@ mod test {
                     id: 1,
                     text: String::from("todo 1"),
                     completed: false,
+                    due_date: None,
+                    recurrence: None,
                     labels: vec![label_1.clone(), label_2.clone()],
                 },
                 TodoEntity {
                     id: 2,
                     text: String::from("todo 2"),
                     completed: false,
+                    due_date: None,
+                    recurrence: None,
                     labels: vec![label_1.clone()],
                 },
             ]
@@ -329,15 +485,17 @@ mod test {
             .expect(&format!("fail connect database, url is [{}]", database_url));
 
         let label_name = String::from("test label");
-        let optional_label = sqlx::query_as::<_, Label>(r#"select * from labels where name = $1"#)
-            .bind(label_name.clone())
-            .fetch_optional(&pool)
-            .await
-            .expect("Failed to prepare label data");
+        let optional_label =
+            sqlx::query_as::<_, LabelFromRow>(r#"select * from labels where name = $1"#)
+                .bind(label_name.clone())
+                .fetch_optional(&pool)
+                .await
+                .expect("Failed to prepare label data")
+                .map(Label::from);
         let label_1 = if let Some(label) = optional_label {
             label
         } else {
-            let label = sqlx::query_as::<_, Label>(
+            let label = sqlx::query_as::<_, LabelFromRow>(
                 r#"
                 insert into labels (name)
                 values ($1)
@@ -348,7 +506,7 @@ mod test {
             .fetch_one(&pool)
             .await
             .expect("Failed to insert label data");
-            label
+            label.into()
         };
 
         let repository = TodoRepositoryForDB::new(pool.clone());
@@ -376,16 +534,17 @@ mod test {
 
         let update_label_name = String::from("test label");
         let optional_update_label =
-            sqlx::query_as::<_, Label>(r#"select * from labels where name = $1"#)
+            sqlx::query_as::<_, LabelFromRow>(r#"select * from labels where name = $1"#)
                 .bind(update_label_name.clone())
                 .fetch_optional(&pool)
                 .await
-                .expect("Failed to prepare label data");
+                .expect("Failed to prepare label data")
+                .map(Label::from);
 
         let update_label_2 = if let Some(label) = optional_update_label {
             label
         } else {
-            let label = sqlx::query_as::<_, Label>(
+            let label = sqlx::query_as::<_, LabelFromRow>(
                 r#"
                 insert into labels (name)
                 values ($1)
@@ -396,7 +555,7 @@ mod test {
             .fetch_one(&pool)
             .await
             .expect("Failed to insert label data");
-            label
+            label.into()
         };
 
         // update
@@ -408,6 +567,8 @@ mod test {
                     text: Some(updated_text.to_string()),
                     completed: Some(false),
                     labels: Some(vec![update_label_2.id]),
+                    due_date: None,
+                    recurrence: None,
                 },
             )
             .await
@@ -439,43 +600,297 @@ mod test {
             .expect("[delete] todo_labels fetch error");
         assert!(rows.len() == 0);
     }
+
+    #[tokio::test]
+    async fn recurrence_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect(&format!("fail connect database, url is [{}]", database_url));
+        let repository = TodoRepositoryForDB::new(pool);
+        let due_date = NaiveDate::from_ymd_opt(2023, 1, 31).unwrap();
+        let recurrence = Recurrence {
+            frequency: Frequency::Monthly,
+            interval: 1,
+            until: None,
+            count: Some(2),
+            anchor: None,
+        };
+
+        let created = repository
+            .create(
+                CreateTodo::new("[recurrence_scenario] text".to_string(), vec![])
+                    .with_recurrence(due_date, recurrence.clone()),
+            )
+            .await
+            .expect("[create] returned Err");
+        assert_eq!(created.due_date, Some(due_date));
+        assert_eq!(created.recurrence, Some(recurrence));
+
+        let next = created.next_occurrence().expect("next occurrence");
+        let spawned = repository
+            .spawn_next(created.id, next.clone())
+            .await
+            .expect("[spawn_next] returned Err")
+            .expect("next not spawned");
+        assert_eq!(spawned.due_date, NaiveDate::from_ymd_opt(2023, 2, 28));
+        assert_eq!(spawned.recurrence.as_ref().unwrap().count, Some(1));
+        assert_eq!(spawned.recurrence.as_ref().unwrap().anchor, Some(due_date));
+        assert!(spawned.next_occurrence().is_none());
+        // 2 度目は作らない
+        let again = repository
+            .spawn_next(created.id, next)
+            .await
+            .expect("[spawn_next] returned Err");
+        assert!(again.is_none());
+
+        repository
+            .delete(created.id)
+            .await
+            .expect("[delete] returned Err");
+        repository
+            .delete(spawned.id)
+            .await
+            .expect("[delete] returned Err");
+    }
+
+    #[tokio::test]
+    async fn search_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect(&format!("fail connect database, url is [{}]", database_url));
+        let repository = TodoRepositoryForDB::new(pool);
+
+        let created = repository
+            .create(CreateTodo::new(
+                "[search_scenario] quarterly zyzzyva review".to_string(),
+                vec![],
+            ))
+            .await
+            .expect("[create] returned Err");
+
+        let results = repository
+            .search("zyzzyva", &TodoFilter::default())
+            .await
+            .expect("[search] returned Err");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].todo, created);
+        assert!(results[0].snippet.contains("<mark>zyzzyva</mark>"));
+
+        let filter = TodoFilter {
+            label: None,
+            completed: Some(true),
+        };
+        let results = repository
+            .search("zyzzyva", &filter)
+            .await
+            .expect("[search] returned Err");
+        assert!(results.is_empty());
+
+        repository
+            .delete(created.id)
+            .await
+            .expect("[delete] returned Err");
+    }
+
+    #[tokio::test]
+    async fn search_label_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect(&format!("fail connect database, url is [{}]", database_url));
+        let repository = TodoRepositoryForDB::new(pool.clone());
+
+        let label = sqlx::query_as::<_, LabelFromRow>(
+            r#"
+            insert into labels (name)
+            values ($1)
+            returning *
+            "#,
+        )
+        .bind("quuxplover")
+        .fetch_one(&pool)
+        .await
+        .map(Label::from)
+        .expect("Failed to insert label data");
+        let created = repository
+            .create(CreateTodo::new(
+                "[search_label_scenario] frobnicate".to_string(),
+                vec![label.id],
+            ))
+            .await
+            .expect("[create] returned Err");
+
+        // ラベル名だけでも、本文とラベル名の組み合わせでもヒットする
+        for q in ["quuxplover", "frobnicate quuxplover"] {
+            let results = repository
+                .search(q, &TodoFilter::default())
+                .await
+                .expect("[search] returned Err");
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].todo, created);
+        }
+
+        // 付け外しに合わせて label_vector も更新される
+        repository
+            .update(
+                created.id,
+                UpdateTodo {
+                    labels: Some(vec![]),
+                    ..UpdateTodo::default()
+                },
+            )
+            .await
+            .expect("[update] returned Err");
+        let results = repository
+            .search("quuxplover", &TodoFilter::default())
+            .await
+            .expect("[search] returned Err");
+        assert!(results.is_empty());
+
+        repository
+            .delete(created.id)
+            .await
+            .expect("[delete] returned Err");
+        sqlx::query("delete from labels where id = $1")
+            .bind(label.id)
+            .execute(&pool)
+            .await
+            .expect("Failed to delete label data");
+    }
+
+    #[tokio::test]
+    async fn search_uses_text_and_labels_index() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect(&format!("fail connect database, url is [{}]", database_url));
+
+        // テスト用の DB は行数が少ないので、seq scan を止めて索引が使える式かどうかだけ確かめる
+        let mut conn = pool.acquire().await.expect("Failed to acquire connection");
+        sqlx::query("set enable_seqscan = off")
+            .execute(&mut conn)
+            .await
+            .expect("Failed to disable seq scan");
+        let plan: Vec<(String,)> = sqlx::query_as(
+            r#"
+            explain select todos.id
+            from todos
+            cross join websearch_to_tsquery('simple', 'quuxplover') query
+            where (todos.search_vector || todos.label_vector) @@ query
+            "#,
+        )
+        .fetch_all(&mut conn)
+        .await
+        .expect("Failed to explain search");
+        sqlx::query("reset enable_seqscan")
+            .execute(&mut conn)
+            .await
+            .expect("Failed to reset seq scan");
+
+        let plan: Vec<String> = plan.into_iter().map(|(line,)| line).collect();
+        assert!(
+            plan.iter()
+                .any(|line| line.contains("Bitmap Index Scan on todos_text_and_labels_idx")),
+            "{}",
+            plan.join("\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn search_escapes_snippet() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect(&format!("fail connect database, url is [{}]", database_url));
+        let repository = TodoRepositoryForDB::new(pool);
+
+        let created = repository
+            .create(CreateTodo::new(
+                "[search_escapes_snippet] a < b & \u{2}xyzzyplugh\u{3} <img src=x onerror=alert(1)>"
+                    .to_string(),
+                vec![],
+            ))
+            .await
+            .expect("[create] returned Err");
+
+        let results = repository
+            .search("xyzzyplugh", &TodoFilter::default())
+            .await
+            .expect("[search] returned Err");
+        assert_eq!(results.len(), 1);
+        let snippet = &results[0].snippet;
+        assert!(snippet.contains("a &lt; b &amp; <mark>xyzzyplugh</mark>"));
+        assert!(!snippet.contains("<img"));
+        assert!(!snippet.contains('\u{2}'));
+
+        repository
+            .delete(created.id)
+            .await
+            .expect("[delete] returned Err");
+    }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils {
     use anyhow::Context;
     use axum::async_trait;
     use std::{
-        collections::HashMap,
+        collections::{HashMap, HashSet},
         sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
     };
 
     use super::*;
 
-    impl TodoEntity {
-        pub fn new(id: i32, text: String, labels: Vec<Label>) -> Self {
-            Self {
-                id,
-                text,
-                completed: false,
-                labels,
-            }
-        }
+    type TodoDatas = HashMap<i32, TodoEntity>;
+
+    // 英数字の連続を 1 語として小文字で切り出す簡易トークナイザ
+    fn tokenize(text: &str) -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_lowercase())
+            .collect()
     }
 
-    #[cfg(test)]
-    impl CreateTodo {
-        pub fn new(text: String, labels: Vec<i32>) -> Self {
-            Self { text, labels }
-        }
+    fn count_hits(words: &[String], terms: &[String]) -> usize {
+        words.iter().filter(|word| terms.contains(word)).count()
     }
 
-    type TodoDatas = HashMap<i32, TodoEntity>;
+    fn highlight(text: &str, terms: &[String]) -> String {
+        let mut snippet = String::new();
+        let mut word = String::new();
+        let flush = |word: &mut String, snippet: &mut String| {
+            if terms.contains(&word.to_lowercase()) {
+                snippet.push_str(&format!("<mark>{}</mark>", escape_html(word)));
+            } else {
+                snippet.push_str(&escape_html(word));
+            }
+            word.clear();
+        };
+        for c in text.chars() {
+            if c.is_alphanumeric() {
+                word.push(c);
+            } else {
+                flush(&mut word, &mut snippet);
+                snippet.push_str(&escape_html(&c.to_string()));
+            }
+        }
+        flush(&mut word, &mut snippet);
+        snippet
+    }
 
     #[derive(Debug, Clone)]
     pub struct TodoRepositoryForMemory {
         store: Arc<RwLock<TodoDatas>>,
         labels: Vec<Label>,
+        // 次の回を作成済みの todo の id
+        spawned: Arc<RwLock<HashSet<i32>>>,
     }
 
     impl TodoRepositoryForMemory {
@@ -483,14 +898,15 @@ pub mod test_utils {
             TodoRepositoryForMemory {
                 store: Arc::default(),
                 labels,
+                spawned: Arc::default(),
             }
         }
 
-        fn write_store_ref(&self) -> RwLockWriteGuard<TodoDatas> {
+        fn write_store_ref(&self) -> RwLockWriteGuard<'_, TodoDatas> {
             self.store.write().unwrap()
         }
 
-        fn read_store_ref(&self) -> RwLockReadGuard<TodoDatas> {
+        fn read_store_ref(&self) -> RwLockReadGuard<'_, TodoDatas> {
             self.store.read().unwrap()
         }
 
@@ -510,7 +926,11 @@ pub mod test_utils {
             let mut store = self.write_store_ref();
             let id = (store.len() + 1) as i32;
             let labels = self.resolve_labels(payload.labels);
-            let todo = TodoEntity::new(id, payload.text.clone(), labels);
+            let todo = TodoEntity {
+                due_date: payload.due_date,
+                recurrence: payload.recurrence,
+                ..TodoEntity::new(id, payload.text.clone(), labels)
+            };
             store.insert(id, todo.clone());
             Ok(todo)
         }
@@ -520,14 +940,52 @@ pub mod test_utils {
             // 戻り値が借用になるため、clone() する。Box::new でも可
             let todo = store
                 .get(&id)
-                .map(|todo| todo.clone())
+                .cloned()
                 .ok_or(RepositoryError::NotFound(id))?;
             Ok(todo)
         }
 
         async fn all(&self) -> anyhow::Result<Vec<TodoEntity>> {
             let store = self.read_store_ref();
-            Ok(Vec::from_iter(store.values().map(|todo| todo.clone())))
+            Ok(Vec::from_iter(store.values().cloned()))
+        }
+
+        async fn search(
+            &self,
+            q: &str,
+            filter: &TodoFilter,
+        ) -> anyhow::Result<Vec<TodoSearchResult>> {
+            let store = self.read_store_ref();
+            let terms = tokenize(q);
+            let mut results: Vec<TodoSearchResult> = store
+                .values()
+                .filter(|todo| filter.matches(todo))
+                .filter_map(|todo| {
+                    let text_words = tokenize(&todo.text);
+                    let label_words: Vec<String> = todo
+                        .labels
+                        .iter()
+                        .flat_map(|label| tokenize(&label.name))
+                        .collect();
+                    // websearch_to_tsquery と同じく、すべての語が text かラベル名にあるものだけ返す
+                    let matched = terms
+                        .iter()
+                        .all(|term| text_words.contains(term) || label_words.contains(term));
+                    if terms.is_empty() || !matched {
+                        return None;
+                    }
+                    // text の一致は 1.0、ラベル名の一致は 0.4 として加算する
+                    let text_hits = count_hits(&text_words, &terms);
+                    let label_hits = count_hits(&label_words, &terms);
+                    Some(TodoSearchResult {
+                        todo: todo.clone(),
+                        rank: text_hits as f32 + label_hits as f32 * 0.4,
+                        snippet: highlight(&todo.text, &terms),
+                    })
+                })
+                .collect();
+            results.sort_by(|a, b| b.rank.total_cmp(&a.rank).then(b.todo.id.cmp(&a.todo.id)));
+            Ok(results)
         }
 
         async fn update(&self, id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity> {
@@ -535,11 +993,19 @@ pub mod test_utils {
             let todo = store.get(&id).context(RepositoryError::NotFound(id))?;
             let text = payload.text.unwrap_or(todo.text.clone());
             let completed = payload.completed.unwrap_or(todo.completed);
+            let due_date = payload.due_date.or(todo.due_date);
+            let recurrence = payload.recurrence.or(todo.recurrence.clone());
+            let labels = match payload.labels {
+                Some(labels) => self.resolve_labels(labels),
+                None => todo.labels.clone(),
+            };
             let todo = TodoEntity {
                 id,
                 text,
                 completed,
-                labels: vec![],
+                due_date,
+                recurrence,
+                labels,
             };
             // insertで更新
             store.insert(id, todo.clone());
@@ -551,6 +1017,18 @@ pub mod test_utils {
             store.remove(&id).ok_or(RepositoryError::NotFound(id))?;
             Ok(())
         }
+
+        async fn spawn_next(
+            &self,
+            id: i32,
+            payload: CreateTodo,
+        ) -> anyhow::Result<Option<TodoEntity>> {
+            if !self.spawned.write().unwrap().insert(id) {
+                return Ok(None);
+            }
+            let todo = self.create(payload).await?;
+            Ok(Some(todo))
+        }
     }
 
     #[cfg(test)]
@@ -570,6 +1048,8 @@ pub mod test_utils {
                 id,
                 text: text.clone(),
                 completed: false,
+                due_date: None,
+                recurrence: None,
                 labels: labels.clone(),
             };
 
@@ -603,6 +1083,8 @@ pub mod test_utils {
                         text: Some(text.clone()),
                         completed: Some(true),
                         labels: Some(vec![]),
+                        due_date: None,
+                        recurrence: None,
                     },
                 )
                 .await
@@ -612,6 +1094,8 @@ pub mod test_utils {
                     id,
                     text,
                     completed: true,
+                    due_date: None,
+                    recurrence: None,
                     labels: vec![],
                 },
                 todo
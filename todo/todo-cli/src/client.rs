@@ -0,0 +1,84 @@
+use crate::config::Config;
+use anyhow::bail;
+use reqwest::{Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use todo_core::v1::{
+    label::{CreateLabel, Label},
+    todo::{CreateTodo, TodoEntity, TodoFilter, UpdateTodo},
+};
+
+// todo-api の HTTP エンドポイントを呼び出すクライアント
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    server: String,
+    token: Option<String>,
+}
+
+impl Client {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            server: config.server.trim_end_matches('/').to_string(),
+            token: config.token.clone(),
+        }
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let req = self
+            .http
+            .request(method, format!("{}{}", self.server, path));
+        match &self.token {
+            Some(token) => req.bearer_auth(token),
+            None => req,
+        }
+    }
+
+    async fn send(req: RequestBuilder) -> anyhow::Result<Response> {
+        let res = req.send().await?;
+        let status = res.status();
+        if !status.is_success() {
+            let url = res.url().clone();
+            let body = res.text().await.unwrap_or_default();
+            bail!("request to [{}] failed: {} {}", url, status, body);
+        }
+        Ok(res)
+    }
+
+    async fn send_json<T: DeserializeOwned>(req: RequestBuilder) -> anyhow::Result<T> {
+        Ok(Self::send(req).await?.json().await?)
+    }
+
+    pub async fn create_todo(&self, payload: &CreateTodo) -> anyhow::Result<TodoEntity> {
+        Self::send_json(self.request(Method::POST, "/todos").json(payload)).await
+    }
+
+    pub async fn all_todo(&self, filter: &TodoFilter) -> anyhow::Result<Vec<TodoEntity>> {
+        Self::send_json(self.request(Method::GET, "/todos").query(filter)).await
+    }
+
+    pub async fn update_todo(&self, id: i32, payload: &UpdateTodo) -> anyhow::Result<TodoEntity> {
+        let path = format!("/todos/{}", id);
+        Self::send_json(self.request(Method::PATCH, &path).json(payload)).await
+    }
+
+    pub async fn delete_todo(&self, id: i32) -> anyhow::Result<()> {
+        let path = format!("/todos/{}", id);
+        Self::send(self.request(Method::DELETE, &path)).await?;
+        Ok(())
+    }
+
+    pub async fn create_label(&self, payload: &CreateLabel) -> anyhow::Result<Label> {
+        Self::send_json(self.request(Method::POST, "/labels").json(payload)).await
+    }
+
+    pub async fn all_label(&self) -> anyhow::Result<Vec<Label>> {
+        Self::send_json(self.request(Method::GET, "/labels")).await
+    }
+
+    pub async fn delete_label(&self, id: i32) -> anyhow::Result<()> {
+        let path = format!("/labels/{}", id);
+        Self::send(self.request(Method::DELETE, &path)).await?;
+        Ok(())
+    }
+}
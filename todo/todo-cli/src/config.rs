@@ -0,0 +1,90 @@
+use anyhow::Context;
+use serde::Deserialize;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+pub const DEFAULT_SERVER: &str = "http://localhost:3000";
+
+// 設定ファイル（TOML）
+// server = "http://localhost:3000"
+// token = "..."
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Config {
+    pub server: String,
+    pub token: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            server: DEFAULT_SERVER.to_string(),
+            token: None,
+        }
+    }
+}
+
+impl Config {
+    // 既定の設定ファイルの場所: $HOME/.config/todo-cli/config.toml
+    pub fn default_path() -> Option<PathBuf> {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join(".config/todo-cli/config.toml"))
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("cannot read config file [{}]", path.display()))?;
+        let config = toml::from_str(&text)
+            .with_context(|| format!("invalid config file [{}]", path.display()))?;
+        Ok(config)
+    }
+
+    // 明示されたファイルは必須、既定の場所のファイルは無ければデフォルト値を使う
+    pub fn resolve(path: Option<&Path>) -> anyhow::Result<Self> {
+        match path {
+            Some(path) => Self::load(path),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => Self::load(&path),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn load_config_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "server = \"http://example.com:8080\"\ntoken = \"secret\""
+        )
+        .unwrap();
+        let config = Config::load(file.path()).expect("[load] returned Err");
+        assert_eq!(
+            config,
+            Config {
+                server: "http://example.com:8080".to_string(),
+                token: Some("secret".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "token = \"secret\"").unwrap();
+        let config = Config::load(file.path()).expect("[load] returned Err");
+        assert_eq!(config.server, DEFAULT_SERVER);
+    }
+
+    #[test]
+    fn explicit_missing_file_is_error() {
+        assert!(Config::resolve(Some(Path::new("/nonexistent/todo-cli.toml"))).is_err());
+    }
+}
@@ -0,0 +1,290 @@
+pub mod client;
+pub mod config;
+pub mod output;
+
+use clap::{Parser, Subcommand};
+use client::Client;
+use config::Config;
+use output::Format;
+use std::path::PathBuf;
+use todo_core::v1::{
+    label::CreateLabel,
+    todo::{CreateTodo, TodoFilter, UpdateTodo},
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "todo-cli", about = "Command-line client for todo-api")]
+pub struct Cli {
+    /// Config file (default: $HOME/.config/todo-cli/config.toml)
+    #[arg(long, global = true, env = "TODO_CLI_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Server URL, overrides the config file
+    #[arg(long, global = true, env = "TODO_SERVER")]
+    pub server: Option<String>,
+    /// API token, overrides the config file
+    #[arg(long, global = true, env = "TODO_TOKEN")]
+    pub token: Option<String>,
+    /// Output format
+    #[arg(short, long, global = true, value_enum, default_value_t = Format::Table)]
+    pub output: Format,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Create a todo
+    Add {
+        text: String,
+        #[arg(short, long = "label")]
+        labels: Vec<i32>,
+    },
+    /// List todos
+    List {
+        #[arg(long)]
+        label: Option<i32>,
+        #[arg(long)]
+        completed: Option<bool>,
+    },
+    /// Mark a todo as completed
+    Done { id: i32 },
+    /// Edit a todo
+    Edit {
+        id: i32,
+        #[arg(long)]
+        text: Option<String>,
+        #[arg(short, long = "label")]
+        labels: Option<Vec<i32>>,
+        /// Remove all labels
+        #[arg(long, conflicts_with = "labels")]
+        clear_labels: bool,
+        /// Mark as not completed
+        #[arg(long)]
+        undone: bool,
+    },
+    /// Delete a todo
+    Rm { id: i32 },
+    /// List labels, or manage them with a subcommand
+    Labels {
+        #[command(subcommand)]
+        action: Option<LabelAction>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum LabelAction {
+    /// Create a label
+    Add { name: String },
+    /// Delete a label
+    Rm { id: i32 },
+}
+
+impl Cli {
+    // コマンドライン引数 > 設定ファイル > デフォルト値 の順に採用する
+    pub fn resolve_config(&self) -> anyhow::Result<Config> {
+        let mut config = Config::resolve(self.config.as_deref())?;
+        if let Some(server) = &self.server {
+            config.server = server.clone();
+        }
+        if let Some(token) = &self.token {
+            config.token = Some(token.clone());
+        }
+        Ok(config)
+    }
+}
+
+// コマンドを実行し、標準出力に出す文字列を返す
+pub async fn run(cli: Cli) -> anyhow::Result<String> {
+    let client = Client::new(&cli.resolve_config()?);
+    let format = cli.output;
+    match cli.command {
+        Command::Add { text, labels } => {
+            let todo = client.create_todo(&CreateTodo::new(text, labels)).await?;
+            output::todos(&[todo], format)
+        }
+        Command::List { label, completed } => {
+            let todos = client.all_todo(&TodoFilter { label, completed }).await?;
+            output::todos(&todos, format)
+        }
+        Command::Done { id } => {
+            let payload = UpdateTodo {
+                completed: Some(true),
+                ..Default::default()
+            };
+            let todo = client.update_todo(id, &payload).await?;
+            output::todos(&[todo], format)
+        }
+        Command::Edit {
+            id,
+            text,
+            labels,
+            clear_labels,
+            undone,
+        } => {
+            let payload = UpdateTodo {
+                text,
+                completed: undone.then_some(false),
+                labels: if clear_labels { Some(vec![]) } else { labels },
+                ..Default::default()
+            };
+            let todo = client.update_todo(id, &payload).await?;
+            output::todos(&[todo], format)
+        }
+        Command::Rm { id } => {
+            client.delete_todo(id).await?;
+            Ok(format!("deleted todo {}", id))
+        }
+        Command::Labels { action: None } => {
+            let labels = client.all_label().await?;
+            output::labels(&labels, format)
+        }
+        Command::Labels {
+            action: Some(LabelAction::Add { name }),
+        } => {
+            let label = client.create_label(&CreateLabel { name }).await?;
+            output::labels(&[label], format)
+        }
+        Command::Labels {
+            action: Some(LabelAction::Rm { id }),
+        } => {
+            client.delete_label(id).await?;
+            Ok(format!("deleted label {}", id))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use tempfile::NamedTempFile;
+    use todo::{
+        create_app,
+        repositories::{
+            label::test_utils::LabelRepositoryForMemory, todo::test_utils::TodoRepositoryForMemory,
+        },
+    };
+    use todo_core::v1::{label::Label, todo::TodoEntity};
+
+    // create_app をメモリ上のリポジトリで起動し、その URL を返す
+    fn spawn_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let todo_repository = TodoRepositoryForMemory::new(vec![Label {
+            id: 1,
+            name: "work".to_string(),
+        }]);
+        let app = create_app(todo_repository, LabelRepositoryForMemory::new());
+        let server = axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service());
+        tokio::spawn(server);
+        format!("http://{}", addr)
+    }
+
+    async fn exec(server: &str, args: &[&str]) -> anyhow::Result<String> {
+        // 開発者の設定ファイルを読まないよう空の設定ファイルを渡す
+        let config = NamedTempFile::new().unwrap();
+        let mut argv = vec!["todo-cli", "--server", server, "--config"];
+        argv.push(config.path().to_str().unwrap());
+        argv.extend(args);
+        run(Cli::try_parse_from(argv)?).await
+    }
+
+    async fn exec_json<T: serde::de::DeserializeOwned>(server: &str, args: &[&str]) -> T {
+        let mut args = args.to_vec();
+        args.extend(["--output", "json"]);
+        let out = exec(server, &args).await.expect("command returned Err");
+        serde_json::from_str(&out).unwrap_or_else(|_| panic!("cannot parse output: {}", out))
+    }
+
+    #[tokio::test]
+    async fn todo_scenario() {
+        let server = spawn_server();
+
+        // add
+        let todos: Vec<TodoEntity> =
+            exec_json(&server, &["add", "write report", "--label", "1"]).await;
+        assert_eq!(todos[0].text, "write report");
+        assert_eq!(todos[0].labels[0].name, "work");
+
+        // done
+        let todos: Vec<TodoEntity> = exec_json(&server, &["done", "1"]).await;
+        assert!(todos[0].completed);
+
+        // list with filter
+        let todos: Vec<TodoEntity> = exec_json(&server, &["list", "--completed", "false"]).await;
+        assert!(todos.is_empty());
+        let out = exec(&server, &["list"]).await.unwrap();
+        assert_eq!(
+            out,
+            "ID  DONE  TEXT          DUE  LABELS\n1   x     write report       work"
+        );
+
+        // edit
+        let todos: Vec<TodoEntity> = exec_json(
+            &server,
+            &[
+                "edit",
+                "1",
+                "--text",
+                "rewrite report",
+                "--clear-labels",
+                "--undone",
+            ],
+        )
+        .await;
+        assert_eq!(todos[0].text, "rewrite report");
+        assert!(todos[0].labels.is_empty());
+        assert!(!todos[0].completed);
+
+        // rm
+        let out = exec(&server, &["rm", "1"]).await.unwrap();
+        assert_eq!(out, "deleted todo 1");
+        assert!(exec(&server, &["rm", "1"]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn label_scenario() {
+        let server = spawn_server();
+
+        let labels: Vec<Label> = exec_json(&server, &["labels", "add", "home"]).await;
+        assert_eq!(labels[0].name, "home");
+
+        let labels: Vec<Label> = exec_json(&server, &["labels"]).await;
+        assert_eq!(labels.len(), 1);
+
+        let out = exec(&server, &["labels", "rm", &labels[0].id.to_string()])
+            .await
+            .unwrap();
+        assert_eq!(out, format!("deleted label {}", labels[0].id));
+    }
+
+    #[test]
+    fn flags_override_config_file() {
+        use std::io::Write;
+        let mut config = NamedTempFile::new().unwrap();
+        writeln!(
+            config,
+            "server = \"http://config:3000\"\ntoken = \"from-file\""
+        )
+        .unwrap();
+        let path = config.path().to_str().unwrap();
+
+        let cli = Cli::try_parse_from(["todo-cli", "--config", path, "list"]).unwrap();
+        let resolved = cli.resolve_config().unwrap();
+        assert_eq!(resolved.server, "http://config:3000");
+        assert_eq!(resolved.token.as_deref(), Some("from-file"));
+
+        let cli = Cli::try_parse_from([
+            "todo-cli",
+            "--config",
+            path,
+            "--server",
+            "http://flag",
+            "list",
+        ])
+        .unwrap();
+        assert_eq!(cli.resolve_config().unwrap().server, "http://flag");
+    }
+}
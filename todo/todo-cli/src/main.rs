@@ -0,0 +1,10 @@
+use clap::Parser;
+use todo_cli::{run, Cli};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let out = run(cli).await?;
+    println!("{}", out);
+    Ok(())
+}
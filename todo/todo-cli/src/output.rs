@@ -0,0 +1,115 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use todo_core::v1::{label::Label, todo::TodoEntity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+pub fn json<T: Serialize + ?Sized>(value: &T) -> anyhow::Result<String> {
+    Ok(serde_json::to_string_pretty(value)?)
+}
+
+// 列幅を揃えたプレーンテキストの表
+fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<String>| {
+        cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![line(headers.iter().map(|h| h.to_string()).collect())];
+    lines.extend(rows.into_iter().map(line));
+    lines.join("\n")
+}
+
+pub fn todos(todos: &[TodoEntity], format: Format) -> anyhow::Result<String> {
+    match format {
+        Format::Json => json(todos),
+        Format::Table => {
+            let rows = todos
+                .iter()
+                .map(|todo| {
+                    vec![
+                        todo.id.to_string(),
+                        if todo.completed { "x" } else { " " }.to_string(),
+                        todo.text.clone(),
+                        todo.due_date.map(|d| d.to_string()).unwrap_or_default(),
+                        todo.labels
+                            .iter()
+                            .map(|label| label.name.clone())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ]
+                })
+                .collect();
+            Ok(table(&["ID", "DONE", "TEXT", "DUE", "LABELS"], rows))
+        }
+    }
+}
+
+pub fn labels(labels: &[Label], format: Format) -> anyhow::Result<String> {
+    match format {
+        Format::Json => json(labels),
+        Format::Table => {
+            let rows = labels
+                .iter()
+                .map(|label| vec![label.id.to_string(), label.name.clone()])
+                .collect();
+            Ok(table(&["ID", "NAME"], rows))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_todo_table() {
+        let todo = TodoEntity {
+            id: 12,
+            text: "write report".to_string(),
+            completed: true,
+            due_date: None,
+            recurrence: None,
+            labels: vec![
+                Label {
+                    id: 1,
+                    name: "work".to_string(),
+                },
+                Label {
+                    id: 2,
+                    name: "weekly".to_string(),
+                },
+            ],
+        };
+        assert_eq!(
+            todos(&[todo], Format::Table).unwrap(),
+            "ID  DONE  TEXT          DUE  LABELS\n12  x     write report       work, weekly"
+        );
+    }
+
+    #[test]
+    fn render_label_json() {
+        let label = Label {
+            id: 1,
+            name: "work".to_string(),
+        };
+        let out = labels(std::slice::from_ref(&label), Format::Json).unwrap();
+        let parsed: Vec<Label> = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed, vec![label]);
+    }
+}
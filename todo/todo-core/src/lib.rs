@@ -0,0 +1,6 @@
+//! todo-api のリクエスト・レスポンス型と検証ルール
+//!
+//! サーバー（todo-api）とクライアント（todo-cli など）で共有する。
+//! JSON の形が変わる変更は `v1` を書き換えず、新しい `v2` モジュールとして追加する。
+
+pub mod v1;
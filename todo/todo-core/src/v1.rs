@@ -0,0 +1,3 @@
+pub mod label;
+pub mod recurrence;
+pub mod todo;
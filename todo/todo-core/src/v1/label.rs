@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct UpdateLabel {
+    id: i32,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Validate)]
+pub struct CreateLabel {
+    #[validate(length(min = 1, message = "Can not be empty"))]
+    #[validate(length(max = 100, message = "Over text length"))]
+    pub name: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validate_create_label() {
+        let valid = CreateLabel {
+            name: "work".to_string(),
+        };
+        assert!(valid.validate().is_ok());
+
+        let empty = CreateLabel {
+            name: String::new(),
+        };
+        assert!(empty.validate().is_err());
+
+        let long = CreateLabel {
+            name: "a".repeat(101),
+        };
+        assert!(long.validate().is_err());
+    }
+}
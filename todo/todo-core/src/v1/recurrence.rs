@@ -0,0 +1,196 @@
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// RRULE の FREQ に相当
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+fn default_interval() -> i32 {
+    1
+}
+
+// 繰り返しルール
+// until: この日付を超える回は作らない
+// count: 現在の回を含めた残り回数（1 なら今回が最後）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    #[serde(default = "default_interval")]
+    #[validate(range(min = 1, max = 365, message = "Out of range"))]
+    pub interval: i32,
+    pub until: Option<NaiveDate>,
+    #[validate(range(min = 1, message = "Must be positive"))]
+    pub count: Option<i32>,
+    // 繰り返しの起点（最初の回の期日）。各回をここから数えるので、
+    // 月末の丸め（1/31 -> 2/28）が以降の回に持ち越されない。None なら今の期日を起点にする
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<NaiveDate>,
+}
+
+impl Recurrence {
+    // 起点から k 回目の日付
+    fn nth(&self, anchor: NaiveDate, k: i64) -> Option<NaiveDate> {
+        let steps = self.interval as i64 * k;
+        match self.frequency {
+            Frequency::Daily => anchor.checked_add_signed(Duration::days(steps)),
+            Frequency::Weekly => anchor.checked_add_signed(Duration::weeks(steps)),
+            // 短い月では月末に丸める（1/31 -> 2/28 -> 3/31）
+            Frequency::Monthly => {
+                anchor.checked_add_months(Months::new(u32::try_from(steps).ok()?))
+            }
+        }
+    }
+
+    // 起点から date までに経過した回数（date が丸められた回でも少なめに数える）
+    fn elapsed(&self, anchor: NaiveDate, date: NaiveDate) -> i64 {
+        let units = match self.frequency {
+            Frequency::Daily => (date - anchor).num_days(),
+            Frequency::Weekly => (date - anchor).num_weeks(),
+            Frequency::Monthly => {
+                (date.year() - anchor.year()) as i64 * 12 + date.month() as i64
+                    - anchor.month() as i64
+            }
+        };
+        (units / self.interval as i64).max(0)
+    }
+
+    // date の次の回の日付。終了条件に達していれば None
+    pub fn next_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        if self.count.is_some_and(|count| count <= 1) {
+            return None;
+        }
+        let anchor = self.anchor.unwrap_or(date);
+        let k = self.elapsed(anchor, date);
+        // 丸めがあっても date を超える回はこの範囲に必ずある
+        let next = (k..=k + 2)
+            .filter_map(|k| self.nth(anchor, k))
+            .find(|next| *next > date)?;
+        match self.until {
+            Some(until) if next > until => None,
+            _ => Some(next),
+        }
+    }
+
+    // 次の回に引き継ぐルール（残り回数を 1 減らす）
+    pub fn advance(&self) -> Self {
+        Self {
+            count: self.count.map(|count| count - 1),
+            ..self.clone()
+        }
+    }
+
+    // 起点が決まっていなければ date を起点にする
+    pub fn anchored_at(&self, date: NaiveDate) -> Self {
+        Self {
+            anchor: Some(self.anchor.unwrap_or(date)),
+            ..self.clone()
+        }
+    }
+
+    // date 以降の n 回分の日付
+    pub fn occurrences(&self, date: NaiveDate, n: usize) -> Vec<NaiveDate> {
+        let mut accum = vec![];
+        let mut rule = self.anchored_at(date);
+        let mut date = date;
+        while accum.len() < n {
+            match rule.next_after(date) {
+                Some(next) => {
+                    accum.push(next);
+                    rule = rule.advance();
+                    date = next;
+                }
+                None => break,
+            }
+        }
+        accum
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn weekly_with_interval() {
+        let rule = Recurrence {
+            frequency: Frequency::Weekly,
+            interval: 2,
+            until: None,
+            count: None,
+            anchor: None,
+        };
+        assert_eq!(
+            rule.occurrences(date(2023, 1, 2), 3),
+            vec![date(2023, 1, 16), date(2023, 1, 30), date(2023, 2, 13)]
+        );
+    }
+
+    #[test]
+    fn monthly_clamps_end_of_month() {
+        let rule = Recurrence {
+            frequency: Frequency::Monthly,
+            interval: 1,
+            until: None,
+            count: None,
+            anchor: None,
+        };
+        assert_eq!(rule.next_after(date(2023, 1, 31)), Some(date(2023, 2, 28)));
+        // 丸めた日付を起点にせず、1/31 から数え直す
+        assert_eq!(
+            rule.occurrences(date(2023, 1, 31), 4),
+            vec![
+                date(2023, 2, 28),
+                date(2023, 3, 31),
+                date(2023, 4, 30),
+                date(2023, 5, 31)
+            ]
+        );
+        let anchored = rule.anchored_at(date(2023, 1, 31));
+        assert_eq!(
+            anchored.next_after(date(2023, 2, 28)),
+            Some(date(2023, 3, 31))
+        );
+        assert_eq!(
+            anchored.next_after(date(2023, 4, 30)),
+            Some(date(2023, 5, 31))
+        );
+    }
+
+    #[test]
+    fn stops_at_until_and_count() {
+        let until = Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            until: Some(date(2023, 1, 3)),
+            count: None,
+            anchor: None,
+        };
+        assert_eq!(
+            until.occurrences(date(2023, 1, 1), 10),
+            vec![date(2023, 1, 2), date(2023, 1, 3)]
+        );
+
+        let count = Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            until: None,
+            count: Some(3),
+            anchor: None,
+        };
+        assert_eq!(
+            count.occurrences(date(2023, 1, 1), 10),
+            vec![date(2023, 1, 2), date(2023, 1, 3)]
+        );
+        assert_eq!(count.advance().advance().next_after(date(2023, 1, 3)), None);
+    }
+}
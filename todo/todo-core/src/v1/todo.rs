@@ -0,0 +1,215 @@
+use super::{label::Label, recurrence::Recurrence};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TodoEntity {
+    pub id: i32,
+    pub text: String,
+    pub completed: bool,
+    pub due_date: Option<NaiveDate>,
+    pub recurrence: Option<Recurrence>,
+    pub labels: Vec<Label>,
+}
+
+impl TodoEntity {
+    pub fn new(id: i32, text: String, labels: Vec<Label>) -> Self {
+        Self {
+            id,
+            text,
+            completed: false,
+            due_date: None,
+            recurrence: None,
+            labels,
+        }
+    }
+
+    // 繰り返し Todo の次の回を作成するためのペイロード（ラベルは引き継ぐ）
+    pub fn next_occurrence(&self) -> Option<CreateTodo> {
+        // 起点を持たない古いルールは今の期日を起点にして引き継ぐ
+        let recurrence = self.recurrence.as_ref()?.anchored_at(self.due_date?);
+        let due_date = recurrence.next_after(self.due_date?)?;
+        Some(CreateTodo {
+            text: self.text.clone(),
+            labels: self.labels.iter().map(|label| label.id).collect(),
+            due_date: Some(due_date),
+            recurrence: Some(recurrence.advance()),
+        })
+    }
+}
+
+// 一覧・検索で共通の絞り込み条件
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct TodoFilter {
+    pub label: Option<i32>,
+    pub completed: Option<bool>,
+}
+
+impl TodoFilter {
+    pub fn matches(&self, todo: &TodoEntity) -> bool {
+        let label = self
+            .label
+            .is_none_or(|id| todo.labels.iter().any(|label| label.id == id));
+        let completed = self
+            .completed
+            .is_none_or(|completed| todo.completed == completed);
+        label && completed
+    }
+}
+
+// 検索結果。snippet はマッチした語を <mark> で囲んだ text
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TodoSearchResult {
+    #[serde(flatten)]
+    pub todo: TodoEntity,
+    pub rank: f32,
+    pub snippet: String,
+}
+
+// 繰り返しルールは起点となる期日がないと計算できない
+fn validate_recurrence(
+    due_date: Option<NaiveDate>,
+    recurrence: &Option<Recurrence>,
+) -> Result<(), ValidationError> {
+    if recurrence.is_some() && due_date.is_none() {
+        let mut error = ValidationError::new("recurrence");
+        error.message = Some("Recurrence requires due_date".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+fn validate_create_recurrence(payload: &CreateTodo) -> Result<(), ValidationError> {
+    validate_recurrence(payload.due_date, &payload.recurrence)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+#[validate(schema(function = "validate_create_recurrence"))]
+pub struct CreateTodo {
+    #[validate(length(min = 1, message = "Can not be empty"))]
+    #[validate(length(max = 100, message = "Over text length"))]
+    pub text: String,
+    pub labels: Vec<i32>,
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>,
+    #[serde(default)]
+    #[validate]
+    pub recurrence: Option<Recurrence>,
+}
+
+impl CreateTodo {
+    pub fn new(text: String, labels: Vec<i32>) -> Self {
+        Self {
+            text,
+            labels,
+            due_date: None,
+            recurrence: None,
+        }
+    }
+
+    pub fn with_recurrence(self, due_date: NaiveDate, recurrence: Recurrence) -> Self {
+        Self {
+            due_date: Some(due_date),
+            recurrence: Some(recurrence),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Validate)]
+pub struct UpdateTodo {
+    #[validate(length(min = 1, message = "Can not be empty"))]
+    #[validate(length(max = 100, message = "Over text length"))]
+    pub text: Option<String>,
+    pub completed: Option<bool>,
+    pub labels: Option<Vec<i32>>,
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>,
+    #[serde(default)]
+    #[validate]
+    pub recurrence: Option<Recurrence>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::v1::recurrence::Frequency;
+
+    fn weekly() -> Recurrence {
+        Recurrence {
+            frequency: Frequency::Weekly,
+            interval: 1,
+            until: None,
+            count: None,
+            anchor: None,
+        }
+    }
+
+    #[test]
+    fn validate_create_todo() {
+        assert!(CreateTodo::new("text".to_string(), vec![])
+            .validate()
+            .is_ok());
+        assert!(CreateTodo::new(String::new(), vec![]).validate().is_err());
+        assert!(CreateTodo::new("a".repeat(101), vec![]).validate().is_err());
+
+        // 期日のない繰り返しは不可
+        let mut payload = CreateTodo::new("text".to_string(), vec![]);
+        payload.recurrence = Some(weekly());
+        assert!(payload.validate().is_err());
+
+        // 繰り返しルール自体も検証される
+        let date = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+        let payload = CreateTodo::new("text".to_string(), vec![]).with_recurrence(
+            date,
+            Recurrence {
+                interval: 0,
+                ..weekly()
+            },
+        );
+        assert!(payload.validate().is_err());
+    }
+
+    #[test]
+    fn next_occurrence_keeps_labels() {
+        let date = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+        let label = Label {
+            id: 3,
+            name: "chore".to_string(),
+        };
+        let todo = TodoEntity {
+            due_date: Some(date),
+            recurrence: Some(weekly()),
+            ..TodoEntity::new(1, "text".to_string(), vec![label])
+        };
+        assert_eq!(
+            todo.next_occurrence(),
+            Some(
+                CreateTodo::new("text".to_string(), vec![3]).with_recurrence(
+                    NaiveDate::from_ymd_opt(2023, 1, 9).unwrap(),
+                    Recurrence {
+                        anchor: Some(date),
+                        ..weekly()
+                    }
+                )
+            )
+        );
+        assert_eq!(
+            TodoEntity::new(1, "text".to_string(), vec![]).next_occurrence(),
+            None
+        );
+    }
+
+    #[test]
+    fn update_todo_accepts_partial_json() {
+        let payload: UpdateTodo = serde_json::from_str(r#"{"completed":true}"#).unwrap();
+        assert_eq!(
+            payload,
+            UpdateTodo {
+                completed: Some(true),
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,407 @@
+use crate::Post;
+use pulldown_cmark::{html, CowStr, Event as CmarkEvent, Parser, Tag};
+use serde::Serialize;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
+use yew::prelude::*;
+use yew::virtual_dom::VNode;
+
+// actix-web-blog の NewPost / PostChanges と同じ制限
+const TITLE_LENGTH: RangeInclusive<usize> = 1..=100;
+const BODY_LENGTH: RangeInclusive<usize> = 1..=199;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FormErrors {
+    pub title: Option<String>,
+    pub body: Option<String>,
+}
+
+impl FormErrors {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.body.is_none()
+    }
+}
+
+// 送る前に弾いておく。メッセージもサーバーに合わせる
+pub fn validate(title: &str, body: &str) -> FormErrors {
+    FormErrors {
+        title: check_length(title, TITLE_LENGTH, "title must be 1-100 characters"),
+        body: check_length(body, BODY_LENGTH, "body must be 1-199 characters"),
+    }
+}
+
+fn check_length(value: &str, range: RangeInclusive<usize>, message: &str) -> Option<String> {
+    (!range.contains(&value.chars().count())).then(|| message.to_string())
+}
+
+// 本文に書かれた生の HTML や javascript: のリンクは実行させない
+pub fn markdown_to_html(source: &str) -> String {
+    let events = Parser::new(source).map(|event| match event {
+        CmarkEvent::Html(html) => CmarkEvent::Text(html),
+        CmarkEvent::Start(Tag::Link(kind, url, title)) => {
+            CmarkEvent::Start(Tag::Link(kind, safe_url(url), title))
+        }
+        CmarkEvent::Start(Tag::Image(kind, url, title)) => {
+            CmarkEvent::Start(Tag::Image(kind, safe_url(url), title))
+        }
+        event => event,
+    });
+    let mut out = String::new();
+    html::push_html(&mut out, events);
+    out
+}
+
+fn safe_url(url: CowStr) -> CowStr {
+    let scheme = url.trim_start().to_ascii_lowercase();
+    if ["javascript:", "vbscript:", "data:"]
+        .iter()
+        .any(|s| scheme.starts_with(s))
+    {
+        "#".into()
+    } else {
+        url
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct MarkdownProps {
+    pub source: String,
+}
+
+#[function_component(Markdown)]
+pub fn markdown(MarkdownProps { source }: &MarkdownProps) -> Html {
+    let div = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("div").ok())
+        .expect("running in a browser");
+    div.set_class_name("markdown");
+    div.set_inner_html(&markdown_to_html(source));
+    VNode::VRef(div.into())
+}
+
+// 作成は title と body だけ、編集では公開するかも選べる
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PostInput {
+    pub title: String,
+    pub body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<bool>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PostFormProps {
+    pub initial: PostInput,
+    pub submit_label: String,
+    pub on_submit: Callback<PostInput>,
+    pub on_cancel: Callback<()>,
+}
+
+#[function_component(PostForm)]
+pub fn post_form(props: &PostFormProps) -> Html {
+    let title = use_state(|| props.initial.title.clone());
+    let body = use_state(|| props.initial.body.clone());
+    let published = use_state(|| props.initial.published);
+    let preview = use_state(|| false);
+    // 入力途中で赤くならないよう、エラーは一度送ろうとしてから出す
+    let submitted = use_state(|| false);
+    let errors = validate(&title, &body);
+
+    let on_title = {
+        let title = title.clone();
+        Callback::from(move |e: InputEvent| {
+            title.set(e.target_unchecked_into::<HtmlInputElement>().value())
+        })
+    };
+    let on_body = {
+        let body = body.clone();
+        Callback::from(move |e: InputEvent| {
+            body.set(e.target_unchecked_into::<HtmlTextAreaElement>().value())
+        })
+    };
+    let on_published = {
+        let published = published.clone();
+        Callback::from(move |e: Event| {
+            published.set(Some(
+                e.target_unchecked_into::<HtmlInputElement>().checked(),
+            ))
+        })
+    };
+    let on_preview = |show: bool| {
+        let preview = preview.clone();
+        Callback::from(move |_: MouseEvent| preview.set(show))
+    };
+    let on_submit = {
+        let (title, body, published) = (title.clone(), body.clone(), published.clone());
+        let submitted = submitted.clone();
+        let errors = errors.clone();
+        let on_submit = props.on_submit.clone();
+        Callback::from(move |e: FocusEvent| {
+            e.prevent_default();
+            submitted.set(true);
+            if errors.is_empty() {
+                on_submit.emit(PostInput {
+                    title: (*title).clone(),
+                    body: (*body).clone(),
+                    published: *published,
+                });
+            }
+        })
+    };
+    let on_cancel = {
+        let on_cancel = props.on_cancel.clone();
+        Callback::from(move |_: MouseEvent| on_cancel.emit(()))
+    };
+    let error = |message: &Option<String>| match message {
+        Some(message) if *submitted => html! { <span class="error">{ message.clone() }</span> },
+        _ => html! {},
+    };
+
+    html! {
+        <form class="post-form" onsubmit={on_submit}>
+            <p>
+                <input placeholder="title" value={(*title).clone()} oninput={on_title} />
+                <small>{ format!(" {}/{}", title.chars().count(), TITLE_LENGTH.end()) }</small>
+                { error(&errors.title) }
+            </p>
+            <p>
+                <button type="button" disabled={!*preview} onclick={on_preview(false)}>{ "Write" }</button>
+                <button type="button" disabled={*preview} onclick={on_preview(true)}>{ "Preview" }</button>
+            </p>
+            if *preview {
+                <Markdown source={(*body).clone()} />
+            } else {
+                <textarea rows="8" cols="60" placeholder="body (Markdown)" value={(*body).clone()} oninput={on_body} />
+            }
+            <p>
+                <small>{ format!("{}/{}", body.chars().count(), BODY_LENGTH.end()) }</small>
+                { error(&errors.body) }
+            </p>
+            if let Some(checked) = *published {
+                <p>
+                    <label>
+                        <input type="checkbox" checked={checked} onchange={on_published} />
+                        { " published" }
+                    </label>
+                </p>
+            }
+            <button type="submit" disabled={*submitted && !errors.is_empty()}>{ props.submit_label.clone() }</button>
+            <button type="button" onclick={on_cancel}>{ "Cancel" }</button>
+        </form>
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub post: Post,
+    // サーバーの返事を待っている間は true。作成中の記事は仮の負の id を持つ
+    pub pending: bool,
+}
+
+// 一覧はサーバーの返事を待たずに書き換え、失敗したら元に戻す
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PostListState {
+    pub entries: Vec<Entry>,
+    pub error: Option<String>,
+}
+
+pub enum ListAction {
+    Insert(Post),
+    Update(Post),
+    // id の記事をサーバーの結果（失敗したときは元の記事）で確定させる
+    Confirm { id: i32, post: Post },
+    Remove(i32),
+    // 削除に失敗したら元の位置に戻す
+    Restore { index: usize, post: Post },
+    Fail(String),
+    DismissError,
+}
+
+impl PostListState {
+    pub fn new(posts: Vec<Post>) -> Self {
+        Self {
+            entries: posts
+                .into_iter()
+                .map(|post| Entry {
+                    post,
+                    pending: false,
+                })
+                .collect(),
+            error: None,
+        }
+    }
+
+    pub fn position(&self, id: i32) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.post.id == id)
+    }
+}
+
+impl Reducible for PostListState {
+    type Action = ListAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut state = (*self).clone();
+        match action {
+            ListAction::Insert(post) => {
+                state.error = None;
+                state.entries.push(Entry {
+                    post,
+                    pending: true,
+                });
+            }
+            ListAction::Update(post) => {
+                state.error = None;
+                if let Some(i) = state.position(post.id) {
+                    state.entries[i] = Entry {
+                        post,
+                        pending: true,
+                    };
+                }
+            }
+            ListAction::Confirm { id, post } => {
+                if let Some(i) = state.position(id) {
+                    state.entries[i] = Entry {
+                        post,
+                        pending: false,
+                    };
+                }
+            }
+            ListAction::Remove(id) => {
+                state.error = None;
+                state.entries.retain(|entry| entry.post.id != id);
+            }
+            ListAction::Restore { index, post } => {
+                let index = index.min(state.entries.len());
+                state.entries.insert(
+                    index,
+                    Entry {
+                        post,
+                        pending: false,
+                    },
+                );
+            }
+            ListAction::Fail(message) => state.error = Some(message),
+            ListAction::DismissError => state.error = None,
+        }
+        state.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(id: i32, title: &str) -> Post {
+        Post {
+            id,
+            title: title.to_string(),
+            body: "body".to_string(),
+            published: false,
+        }
+    }
+
+    fn reduce(state: PostListState, actions: Vec<ListAction>) -> PostListState {
+        let mut state = Rc::new(state);
+        for action in actions {
+            state = state.reduce(action);
+        }
+        (*state).clone()
+    }
+
+    fn titles(state: &PostListState) -> Vec<(&str, bool)> {
+        state
+            .entries
+            .iter()
+            .map(|entry| (entry.post.title.as_str(), entry.pending))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate("title", "body").is_empty());
+        assert!(validate(&"あ".repeat(100), &"b".repeat(199)).is_empty());
+        let errors = validate("", &"b".repeat(200));
+        assert_eq!(
+            Some("title must be 1-100 characters".to_string()),
+            errors.title
+        );
+        assert_eq!(
+            Some("body must be 1-199 characters".to_string()),
+            errors.body
+        );
+        assert!(validate(&"t".repeat(101), "body").title.is_some());
+    }
+
+    #[test]
+    fn test_markdown() {
+        assert_eq!(
+            "<p><strong>bold</strong> <a href=\"https://example.com\">link</a></p>\n",
+            markdown_to_html("**bold** [link](https://example.com)")
+        );
+        assert_eq!(
+            "&lt;script&gt;alert(1)&lt;/script&gt;",
+            markdown_to_html("<script>alert(1)</script>")
+        );
+        assert_eq!(
+            "<p>a &lt;img src=x onerror=alert(1)&gt;</p>\n",
+            markdown_to_html("a <img src=x onerror=alert(1)>")
+        );
+        assert_eq!(
+            "<p><a href=\"#\">x</a></p>\n",
+            markdown_to_html("[x]( JavaScript:alert(1))")
+        );
+    }
+
+    #[test]
+    fn test_create() {
+        let state = PostListState::new(vec![post(1, "first")]);
+        let state = reduce(state, vec![ListAction::Insert(post(-1, "new"))]);
+        assert_eq!(vec![("first", false), ("new", true)], titles(&state));
+
+        let created = reduce(
+            state.clone(),
+            vec![ListAction::Confirm {
+                id: -1,
+                post: post(2, "new"),
+            }],
+        );
+        assert_eq!(vec![("first", false), ("new", false)], titles(&created));
+        assert_eq!(Some(1), created.position(2));
+
+        let failed = reduce(
+            state,
+            vec![
+                ListAction::Remove(-1),
+                ListAction::Fail("Forbidden".to_string()),
+            ],
+        );
+        assert_eq!(vec![("first", false)], titles(&failed));
+        assert_eq!(Some("Forbidden".to_string()), failed.error);
+    }
+
+    #[test]
+    fn test_update_and_delete() {
+        let state = PostListState::new(vec![post(1, "first"), post(2, "second")]);
+        let state = reduce(state, vec![ListAction::Update(post(1, "edited"))]);
+        assert_eq!(vec![("edited", true), ("second", false)], titles(&state));
+        let reverted = reduce(
+            state,
+            vec![ListAction::Confirm {
+                id: 1,
+                post: post(1, "first"),
+            }],
+        );
+        assert_eq!(vec![("first", false), ("second", false)], titles(&reverted));
+
+        let deleted = reduce(reverted, vec![ListAction::Remove(1)]);
+        assert_eq!(vec![("second", false)], titles(&deleted));
+        let restored = reduce(
+            deleted,
+            vec![ListAction::Restore {
+                index: 0,
+                post: post(1, "first"),
+            }],
+        );
+        assert_eq!(vec![("first", false), ("second", false)], titles(&restored));
+    }
+}
@@ -0,0 +1,200 @@
+use gloo_net::http::{Request, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Deref;
+use std::rc::Rc;
+use yew::prelude::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FetchState<T> {
+    Idle,
+    Loading,
+    Loaded(T),
+    Failed(FetchError),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FetchError {
+    // サーバーに届かなかった（オフライン、CORS など）
+    Network(String),
+    NotFound,
+    // トークンが無いか期限切れ
+    Unauthorized,
+    // サーバーが理由を付けて断った（403、409、422 など）
+    Rejected { status: u16, message: String },
+    Status(u16),
+    // 返ってきた JSON が期待した形ではない
+    Decode(String),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Network(e) => write!(f, "could not reach the server: {}", e),
+            FetchError::NotFound => write!(f, "not found"),
+            FetchError::Unauthorized => write!(f, "please log in again"),
+            FetchError::Rejected { message, .. } => write!(f, "{}", message),
+            FetchError::Status(status) => write!(f, "the server returned {}", status),
+            FetchError::Decode(e) => write!(f, "unexpected response: {}", e),
+        }
+    }
+}
+
+// actix-web-blog がエラーのときに返す JSON
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+    #[serde(default)]
+    fields: BTreeMap<String, Vec<String>>,
+}
+
+impl ErrorBody {
+    // 入力エラーはどの項目の問題かが分かるよう項目ごとのメッセージも並べる
+    fn into_message(self) -> String {
+        if self.fields.is_empty() {
+            return self.message;
+        }
+        let fields: Vec<String> = self.fields.into_values().flatten().collect();
+        format!("{}: {}", self.message, fields.join(", "))
+    }
+}
+
+async fn send(request: Request) -> Result<Response, FetchError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| FetchError::Network(e.to_string()))?;
+    match response.status() {
+        _ if response.ok() => Ok(response),
+        401 => Err(FetchError::Unauthorized),
+        404 => Err(FetchError::NotFound),
+        status => match response.json::<ErrorBody>().await {
+            Ok(body) => Err(FetchError::Rejected {
+                status,
+                message: body.into_message(),
+            }),
+            Err(_) => Err(FetchError::Status(status)),
+        },
+    }
+}
+
+async fn decode<T: DeserializeOwned>(response: Response) -> Result<T, FetchError> {
+    response
+        .json()
+        .await
+        .map_err(|e| FetchError::Decode(e.to_string()))
+}
+
+pub async fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, FetchError> {
+    decode(send(Request::get(url)).await?).await
+}
+
+// ログインで受け取ったトークンを付ける
+pub fn authorized(request: Request, token: &str) -> Request {
+    request.header("Authorization", &format!("Bearer {}", token))
+}
+
+pub async fn send_json<B: Serialize, T: DeserializeOwned>(
+    request: Request,
+    body: &B,
+) -> Result<T, FetchError> {
+    let request = request
+        .json(body)
+        .map_err(|e| FetchError::Network(e.to_string()))?;
+    decode(send(request).await?).await
+}
+
+// 204 のように本文を返さないリクエスト
+pub async fn send_empty(request: Request) -> Result<(), FetchError> {
+    send(request).await.map(|_| ())
+}
+
+pub struct UseFetchHandle<T> {
+    state: UseStateHandle<FetchState<T>>,
+    attempt: UseStateHandle<u32>,
+}
+
+impl<T> UseFetchHandle<T> {
+    // もう一度取りにいく。エラー表示の再試行ボタンから呼ぶ
+    pub fn retry(&self) -> Callback<MouseEvent> {
+        let attempt = self.attempt.clone();
+        Callback::from(move |_| attempt.set(*attempt + 1))
+    }
+}
+
+impl<T> Deref for UseFetchHandle<T> {
+    type Target = FetchState<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
+
+// url の JSON を取ってきて状態として持つ。url が変わるか retry されるたびに取り直す
+pub fn use_fetch<T: DeserializeOwned + 'static>(url: String) -> UseFetchHandle<T> {
+    let state = use_state(|| FetchState::Idle);
+    let attempt = use_state(|| 0u32);
+    {
+        let state = state.clone();
+        use_effect_with_deps(
+            move |(url, _)| {
+                // 取得中に url が変わったら、古いレスポンスで上書きしないよう捨てる
+                let cancelled = Rc::new(Cell::new(false));
+                state.set(FetchState::Loading);
+                {
+                    let cancelled = cancelled.clone();
+                    let url = url.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let fetched = match get_json(&url).await {
+                            Ok(data) => FetchState::Loaded(data),
+                            Err(e) => FetchState::Failed(e),
+                        };
+                        if !cancelled.get() {
+                            state.set(fetched);
+                        }
+                    });
+                }
+                move || cancelled.set(true)
+            },
+            (url, *attempt),
+        );
+    }
+
+    UseFetchHandle { state, attempt }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_messages() {
+        assert_eq!("not found", FetchError::NotFound.to_string());
+        assert_eq!(
+            "the server returned 503",
+            FetchError::Status(503).to_string()
+        );
+        assert_eq!(
+            "could not reach the server: offline",
+            FetchError::Network("offline".to_string()).to_string()
+        );
+    }
+
+    #[test]
+    fn test_error_body_message() {
+        let body: ErrorBody = serde_json::from_str(
+            r#"{"error":"validation","message":"Validation failed","fields":{"body":["body must be 1-199 characters"],"title":["title must be 1-100 characters"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            "Validation failed: body must be 1-199 characters, title must be 1-100 characters",
+            body.into_message()
+        );
+        let body: ErrorBody =
+            serde_json::from_str(r#"{"error":"forbidden","message":"Forbidden"}"#).unwrap();
+        assert_eq!("Forbidden", body.into_message());
+    }
+}
@@ -1,8 +1,19 @@
+// yew 0.19 の html! が展開するコードはコンポーネントごとにこの 2 つに引っかかる
+#![allow(clippy::unnecessary_operation, clippy::let_unit_value)]
+
+mod editor;
+mod fetch;
+mod session;
+
+use editor::{Entry, ListAction, Markdown, PostForm, PostInput, PostListState};
+use fetch::{authorized, send_empty, send_json, use_fetch, FetchError, FetchState};
 use gloo_net::http::Request;
 use serde::Deserialize;
+use session::{use_session, Login, Session, SessionProvider};
 use yew::prelude::*;
+use yew_router::prelude::*;
 
-#[derive(Deserialize, Clone, PartialEq)]
+#[derive(Deserialize, Clone, Debug, PartialEq)]
 pub struct Post {
     id: i32,
     title: String,
@@ -10,89 +21,366 @@ pub struct Post {
     published: bool,
 }
 
+#[derive(Clone, Routable, PartialEq)]
+enum Route {
+    #[at("/")]
+    Home,
+    #[at("/posts/:id")]
+    Post { id: i32 },
+    #[at("/login")]
+    Login,
+    #[not_found]
+    #[at("/404")]
+    NotFound,
+}
+
 #[derive(Properties, PartialEq)]
 struct PostsListProps {
-    posts: Vec<Post>,
-    on_click: Callback<Post>,
+    entries: Vec<Entry>,
+    // ログインしていれば編集と削除のボタンを出す
+    editable: bool,
+    editing: Option<i32>,
+    on_edit: Callback<Option<i32>>,
+    on_update: Callback<(Post, PostInput)>,
+    on_delete: Callback<Post>,
 }
 
 #[function_component(PostList)]
-fn posts_list(PostsListProps { posts, on_click }: &PostsListProps) -> Html {
-    posts
+fn posts_list(props: &PostsListProps) -> Html {
+    props
+        .entries
         .iter()
-        .map(|post| {
-            let on_post_select = {
-                let on_click = on_click.clone();
+        .map(|Entry { post, pending }| {
+            if props.editing == Some(post.id) {
+                let on_submit = {
+                    let on_update = props.on_update.clone();
+                    let post = post.clone();
+                    Callback::from(move |input| on_update.emit((post.clone(), input)))
+                };
+                let on_cancel = props.on_edit.reform(|_| None);
+                let initial = PostInput {
+                    title: post.title.clone(),
+                    body: post.body.clone(),
+                    published: Some(post.published),
+                };
+                return html! {
+                    <PostForm key={post.id} {initial} submit_label="Save" {on_submit} {on_cancel} />
+                };
+            }
+            if *pending {
+                return html! {
+                    <p class="pending">{ post.title.clone() }<small>{ " saving..." }</small></p>
+                };
+            }
+            let id = post.id;
+            let on_edit = props.on_edit.reform(move |_: MouseEvent| Some(id));
+            let on_delete = {
                 let post = post.clone();
-                Callback::from(move |_| on_click.emit(post.clone()))
+                props.on_delete.reform(move |_: MouseEvent| post.clone())
             };
             html! {
-                <p onclick={on_post_select}>{
-                    format!("{} {}",
-                    post.id, post.title)
-                }</p>
+                <p>
+                    <Link<Route> to={Route::Post { id: post.id }}>{
+                        format!("{} {}",
+                        post.id, post.title)
+                    }</Link<Route>>
+                    if !post.published {
+                        <small>{ " (draft)" }</small>
+                    }
+                    if props.editable {
+                        { " " }
+                        <button onclick={on_edit}>{ "Edit" }</button>
+                        <button onclick={on_delete}>{ "Delete" }</button>
+                    }
+                </p>
             }
         })
         .collect()
 }
 
+// 失敗したら理由を一覧の上に出す。トークンが切れていたらログアウトさせる
+fn report(list: &UseReducerHandle<PostListState>, session: &Session, error: FetchError) {
+    if error == FetchError::Unauthorized {
+        session.log_out();
+    }
+    list.dispatch(ListAction::Fail(error.to_string()));
+}
+
+fn confirm(message: &str) -> bool {
+    web_sys::window()
+        .and_then(|window| window.confirm_with_message(message).ok())
+        .unwrap_or(false)
+}
+
+#[derive(Properties, PartialEq)]
+struct PostsProps {
+    posts: Vec<Post>,
+}
+
+// 取得した一覧を元に、作成・編集・削除をサーバーの返事を待たずに反映する
+#[function_component(Posts)]
+fn posts(PostsProps { posts }: &PostsProps) -> Html {
+    let list = use_reducer({
+        let posts = posts.clone();
+        move || PostListState::new(posts)
+    });
+    let session = use_session();
+    let creating = use_state(|| false);
+    let editing = use_state(|| None);
+    // 作成中の記事には本物と被らない負の仮 id を振る
+    let next_temp_id = use_mut_ref(|| 0);
+
+    let on_create = {
+        let (list, session, creating) = (list.clone(), session.clone(), creating.clone());
+        Callback::from(move |input: PostInput| {
+            creating.set(false);
+            let token = match session.token() {
+                Some(token) => token,
+                None => return,
+            };
+            let temp_id = {
+                let mut next = next_temp_id.borrow_mut();
+                *next -= 1;
+                *next
+            };
+            list.dispatch(ListAction::Insert(Post {
+                id: temp_id,
+                title: input.title.clone(),
+                body: input.body.clone(),
+                published: false,
+            }));
+            let (list, session) = (list.clone(), session.clone());
+            wasm_bindgen_futures::spawn_local(async move {
+                let request = authorized(Request::post("/posts"), &token);
+                match send_json::<_, Post>(request, &input).await {
+                    Ok(post) => list.dispatch(ListAction::Confirm { id: temp_id, post }),
+                    Err(e) => {
+                        list.dispatch(ListAction::Remove(temp_id));
+                        report(&list, &session, e);
+                    }
+                }
+            });
+        })
+    };
+    let on_update = {
+        let (list, session, editing) = (list.clone(), session.clone(), editing.clone());
+        Callback::from(move |(previous, input): (Post, PostInput)| {
+            editing.set(None);
+            let token = match session.token() {
+                Some(token) => token,
+                None => return,
+            };
+            list.dispatch(ListAction::Update(Post {
+                id: previous.id,
+                title: input.title.clone(),
+                body: input.body.clone(),
+                published: input.published.unwrap_or(previous.published),
+            }));
+            let (list, session) = (list.clone(), session.clone());
+            wasm_bindgen_futures::spawn_local(async move {
+                let id = previous.id;
+                let request = authorized(Request::patch(&format!("/posts/{}", id)), &token);
+                match send_json::<_, Post>(request, &input).await {
+                    Ok(post) => list.dispatch(ListAction::Confirm { id, post }),
+                    Err(e) => {
+                        list.dispatch(ListAction::Confirm { id, post: previous });
+                        report(&list, &session, e);
+                    }
+                }
+            });
+        })
+    };
+    let on_delete = {
+        let (list, session) = (list.clone(), session.clone());
+        Callback::from(move |post: Post| {
+            let token = match session.token() {
+                Some(token) => token,
+                None => return,
+            };
+            if !confirm(&format!("Delete \"{}\"?", post.title)) {
+                return;
+            }
+            let index = list.position(post.id).unwrap_or(0);
+            list.dispatch(ListAction::Remove(post.id));
+            let (list, session) = (list.clone(), session.clone());
+            wasm_bindgen_futures::spawn_local(async move {
+                let request = authorized(Request::delete(&format!("/posts/{}", post.id)), &token);
+                if let Err(e) = send_empty(request).await {
+                    list.dispatch(ListAction::Restore { index, post });
+                    report(&list, &session, e);
+                }
+            });
+        })
+    };
+    let on_new = {
+        let creating = creating.clone();
+        Callback::from(move |_: MouseEvent| creating.set(true))
+    };
+    let on_cancel_new = {
+        let creating = creating.clone();
+        Callback::from(move |_| creating.set(false))
+    };
+    let on_dismiss = {
+        let list = list.clone();
+        Callback::from(move |_: MouseEvent| list.dispatch(ListAction::DismissError))
+    };
+    let on_edit = {
+        let editing = editing.clone();
+        Callback::from(move |id| editing.set(id))
+    };
+    let editable = session.token().is_some();
+    let new_post = PostInput {
+        title: String::new(),
+        body: String::new(),
+        published: None,
+    };
+
+    html! {
+        <>
+            if let Some(error) = &list.error {
+                <div class="error" role="alert">
+                    <p>{ format!("Failed to save: {}", error) }</p>
+                    <button onclick={on_dismiss}>{ "Dismiss" }</button>
+                </div>
+            }
+            if editable {
+                if *creating {
+                    <PostForm initial={new_post} submit_label="Create" on_submit={on_create} on_cancel={on_cancel_new} />
+                } else {
+                    <button onclick={on_new}>{ "New post" }</button>
+                }
+            }
+            if list.entries.is_empty() {
+                <p class="empty">{ "No posts yet." }</p>
+            } else {
+                <PostList
+                    entries={list.entries.clone()}
+                    {editable}
+                    editing={*editing}
+                    {on_edit}
+                    {on_update}
+                    {on_delete}
+                />
+            }
+        </>
+    }
+}
+
+#[function_component(Spinner)]
+fn spinner() -> Html {
+    html! {
+        <div class="spinner" role="status" aria-label="loading"></div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct ErrorViewProps {
+    error: FetchError,
+    on_retry: Callback<MouseEvent>,
+}
+
+#[function_component(ErrorView)]
+fn error_view(ErrorViewProps { error, on_retry }: &ErrorViewProps) -> Html {
+    html! {
+        <div class="error" role="alert">
+            <p>{ format!("Failed to load: {}", error) }</p>
+            <button onclick={on_retry.clone()}>{ "Retry" }</button>
+        </div>
+    }
+}
+
+#[function_component(Home)]
+fn home() -> Html {
+    let posts = use_fetch::<Vec<Post>>("/posts".to_string());
+    let content = match &*posts {
+        FetchState::Idle | FetchState::Loading => html! { <Spinner /> },
+        FetchState::Failed(error) => html! {
+            <ErrorView error={error.clone()} on_retry={posts.retry()} />
+        },
+        FetchState::Loaded(posts) => html! { <Posts posts={posts.clone()} /> },
+    };
+
+    html! {
+        <div>
+            <h3>{ "posts list" }</h3>
+            { content }
+        </div>
+    }
+}
+
 #[derive(Clone, Properties, PartialEq)]
 struct PostDetailProps {
-    post: Post,
+    id: i32,
 }
 
+// URL から直接開かれても表示できるよう、一覧を経由せず記事を取りにいく
 #[function_component(PostDetail)]
-fn post_detail(PostDetailProps { post }: &PostDetailProps) -> Html {
+fn post_detail(PostDetailProps { id }: &PostDetailProps) -> Html {
+    let post = use_fetch::<Post>(format!("/posts/{}", id));
+    match &*post {
+        FetchState::Idle | FetchState::Loading => html! { <Spinner /> },
+        FetchState::Failed(FetchError::NotFound) => html! { <NotFound /> },
+        FetchState::Failed(error) => html! {
+            <ErrorView error={error.clone()} on_retry={post.retry()} />
+        },
+        FetchState::Loaded(post) => html! {
+            <div>
+                <h3>{ post.title.clone() }</h3>
+                <Markdown source={post.body.clone()} />
+                <Link<Route> to={Route::Home}>{ "back to posts" }</Link<Route>>
+            </div>
+        },
+    }
+}
+
+#[function_component(NotFound)]
+fn not_found() -> Html {
     html! {
         <div>
-            <h3>{ post.title.clone() }</h3>
-            <p>{ post.body.clone() }</p>
+            <h3>{ "404 Not Found" }</h3>
+            <Link<Route> to={Route::Home}>{ "back to posts" }</Link<Route>>
         </div>
     }
 }
 
-#[function_component(App)]
-fn app() -> Html {
-    let posts = use_state(|| vec![]);
-    {
-        let posts = posts.clone();
-        use_effect_with_deps(
-            move |_| {
-                wasm_bindgen_futures::spawn_local(async move {
-                    let fetched_posts: Vec<Post> = Request::get("/posts")
-                        .send()
-                        .await
-                        .unwrap()
-                        .json()
-                        .await
-                        .unwrap();
-                    posts.set(fetched_posts);
-                });
-                || ()
-            },
-            (),
-        );
+fn switch(route: &Route) -> Html {
+    match route {
+        Route::Home => html! { <Home /> },
+        Route::Post { id } => html! { <PostDetail id={*id} /> },
+        Route::Login => html! { <Login /> },
+        Route::NotFound => html! { <NotFound /> },
     }
-    let selected_post = use_state(|| None);
-    let on_post_select = {
-        let selected_post = selected_post.clone();
-        Callback::from(move |post: Post| selected_post.set(Some(post)))
+}
+
+#[function_component(Header)]
+fn header() -> Html {
+    let session = use_session();
+    let on_logout = {
+        let session = session.clone();
+        Callback::from(move |_: MouseEvent| session.log_out())
     };
-    let detail = selected_post.as_ref().map(|post| {
-        html! {
-            <PostDetail post={post.clone()} />
-        }
-    });
 
     html! {
-        <>
-            <h1>{ "My blog" }</h1>
-            <div>
-                <h3>{ "posts list" }</h3>
-                <PostList posts={(*posts).clone()} on_click={on_post_select.clone()} />
-            </div>
-            { for detail }
-        </>
+        <header>
+            <h1><Link<Route> to={Route::Home}>{ "My blog" }</Link<Route>></h1>
+            if session.token().is_some() {
+                <button onclick={on_logout}>{ "Log out" }</button>
+            } else {
+                <Link<Route> to={Route::Login}>{ "Log in" }</Link<Route>>
+            }
+        </header>
+    }
+}
+
+#[function_component(App)]
+fn app() -> Html {
+    html! {
+        <SessionProvider>
+            <BrowserRouter>
+                <Header />
+                <Switch<Route> render={Switch::render(switch)} />
+            </BrowserRouter>
+        </SessionProvider>
     }
 }
 
@@ -0,0 +1,129 @@
+use crate::fetch::{send_json, FetchError};
+use crate::Route;
+use gloo_net::http::Request;
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+// 再読み込みしてもログインが続くよう、トークンは localStorage に置く
+const TOKEN_KEY: &str = "yew-blog.token";
+
+#[derive(Clone, PartialEq)]
+pub struct Session(UseStateHandle<Option<String>>);
+
+impl Session {
+    pub fn token(&self) -> Option<String> {
+        (*self.0).clone()
+    }
+
+    pub fn log_in(&self, token: String) {
+        // 保存できなくてもこのタブの間はログインしたままにする
+        let _ = LocalStorage::set(TOKEN_KEY, &token);
+        self.0.set(Some(token));
+    }
+
+    pub fn log_out(&self) {
+        LocalStorage::delete(TOKEN_KEY);
+        self.0.set(None);
+    }
+}
+
+pub fn use_session() -> Session {
+    use_context::<Session>().expect("Session is provided by SessionProvider")
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SessionProviderProps {
+    pub children: Children,
+}
+
+#[function_component(SessionProvider)]
+pub fn session_provider(SessionProviderProps { children }: &SessionProviderProps) -> Html {
+    let token = use_state(|| LocalStorage::get::<String>(TOKEN_KEY).ok());
+    html! {
+        <ContextProvider<Session> context={Session(token)}>
+            { children.clone() }
+        </ContextProvider<Session>>
+    }
+}
+
+#[derive(Serialize)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    token: String,
+}
+
+#[function_component(Login)]
+pub fn login() -> Html {
+    let session = use_session();
+    let history = use_history();
+    let username = use_state(String::new);
+    let password = use_state(String::new);
+    let error = use_state(|| None);
+
+    let on_username = {
+        let username = username.clone();
+        Callback::from(move |e: InputEvent| {
+            username.set(e.target_unchecked_into::<HtmlInputElement>().value())
+        })
+    };
+    let on_password = {
+        let password = password.clone();
+        Callback::from(move |e: InputEvent| {
+            password.set(e.target_unchecked_into::<HtmlInputElement>().value())
+        })
+    };
+    let on_submit = {
+        let username = username.clone();
+        let password = password.clone();
+        let error = error.clone();
+        Callback::from(move |e: FocusEvent| {
+            e.prevent_default();
+            let body = LoginRequest {
+                username: (*username).clone(),
+                password: (*password).clone(),
+            };
+            let session = session.clone();
+            let history = history.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match send_json::<_, LoginResponse>(Request::post("/login"), &body).await {
+                    Ok(response) => {
+                        session.log_in(response.token);
+                        if let Some(history) = history {
+                            history.push(Route::Home);
+                        }
+                    }
+                    // ユーザー名とパスワードのどちらが違うかはサーバーも区別しない
+                    Err(FetchError::Unauthorized) => {
+                        error.set(Some("wrong username or password".to_string()))
+                    }
+                    Err(e) => error.set(Some(e.to_string())),
+                }
+            });
+        })
+    };
+
+    html! {
+        <form onsubmit={on_submit}>
+            <h3>{ "Log in" }</h3>
+            <p>
+                <input placeholder="username" value={(*username).clone()} oninput={on_username} />
+            </p>
+            <p>
+                <input type="password" placeholder="password" value={(*password).clone()} oninput={on_password} />
+            </p>
+            if let Some(error) = &*error {
+                <p class="error" role="alert">{ error.clone() }</p>
+            }
+            <button type="submit">{ "Log in" }</button>
+        </form>
+    }
+}